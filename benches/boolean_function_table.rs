@@ -0,0 +1,48 @@
+//! Compare the integer fast path of `BmaNetwork::build_function_table` for Boolean variables
+//! with the general `Decimal` evaluation on a variable with 12 Boolean regulators.
+//!
+//! Run using `cargo bench --bench boolean_function_table`.
+
+use biodivine_lib_io_bma::update_function::BmaUpdateFunction;
+use biodivine_lib_io_bma::{BmaNetwork, BmaRelationship, BmaVariable};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+/// The function of the target variable, which only uses integer operations.
+const FORMULA: &str = "max(var(1), var(2), var(3), var(4)) - min(var(5), var(6), var(7)) \
+    + var(8) * var(9) - abs(var(10) - var(11)) + var(12)";
+
+fn make_network(formula: &str) -> BmaNetwork {
+    let mut variables = (1..=12)
+        .map(|id| BmaVariable::new_boolean(id, &format!("v_{id}"), None))
+        .collect::<Vec<_>>();
+    let function = BmaUpdateFunction::try_from(formula).unwrap();
+    variables.push(BmaVariable::new_boolean(0, "target", Some(function)));
+    let relationships = (1..=12)
+        .map(|id| BmaRelationship::new_activator(id, id, 0))
+        .collect();
+    BmaNetwork::new(variables, relationships)
+}
+
+fn boolean_function_table(c: &mut Criterion) {
+    let fast = make_network(FORMULA);
+    // Division disables the integer fast path, so the same function (divided by one)
+    // is evaluated using the general `Decimal` path.
+    let general = make_network(&format!("({FORMULA}) / 1"));
+    assert_eq!(
+        fast.build_function_table(0).unwrap(),
+        general.build_function_table(0).unwrap()
+    );
+
+    let mut group = c.benchmark_group("boolean_function_table");
+    group.bench_function("integer_fast_path", |b| {
+        b.iter(|| black_box(&fast).build_function_table(0).unwrap());
+    });
+    group.bench_function("general_path", |b| {
+        b.iter(|| black_box(&general).build_function_table(0).unwrap());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, boolean_function_table);
+criterion_main!(benches);
@@ -1,5 +1,4 @@
 use biodivine_lib_io_bma::BmaModel;
-use biodivine_lib_param_bn::BooleanNetwork;
 use std::io::{self, Read};
 
 fn main() {
@@ -9,11 +8,9 @@ fn main() {
         .read_to_string(&mut input)
         .expect("Failed to read from stdin");
 
-    // Parse AEON format into BooleanNetwork
-    let bn = BooleanNetwork::try_from(input.as_str()).expect("Failed to parse AEON format");
-
-    // Convert BooleanNetwork to BmaModel
-    let bma_model = BmaModel::try_from(&bn).expect("Failed to convert BooleanNetwork to BmaModel");
+    // Parse AEON format and convert it to BmaModel
+    let bma_model =
+        BmaModel::from_aeon_string(&input, "").expect("Failed to convert AEON model to BmaModel");
 
     // Output as BMA JSON format
     let output = bma_model
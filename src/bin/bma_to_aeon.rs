@@ -1,5 +1,4 @@
 use biodivine_lib_io_bma::BmaModel;
-use biodivine_lib_param_bn::BooleanNetwork;
 use std::io::{self, Read};
 
 fn main() {
@@ -14,11 +13,10 @@ fn main() {
         .or_else(|_| BmaModel::from_xml_string(&input))
         .expect("Failed to parse BMA format (tried both JSON and XML)");
 
-    // Convert BmaModel to BooleanNetwork
-    let bn = BooleanNetwork::try_from(&bma_model)
-        .and_then(|bn| bn.infer_valid_graph().map_err(|e| anyhow::anyhow!("{}", e)))
+    // Convert BmaModel to the AEON format
+    let aeon = bma_model
+        .to_aeon_string()
         .expect("Failed to convert BMA model to BooleanNetwork");
 
-    // Output as AEON format (BooleanNetwork implements Display)
-    println!("{}", bn);
+    println!("{}", aeon);
 }
@@ -12,9 +12,17 @@ pub(crate) mod serde;
 /// Structures and utilities for parsing/evaluating update functions.
 pub mod update_function;
 
+pub use crate::model::bma_model::builder::{BmaBuilderError, BmaModelBuilder};
+#[cfg(feature = "bn-conversion")]
+pub use crate::model::bma_model::into_aeon::NamingScheme;
+pub use crate::model::bma_model::io::{BmaIoError, Format};
+pub use crate::model::bma_model::source_spans::{BmaSourceSpans, SpannedBmaModelError};
+pub use crate::model::bma_model::statistics::ModelStats;
 pub use crate::model::bma_model::{BmaModel, BmaModelError};
 pub use crate::model::bma_network::{BmaNetwork, BmaNetworkError};
-pub use crate::model::bma_relationship::{BmaRelationship, BmaRelationshipError, RelationshipType};
+pub use crate::model::bma_relationship::{
+    BmaRelationship, BmaRelationshipError, ParseRelationshipTypeError, RelationshipType,
+};
 pub use crate::model::bma_variable::{BmaVariable, BmaVariableError};
 pub use crate::model::layout::bma_layout::{BmaLayout, BmaLayoutError};
 pub use crate::model::layout::bma_layout_container::{BmaLayoutContainer, BmaLayoutContainerError};
@@ -24,7 +32,13 @@ pub use crate::model::layout::bma_layout_variable::{
 
 mod validation;
 pub use validation::{
-    ContextualValidation, ErrorReporter, ReporterWrapper, Validation, VecReporter,
+    CallbackReporter, ContextualValidation, ErrorReporter, LimitReporter, ReporterWrapper,
+    Validation, VecReporter,
 };
 
 pub(crate) mod utils;
+
+/// `String`-in/`String`-out wrappers that can be exposed through WebAssembly (or other FFI)
+/// bindings without marshalling any of the complex types of this crate.
+#[cfg(feature = "wasm")]
+pub mod wasm;
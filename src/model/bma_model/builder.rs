@@ -0,0 +1,249 @@
+use crate::update_function::BmaUpdateFunction;
+use crate::{
+    BmaLayout, BmaModel, BmaModelError, BmaNetwork, BmaRelationship, BmaVariable, RelationshipType,
+    Validation,
+};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// A fluent builder for [`BmaModel`] instances.
+///
+/// The builder only creates the network part of the model (the layout is empty). Relationship
+/// IDs are assigned automatically (starting from `0`), unless they are given explicitly using
+/// [`BmaModelBuilder::add_relationship_with_id`]. Update functions are parsed once the model is
+/// built, so they can reference variables by ID or by name, regardless of the order in which
+/// the variables are added.
+///
+/// ```rust
+/// # use biodivine_lib_io_bma::{BmaModelBuilder, RelationshipType};
+/// let model = BmaModelBuilder::new("example")
+///     .add_variable(1, "a", (0, 1))
+///     .add_variable(2, "b", (0, 1))
+///     .add_relationship(1, 2, RelationshipType::Activator)
+///     .add_relationship(2, 1, RelationshipType::Activator)
+///     .set_formula(2, "var(a)")
+///     .build()
+///     .unwrap();
+/// assert_eq!(model.network.variables.len(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BmaModelBuilder {
+    network: BmaNetwork,
+    formulas: Vec<(u32, String)>,
+    metadata: BTreeMap<String, String>,
+}
+
+impl BmaModelBuilder {
+    /// Create a new builder for a model with the given `name`.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        BmaModelBuilder {
+            network: BmaNetwork {
+                name: name.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Add a new variable with the given `id`, `name`, and `range`. The variable uses
+    /// the default update function unless [`BmaModelBuilder::set_formula`] is called.
+    #[must_use]
+    pub fn add_variable(mut self, id: u32, name: &str, range: (u32, u32)) -> Self {
+        let variable = BmaVariable::new(id, name, range, None);
+        self.network.variables.push(variable);
+        self
+    }
+
+    /// Add a new relationship of the given type between two variables. The relationship ID
+    /// is one greater than the greatest ID used so far (or `0` for the first relationship).
+    #[must_use]
+    pub fn add_relationship(self, from: u32, to: u32, r#type: RelationshipType) -> Self {
+        let id = self
+            .network
+            .relationships
+            .iter()
+            .map(|r| r.id + 1)
+            .max()
+            .unwrap_or(0);
+        self.add_relationship_with_id(id, from, to, r#type)
+    }
+
+    /// Add a new relationship of the given type between two variables, using the given `id`.
+    #[must_use]
+    pub fn add_relationship_with_id(
+        mut self,
+        id: u32,
+        from: u32,
+        to: u32,
+        r#type: RelationshipType,
+    ) -> Self {
+        self.network.relationships.push(BmaRelationship {
+            id,
+            from_variable: from,
+            to_variable: to,
+            r#type,
+        });
+        self
+    }
+
+    /// Set the update function of variable `id` to the given `formula`.
+    ///
+    /// The formula is parsed in [`BmaModelBuilder::build`], and if it is invalid, the error
+    /// is reported by the model validation. Similarly, if no variable with the given `id` is
+    /// added to the builder, [`BmaBuilderError::FormulaVariableNotFound`] is reported.
+    #[must_use]
+    pub fn set_formula(mut self, id: u32, formula: &str) -> Self {
+        self.formulas.retain(|(var_id, _)| *var_id != id);
+        self.formulas.push((id, formula.to_string()));
+        self
+    }
+
+    /// Set the metadata `key` to the given `value`.
+    #[must_use]
+    pub fn set_metadata(mut self, key: &str, value: &str) -> Self {
+        self.metadata.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Build the model and run [`Validation::validate`] on it. If the model is not valid,
+    /// all validation errors are returned (the same as in [`Validation::validate`], so that
+    /// all issues can be fixed at once).
+    ///
+    /// Update functions of variables that were never added are reported as
+    /// [`BmaBuilderError::FormulaVariableNotFound`] (before the validation errors).
+    pub fn build(self) -> Result<BmaModel, Vec<BmaBuilderError>> {
+        let mut network = self.network;
+        let hint = network
+            .variables
+            .iter()
+            .map(|v| (v.id, v.name.clone()))
+            .collect::<Vec<_>>();
+        let mut errors = Vec::new();
+        for (id, formula) in &self.formulas {
+            if network.find_variable(*id).is_none() {
+                errors.push(BmaBuilderError::FormulaVariableNotFound { id: *id });
+                continue;
+            }
+            let formula = BmaUpdateFunction::parse_optional_with_hint(formula, &hint);
+            for variable in network.variables.iter_mut().filter(|v| v.id == *id) {
+                variable.formula.clone_from(&formula);
+            }
+        }
+
+        let model = BmaModel::new(network, BmaLayout::default(), self.metadata);
+        if let Err(validation_errors) = model.validate() {
+            errors.extend(validation_errors.into_iter().map(BmaBuilderError::from));
+        }
+        if errors.is_empty() {
+            Ok(model)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Errors reported by [`BmaModelBuilder::build`].
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BmaBuilderError {
+    /// The built model is not valid (see [`Validation::validate`]).
+    #[error(transparent)]
+    Model(#[from] BmaModelError),
+    /// The update function of a variable was set, but the variable was never added.
+    #[error("(Variable id: `{id}`) Update function is set, but the variable does not exist")]
+    FormulaVariableNotFound { id: u32 },
+}
+
+impl BmaBuilderError {
+    /// A stable identifier of the error kind (see [`BmaModelError::code`]).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaBuilderError::Model(e) => e.code(),
+            BmaBuilderError::FormulaVariableNotFound { .. } => "builder.formula_variable_not_found",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RelationshipType::{Activator, Inhibitor};
+    use crate::{
+        BmaBuilderError, BmaModelBuilder, BmaModelError, BmaNetworkError, BmaVariableError,
+    };
+
+    #[test]
+    fn build_valid_model() {
+        let model = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (0, 1))
+            .add_variable(2, "b", (0, 2))
+            .add_relationship(1, 2, Activator)
+            .add_relationship(2, 1, Inhibitor)
+            .add_relationship(2, 2, Activator)
+            .set_formula(1, "1 - var(2)")
+            .set_formula(2, "var(a) + var(b)")
+            .set_metadata("version", "1")
+            .build()
+            .unwrap();
+
+        assert_eq!(model.network.name, "test");
+        assert_eq!(model.network.variables.len(), 2);
+        let ids = model
+            .network
+            .relationships
+            .iter()
+            .map(|r| r.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 1, 2]);
+        let formula = model.network.find_variable(2).unwrap().formula_string();
+        assert_eq!(formula, "(var(1) + var(2))");
+        assert_eq!(model.metadata["version"], "1");
+    }
+
+    #[test]
+    fn build_invalid_model() {
+        let errors = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (0, 1))
+            .set_formula(1, "var(a) +")
+            .build()
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            BmaBuilderError::Model(BmaModelError::Network(BmaNetworkError::Variable(
+                BmaVariableError::UpdateFunctionExpressionInvalid { id: 1, .. }
+            )))
+        ));
+    }
+
+    #[test]
+    fn formula_of_unknown_variable() {
+        let errors = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (0, 1))
+            .set_formula(2, "1")
+            .build()
+            .unwrap_err();
+        let expected = BmaBuilderError::FormulaVariableNotFound { id: 2 };
+        assert_eq!(errors, vec![expected]);
+        assert_eq!(errors[0].code(), "builder.formula_variable_not_found");
+    }
+
+    #[test]
+    fn explicit_relationship_ids() {
+        let model = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (0, 1))
+            .add_variable(2, "b", (0, 1))
+            .add_variable(3, "c", (0, 1))
+            .add_relationship_with_id(100, 1, 2, Activator)
+            .add_relationship(1, 3, Activator)
+            .build()
+            .unwrap();
+        let ids = model
+            .network
+            .relationships
+            .iter()
+            .map(|r| r.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![100, 101]);
+    }
+}
@@ -0,0 +1,294 @@
+use crate::update_function::{ArithOp, BmaUpdateFunction};
+use crate::{BmaModel, BmaVariable};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+impl BmaModel {
+    /// Find all variables that are fixed to a constant level, together with this level.
+    ///
+    /// A variable is considered constant if it has a constant range (see
+    /// [`BmaVariable::has_constant_range`]), in which case the level is the only value
+    /// in this range, or if its update function always produces the same level (see
+    /// [`crate::BmaNetwork::is_effectively_constant`]). Variables whose function table
+    /// cannot be built are ignored.
+    #[must_use]
+    pub fn constant_inputs(&self) -> HashMap<u32, u32> {
+        let mut result = HashMap::new();
+        for variable in &self.network.variables {
+            if variable.has_constant_range() {
+                result.insert(variable.id, variable.min_level());
+            } else if let Ok(Some(level)) = self.network.is_effectively_constant(variable.id) {
+                result.insert(variable.id, level);
+            }
+        }
+        result
+    }
+
+    /// Substitute the levels of constant variables (see [`BmaModel::constant_inputs`]) into
+    /// the update functions of the remaining variables and remove the constant variables
+    /// (including their relationships and layout information) from the model.
+    ///
+    /// The substituted values are normalized to the range of each target variable, such that
+    /// the behaviour of the remaining variables is preserved. Variables that use the default
+    /// update function and are regulated by a constant variable are assigned an explicit
+    /// update function first. Since removing constants can make other variables constant,
+    /// the process is repeated until no constant variable can be removed.
+    ///
+    /// A constant variable is not removed if it regulates a variable with an invalid
+    /// update function (such function cannot be updated).
+    ///
+    /// Returns the sorted list of removed variable IDs.
+    pub fn fold_constants(&mut self) -> Vec<u32> {
+        let mut removed = Vec::new();
+        loop {
+            let constants = self.constant_inputs();
+            let foldable = self.foldable_constants(&constants);
+            if foldable.is_empty() {
+                break;
+            }
+
+            // Compute all updated functions before the network is modified.
+            let mut updated_functions = Vec::new();
+            for variable in &self.network.variables {
+                if foldable.contains_key(&variable.id) {
+                    continue;
+                }
+                let function = match &variable.formula {
+                    Some(Ok(function)) => function.clone(),
+                    Some(Err(_)) => continue,
+                    None => {
+                        let regulators = self.network.get_regulators(variable.id, &None);
+                        if !regulators.iter().any(|id| foldable.contains_key(id)) {
+                            continue;
+                        }
+                        self.network.build_default_update_function(variable.id)
+                    }
+                };
+
+                let mut folded = function.clone();
+                for var_id in function.variables_in_order() {
+                    let Some(level) = foldable.get(&var_id) else {
+                        continue;
+                    };
+                    let constant = self
+                        .network
+                        .find_variable(var_id)
+                        .expect("Invariant violation: Constant variable must exist.");
+                    let replacement = normalized_constant(*level, constant, variable)
+                        .expect("Invariant violation: Foldable constants can be normalized.");
+                    folded = folded.substitute_variable(var_id, &replacement);
+                }
+                updated_functions.push((variable.id, folded));
+            }
+
+            for (var_id, function) in updated_functions {
+                if let Some(variable) = self.network.variables.iter_mut().find(|v| v.id == var_id) {
+                    variable.formula = Some(Ok(function));
+                }
+            }
+
+            let network = &mut self.network;
+            network.variables.retain(|v| !foldable.contains_key(&v.id));
+            network.relationships.retain(|r| {
+                !foldable.contains_key(&r.from_variable) && !foldable.contains_key(&r.to_variable)
+            });
+            self.layout
+                .variables
+                .retain(|v| !foldable.contains_key(&v.id));
+            removed.extend(foldable.keys().copied());
+        }
+
+        removed.sort_unstable();
+        removed
+    }
+
+    /// Select the `constants` that can be safely removed from the model, i.e., they do not
+    /// regulate (directly or through other constants) a variable whose update function cannot
+    /// be updated.
+    fn foldable_constants(&self, constants: &HashMap<u32, u32>) -> BTreeMap<u32, u32> {
+        let mut foldable: BTreeMap<u32, u32> = constants.iter().map(|(k, v)| (*k, *v)).collect();
+        loop {
+            let mut blocked = BTreeSet::new();
+            for (id, level) in &foldable {
+                let constant = self.network.find_variable(*id);
+                for relationship in &self.network.relationships {
+                    if relationship.from_variable != *id
+                        || foldable.contains_key(&relationship.to_variable)
+                    {
+                        continue;
+                    }
+                    let target = self.network.find_variable(relationship.to_variable);
+                    let is_blocked = match (constant, target) {
+                        (Some(constant), Some(target)) => {
+                            matches!(target.formula, Some(Err(_)))
+                                || normalized_constant(*level, constant, target).is_none()
+                        }
+                        _ => true,
+                    };
+                    if is_blocked {
+                        blocked.insert(*id);
+                    }
+                }
+            }
+            if blocked.is_empty() {
+                return foldable;
+            }
+            foldable.retain(|id, _| !blocked.contains(id));
+        }
+    }
+}
+
+/// Build an expression that evaluates to the given `level` of the `constant` variable,
+/// normalized to the range of the `target` variable
+/// (see [`BmaVariable::normalize_input_level`]).
+///
+/// The expression performs the same arithmetic as the normalization, such that the result
+/// is the same as if the variable was evaluated directly. Returns `None` if the values
+/// do not fit into the expression constants.
+fn normalized_constant(
+    level: u32,
+    constant: &BmaVariable,
+    target: &BmaVariable,
+) -> Option<BmaUpdateFunction> {
+    let (a, b) = (
+        i64::from(constant.min_level()),
+        i64::from(constant.max_level()),
+    );
+    let (c, d) = (i64::from(target.min_level()), i64::from(target.max_level()));
+    let value = i64::from(level);
+    if a == b {
+        return Some(BmaUpdateFunction::mk_constant(i32::try_from(value).ok()?));
+    }
+
+    let numerator = (value - a) * (d - c);
+    let denominator = b - a;
+    if numerator % denominator == 0 {
+        let value = i32::try_from(numerator / denominator + c).ok()?;
+        return Some(BmaUpdateFunction::mk_constant(value));
+    }
+
+    let fraction = BmaUpdateFunction::mk_arithmetic(
+        ArithOp::Div,
+        &BmaUpdateFunction::mk_constant(i32::try_from(numerator).ok()?),
+        &BmaUpdateFunction::mk_constant(i32::try_from(denominator).ok()?),
+    );
+    if c == 0 {
+        Some(fraction)
+    } else {
+        let offset = BmaUpdateFunction::mk_constant(i32::try_from(c).ok()?);
+        Some(BmaUpdateFunction::mk_arithmetic(
+            ArithOp::Plus,
+            &fraction,
+            &offset,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::update_function::BmaUpdateFunction;
+    use crate::{BmaModel, BmaNetwork, BmaRelationship, BmaVariable};
+    use std::collections::{BTreeMap, HashMap};
+
+    /// Input `c` fixed to `1` (range `[1,1]`), `k` which is effectively constant (`[0,2]`),
+    /// `x` in `[0,2]` regulated by `c` and `y` (default function), and `y` in `[0,1]`
+    /// regulated by `c`, `k`, and `x`.
+    fn constant_model() -> BmaModel {
+        let f = |s: &str| Some(BmaUpdateFunction::try_from(s).unwrap());
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "c", (1, 1), None),
+                BmaVariable::new(2, "k", (0, 2), f("1")),
+                BmaVariable::new(3, "x", (0, 2), None),
+                BmaVariable::new(4, "y", (0, 1), f("max(var(1), var(2)) * var(3)")),
+            ],
+            vec![
+                BmaRelationship::new_activator(1, 1, 3),
+                BmaRelationship::new_activator(2, 4, 3),
+                BmaRelationship::new_activator(3, 1, 4),
+                BmaRelationship::new_activator(4, 2, 4),
+                BmaRelationship::new_activator(5, 3, 4),
+            ],
+        );
+        BmaModel {
+            network,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn constant_inputs() {
+        let model = constant_model();
+        assert_eq!(model.constant_inputs(), HashMap::from([(1, 1), (2, 1)]));
+    }
+
+    #[test]
+    fn fold_constants_preserves_behaviour() {
+        let original = constant_model();
+        let mut model = original.clone();
+        assert_eq!(model.fold_constants(), vec![1, 2]);
+        assert_eq!(model.network.variables.len(), 2);
+        assert_eq!(model.network.relationships.len(), 2);
+        assert!(model.constant_inputs().is_empty());
+
+        // Variable `x` now has an explicit function.
+        assert!(model.network.find_variable(3).unwrap().formula.is_some());
+
+        for x in 0..=2 {
+            for y in 0..=1 {
+                let reduced = BTreeMap::from([(3, x), (4, y)]);
+                let mut full = reduced.clone();
+                full.extend([(1, 1), (2, 1)]);
+                for var in [3, 4] {
+                    let table = original.network.build_function_table(var).unwrap();
+                    let expected = table
+                        .iter()
+                        .find(|(input, _)| input.iter().all(|(k, v)| full[k] == *v))
+                        .unwrap()
+                        .1;
+                    assert_eq!(model.network.evaluate(var, &reduced).unwrap(), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fold_constants_skips_invalid_functions() {
+        let mut model = constant_model();
+        model.network.variables[3].formula =
+            Some(BmaUpdateFunction::parse_with_hint("var(1) +", &[]));
+        // Constant `c` regulates `y`, which cannot be updated. Constant `k` also regulates `y`.
+        assert_eq!(model.fold_constants(), Vec::<u32>::new());
+
+        model.network.relationships.retain(|r| r.from_variable != 2);
+        assert_eq!(model.fold_constants(), vec![2]);
+        assert!(model.network.find_variable(1).is_some());
+    }
+
+    #[test]
+    fn fold_constants_fractional_levels() {
+        let f = |s: &str| Some(BmaUpdateFunction::try_from(s).unwrap());
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "k", (0, 2), f("1")),
+                BmaVariable::new(2, "x", (0, 1), f("var(1) * var(3)")),
+                BmaVariable::new(3, "y", (0, 1), f("var(3)")),
+            ],
+            vec![
+                BmaRelationship::new_activator(1, 1, 2),
+                BmaRelationship::new_activator(2, 3, 2),
+                BmaRelationship::new_activator(3, 3, 3),
+            ],
+        );
+        let mut model = BmaModel {
+            network,
+            ..Default::default()
+        };
+        assert_eq!(model.fold_constants(), vec![1]);
+        let function = model.network.find_variable(2).unwrap().formula.clone();
+        let function = function.unwrap().unwrap();
+        assert_eq!(function.to_string(), "((1 / 2) * var(3))");
+        // The normalized level of `k` is `0.5`, which is then rounded up.
+        let valuation = BTreeMap::from([(3, 1)]);
+        assert_eq!(model.network.evaluate(2, &valuation).unwrap(), 1);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{BmaLayoutContainer, BmaModel};
+#[cfg(feature = "regex")]
+use regex::Regex;
+#[cfg(feature = "regex")]
+use std::collections::HashMap;
+
+impl BmaModel {
+    /// All layout containers of this model.
+    #[must_use]
+    pub fn containers(&self) -> &[BmaLayoutContainer] {
+        &self.layout.containers
+    }
+
+    /// Find the layout container with the given `id`, if it exists.
+    #[must_use]
+    pub fn container_by_id(&self, id: u32) -> Option<&BmaLayoutContainer> {
+        self.layout.find_container(id)
+    }
+
+    /// Add a new layout container to this model.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a container with the same `id` already exists.
+    pub fn add_container(&mut self, container: BmaLayoutContainer) {
+        assert!(
+            self.container_by_id(container.id).is_none(),
+            "Precondition violated: Container `{}` already exists.",
+            container.id
+        );
+        self.layout.containers.push(container);
+    }
+
+    /// Remove the layout container with the given `id` and return it (if it exists).
+    ///
+    /// Variables that are members of the removed container no longer belong to any
+    /// container (i.e., their `container_id` is cleared).
+    pub fn remove_container(&mut self, id: u32) -> Option<BmaLayoutContainer> {
+        let index = self.layout.containers.iter().position(|c| c.id == id)?;
+        for variable in &mut self.layout.variables {
+            if variable.container_id == Some(id) {
+                variable.container_id = None;
+            }
+        }
+        Some(self.layout.containers.remove(index))
+    }
+
+    /// Group variables that represent the same biological species (typically the same
+    /// species duplicated across multiple cells/containers of a tissue model).
+    ///
+    /// The species of a variable is extracted from its name using `name_regex`: if the regex
+    /// has a capture group named `species`, it is used as the key; otherwise, the first capture
+    /// group is used, or the whole match if the regex has no capture groups. Variables whose
+    /// name does not match the regex are not included in the result. The IDs in each group are
+    /// sorted.
+    ///
+    /// For example, `^(.+)_cell\d+$` groups `TF_cell1` and `TF_cell2` into a group `TF`.
+    ///
+    /// Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    #[must_use]
+    pub fn group_by_species(&self, name_regex: &Regex) -> HashMap<String, Vec<u32>> {
+        let mut groups = HashMap::<String, Vec<u32>>::new();
+        for variable in &self.network.variables {
+            let Some(captures) = name_regex.captures(&variable.name) else {
+                continue;
+            };
+            let species = captures
+                .name("species")
+                .or_else(|| captures.get(1))
+                .or_else(|| captures.get(0))
+                .map_or("", |m| m.as_str());
+            groups
+                .entry(species.to_string())
+                .or_default()
+                .push(variable.id);
+        }
+        for ids in groups.values_mut() {
+            ids.sort_unstable();
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::tests::{simple_layout, simple_network};
+    use crate::{BmaLayoutContainer, BmaModel, Validation};
+
+    #[test]
+    fn container_management() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            ..Default::default()
+        };
+        assert_eq!(model.containers().len(), 1);
+        assert_eq!(model.container_by_id(13).unwrap().name, "Test container");
+        assert!(model.container_by_id(14).is_none());
+
+        model.add_container(BmaLayoutContainer::new(14, "Second"));
+        assert_eq!(model.containers().len(), 2);
+        assert_eq!(model.container_by_id(14).unwrap().name, "Second");
+
+        let removed = model.remove_container(13).unwrap();
+        assert_eq!(removed.name, "Test container");
+        assert_eq!(model.layout.find_variable(3).unwrap().container_id, None);
+        assert!(model.remove_container(13).is_none());
+        model.validate().unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "already exists")]
+    fn add_duplicate_container() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            ..Default::default()
+        };
+        model.add_container(BmaLayoutContainer::new(13, "Duplicate"));
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn group_by_species() {
+        use crate::{BmaNetwork, BmaVariable};
+        use regex::Regex;
+        use std::collections::HashMap;
+
+        let names = ["TF_cell2", "TF_cell1", "Wnt_cell1", "Wnt_cell2", "Signal"];
+        let variables = names
+            .iter()
+            .zip(0..)
+            .map(|(name, id)| BmaVariable::new_boolean(id, name, None))
+            .collect();
+        let model = BmaModel {
+            network: BmaNetwork::new(variables, Vec::new()),
+            ..Default::default()
+        };
+
+        let expected = HashMap::from([
+            ("TF".to_string(), vec![0, 1]),
+            ("Wnt".to_string(), vec![2, 3]),
+        ]);
+        let regex = Regex::new(r"^(.+)_cell\d+$").unwrap();
+        assert_eq!(model.group_by_species(&regex), expected);
+        // The `species` group takes precedence over the first capture group.
+        let regex = Regex::new(r"^((?<species>[A-Za-z]+)_cell)\d+$").unwrap();
+        assert_eq!(model.group_by_species(&regex), expected);
+
+        // Without capture groups, the whole match is used.
+        let regex = Regex::new(r"^[A-Za-z]+").unwrap();
+        let groups = model.group_by_species(&regex);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups["Signal"], vec![4]);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::BmaModel;
+
+/// A minimal 64-bit FNV-1a hasher. Unlike [`std::hash::DefaultHasher`], its output is
+/// guaranteed to be the same across platforms and compiler versions.
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Fnv64(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        // The length prefix ensures that consecutive strings cannot be confused.
+        self.write_bytes(&(value.len() as u64).to_le_bytes());
+        self.write_bytes(value.as_bytes());
+    }
+}
+
+impl BmaModel {
+    /// Compute a stable fingerprint of the functional part of this model, which can be used
+    /// to detect duplicate models (e.g. the same model loaded from JSON and XML).
+    ///
+    /// The fingerprint covers the variables (ID, name, and range), the relationships (regulator,
+    /// target, and type), and the semantics of the update functions, which are represented by
+    /// their function tables (see [`crate::BmaNetwork::build_function_table`]). As such, it does
+    /// not depend on the order of variables and relationships, on relationship IDs, or on how the
+    /// update functions are written (e.g. `var(1)` and `max(var(1), var(1))` are the same).
+    /// The model name, layout, and metadata are ignored. If a function table cannot be built,
+    /// the update function is represented by its string instead.
+    ///
+    /// The fingerprint is a hash, so different models can (rarely) have the same fingerprint.
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        let network = &self.network;
+        let mut hasher = Fnv64::new();
+        hasher.write_bytes(&[u8::from(network.avg_integer_semantics)]);
+
+        let mut variables = network.variables.iter().collect::<Vec<_>>();
+        variables.sort_by_key(|v| v.id);
+        hasher.write_bytes(&(variables.len() as u64).to_le_bytes());
+        for variable in variables {
+            hasher.write_u32(variable.id);
+            hasher.write_str(&variable.name);
+            hasher.write_u32(variable.range.0);
+            hasher.write_u32(variable.range.1);
+            if let Ok(table) = network.build_function_table(variable.id) {
+                hasher.write_bytes(&[1]);
+                hasher.write_bytes(&(table.len() as u64).to_le_bytes());
+                for (valuation, output) in table {
+                    for (regulator, level) in valuation {
+                        hasher.write_u32(regulator);
+                        hasher.write_u32(level);
+                    }
+                    hasher.write_u32(output);
+                }
+            } else {
+                hasher.write_bytes(&[0]);
+                hasher.write_str(&variable.formula_string());
+            }
+        }
+
+        let relationships = network.interaction_signature();
+        hasher.write_bytes(&(relationships.len() as u64).to_le_bytes());
+        for (from, to, r#type) in relationships {
+            hasher.write_u32(from);
+            hasher.write_u32(to);
+            hasher.write_str(&r#type);
+        }
+
+        hasher.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::tests::{simple_layout, simple_network};
+    use crate::update_function::BmaUpdateFunction;
+    use crate::{BmaLayout, BmaModel, BmaRelationship};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn fingerprint_is_canonical() {
+        let model = BmaModel::new(simple_network(), simple_layout(), BTreeMap::new());
+        let fingerprint = model.fingerprint();
+
+        // Ordering, relationship IDs, layout, and the model name do not matter.
+        let mut other = model.clone();
+        other.network.variables.reverse();
+        other.network.relationships.reverse();
+        other.network.relationships[0].id = 10;
+        other.network.name = "Other name".to_string();
+        other.layout = BmaLayout::default();
+        assert_eq!(other.fingerprint(), fingerprint);
+
+        // Equivalent update functions have the same fingerprint.
+        let mut first = model.clone();
+        first.network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(0)"));
+        let mut second = model.clone();
+        second.network.variables[0].formula =
+            Some(BmaUpdateFunction::try_from("max(var(0), var(0))"));
+        assert_eq!(first.fingerprint(), second.fingerprint());
+        assert_ne!(first.fingerprint(), fingerprint);
+
+        // Changes in ranges or relationships change the fingerprint.
+        let mut other = model.clone();
+        other.network.variables[1].range = (0, 3);
+        assert_ne!(other.fingerprint(), fingerprint);
+        let mut other = model.clone();
+        other
+            .network
+            .relationships
+            .push(BmaRelationship::new_activator(5, 3, 0));
+        assert_ne!(other.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn fingerprint_of_json_and_xml() {
+        let json = std::fs::read_to_string("./models/json-export-from-repo/SmallTestCase.json");
+        let xml = std::fs::read_to_string("./models/xml-repo/SmallTestCase.xml");
+        let json = BmaModel::from_json_string(&json.unwrap()).unwrap();
+        let xml = BmaModel::from_xml_string(&xml.unwrap()).unwrap();
+        assert_eq!(json.fingerprint(), xml.fingerprint());
+    }
+}
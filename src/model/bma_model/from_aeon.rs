@@ -7,7 +7,22 @@ use anyhow::anyhow;
 use biodivine_lib_param_bn::BooleanNetwork;
 use biodivine_lib_param_bn::Monotonicity::Inhibition;
 use rust_decimal::Decimal;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+
+impl BmaModel {
+    /// Create a new BMA model from a string in the AEON `.aeon` format, using `name` as the
+    /// name of the resulting [`BmaNetwork`].
+    ///
+    /// The model is constructed using the [`BooleanNetwork`] conversion (see
+    /// `BmaModel::try_from(&BooleanNetwork)`), meaning it has a default layout with
+    /// all variables in a single container.
+    pub fn from_aeon_string(aeon: &str, name: &str) -> anyhow::Result<BmaModel> {
+        let bn = BooleanNetwork::try_from(aeon).map_err(|e| anyhow!(e))?;
+        let mut model = BmaModel::try_from(&bn)?;
+        model.network.name = name.to_string();
+        Ok(model)
+    }
+}
 
 /// Construct a [`BmaModel`] instance from a provided [`BooleanNetwork`].
 ///
@@ -126,6 +141,7 @@ impl TryFrom<&BooleanNetwork> for BmaModel {
             name: String::default(),
             variables,
             relationships,
+            ..Default::default()
         };
 
         let layout = BmaLayout {
@@ -134,9 +150,10 @@ impl TryFrom<&BooleanNetwork> for BmaModel {
             description: String::default(),
             zoom_level: None,
             pan: None,
+            ..Default::default()
         };
 
-        Ok(BmaModel::new(model, layout, HashMap::new()))
+        Ok(BmaModel::new(model, layout, BTreeMap::new()))
     }
 }
 
@@ -147,6 +164,25 @@ mod tests {
     use biodivine_lib_param_bn::BooleanNetwork;
     use test_generator::test_resources;
 
+    #[test]
+    fn test_from_aeon_string() {
+        let aeon_model = r"
+        $A: A & !B
+        $B: A
+        B -| A
+        A -> A
+        A -> B
+        ";
+        let bma_model = BmaModel::from_aeon_string(aeon_model, "Toy").unwrap();
+        let bn = BooleanNetwork::try_from(aeon_model).unwrap();
+        let mut expected = BmaModel::try_from(&bn).unwrap();
+        expected.network.name = "Toy".to_string();
+        assert_eq!(bma_model, expected);
+
+        assert!(BmaModel::from_aeon_string("$A: B", "Invalid").is_err());
+        assert!(BmaModel::from_aeon_string("$A: f(A)\nA -> A", "Parametrized").is_err());
+    }
+
     #[test]
     fn test_from_bn() {
         let aeon_model = r#"
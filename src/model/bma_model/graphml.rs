@@ -0,0 +1,128 @@
+use crate::utils::escape_xml;
+use crate::{BmaModel, RelationshipType, VariableType};
+use std::fmt::Write;
+
+impl BmaModel {
+    /// Convert the network of this model into a `GraphML` document (e.g. for Cytoscape).
+    ///
+    /// Each variable is a node (with attributes `name`, `range_from`, `range_to`, and `type`)
+    /// and each relationship is a directed edge (with attribute `relationship_type`). The node
+    /// ids are the BMA variable ids and the edge ids are the BMA relationship ids prefixed
+    /// with `e`. The variable `type` is taken from the layout (`Default` if the variable has
+    /// no layout information).
+    #[must_use]
+    pub fn to_graphml(&self) -> String {
+        let mut result = String::new();
+        result.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        result.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, target, r#type) in [
+            ("name", "node", "string"),
+            ("range_from", "node", "int"),
+            ("range_to", "node", "int"),
+            ("type", "node", "string"),
+            ("relationship_type", "edge", "string"),
+        ] {
+            writeln!(
+                result,
+                "  <key id=\"{id}\" for=\"{target}\" attr.name=\"{id}\" attr.type=\"{type}\"/>"
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            result,
+            "  <graph id=\"{}\" edgedefault=\"directed\">",
+            escape_xml(&self.network.name)
+        )
+        .unwrap();
+
+        for var in &self.network.variables {
+            let var_type = self
+                .layout
+                .find_variable(var.id)
+                .map_or(VariableType::Default, |it| it.r#type.clone());
+            writeln!(result, "    <node id=\"{}\">", var.id).unwrap();
+            writeln!(
+                result,
+                "      <data key=\"name\">{}</data>",
+                escape_xml(&var.name)
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "      <data key=\"range_from\">{}</data>",
+                var.min_level()
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "      <data key=\"range_to\">{}</data>",
+                var.max_level()
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "      <data key=\"type\">{}</data>",
+                escape_xml(&var_type.to_string())
+            )
+            .unwrap();
+            result.push_str("    </node>\n");
+        }
+
+        for relationship in &self.network.relationships {
+            let r#type = match &relationship.r#type {
+                RelationshipType::Activator => "Activator",
+                RelationshipType::Inhibitor => "Inhibitor",
+                RelationshipType::Unknown(value) => value.as_str(),
+            };
+            writeln!(
+                result,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+                relationship.id, relationship.from_variable, relationship.to_variable
+            )
+            .unwrap();
+            writeln!(
+                result,
+                "      <data key=\"relationship_type\">{}</data>",
+                escape_xml(r#type)
+            )
+            .unwrap();
+            result.push_str("    </edge>\n");
+        }
+
+        result.push_str("  </graph>\n");
+        result.push_str("</graphml>\n");
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BmaModel;
+    use crate::model::tests::{simple_layout, simple_network};
+
+    #[test]
+    fn graphml_export() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            ..Default::default()
+        };
+        model.network.name = "A & B".to_string();
+
+        let graphml = model.to_graphml();
+        assert!(graphml.starts_with("<?xml"));
+        assert!(graphml.contains("<graph id=\"A &amp; B\" edgedefault=\"directed\">"));
+        assert!(graphml.contains("<node id=\"3\">"));
+        assert!(graphml.contains("<data key=\"name\">var_B</data>"));
+        assert!(graphml.contains("<data key=\"range_from\">1</data>"));
+        assert!(graphml.contains("<data key=\"range_to\">3</data>"));
+        assert!(graphml.contains("<edge id=\"e1\" source=\"3\" target=\"3\">"));
+        assert!(graphml.contains("<data key=\"relationship_type\">Inhibitor</data>"));
+        assert_eq!(graphml.matches("<node ").count(), 2);
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+
+        // The output is deterministic.
+        assert_eq!(graphml, model.to_graphml());
+    }
+}
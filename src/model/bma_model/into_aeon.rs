@@ -1,13 +1,67 @@
+use crate::model::bma_variable::sanitize_name;
 use crate::update_function::FunctionTable;
 use crate::{BmaModel, BmaVariable};
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use biodivine_lib_bdd::{
-    Bdd, BddPartialValuation, BddVariable, BddVariableSet, BddVariableSetBuilder,
+    Bdd, BddPartialValuation, BddValuation, BddVariable, BddVariableSet, BddVariableSetBuilder,
 };
 use biodivine_lib_param_bn::{BooleanNetwork, FnUpdate, Regulation, RegulatoryGraph, VariableId};
-use std::collections::HashMap;
+use num_traits::ToPrimitive;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::RangeInclusive;
 
+/// The naming scheme of the variables in a [`BooleanNetwork`] created from a [`BmaModel`]
+/// (see [`BmaModel::to_boolean_network_named`]).
+///
+/// Every level of a BMA variable (except for the lowest one) is represented by one Boolean
+/// variable, which is identified by a `_b<level>` suffix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NamingScheme {
+    /// Variables are named `v<id>_<name>_b<level>`, with special characters in the name
+    /// replaced by `_`. This is the default scheme used by `BooleanNetwork::try_from`.
+    #[default]
+    IdPrefixed,
+    /// Variables are named `<name>_b<level>`, or just `<name>` for Boolean variables.
+    /// The conversion fails if a name is empty, contains characters other than ASCII
+    /// alphanumerics and `_`, or if the resulting names are not unique.
+    NameOnly,
+    /// Variables are named `<name>_<id>_b<level>`, or just `<name>_<id>` for Boolean
+    /// variables, with special characters in the name replaced by `_`.
+    NameWithIdSuffix,
+}
+
+impl NamingScheme {
+    /// Build the name of the Boolean variable representing the given `level` of `var`.
+    fn level_identifier(self, var: &BmaVariable, level: u32) -> anyhow::Result<String> {
+        let base = match self {
+            NamingScheme::IdPrefixed => return Ok(var.mk_level_identifier(level)),
+            NamingScheme::NameOnly => {
+                let is_valid = var
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if var.name.is_empty() || !is_valid {
+                    bail!(
+                        "Name `{}` of variable `{}` is not a valid Boolean network variable name",
+                        var.name,
+                        var.id
+                    );
+                }
+                var.name.clone()
+            }
+            NamingScheme::NameWithIdSuffix if var.name.is_empty() => format!("v_{}", var.id),
+            NamingScheme::NameWithIdSuffix => {
+                format!("{}_{}", sanitize_name(var.name.as_str()), var.id)
+            }
+        };
+        if var.range == (0, 1) {
+            Ok(base)
+        } else {
+            Ok(format!("{base}_b{level}"))
+        }
+    }
+}
+
 /// Symbolic update function stores a [`Bdd`] condition for each output level of
 /// a specific update function. The conditions should be mutually exclusive and exhaustive
 /// (i.e. each input valuation satisfies exactly one of the stored BDDs). Levels should
@@ -100,8 +154,7 @@ impl TryFrom<&BmaModel> for BooleanNetwork {
     type Error = anyhow::Error;
 
     fn try_from(model: &BmaModel) -> Result<Self, Self::Error> {
-        let context = SymbolicContext::try_from(model)?;
-        BooleanNetwork::try_from(&context)
+        model.to_boolean_network_named(NamingScheme::default())
     }
 }
 
@@ -113,6 +166,125 @@ impl TryFrom<BmaModel> for BooleanNetwork {
     }
 }
 
+impl BmaModel {
+    /// Same as `BooleanNetwork::try_from`, but the variables of the resulting network
+    /// are named using the given [`NamingScheme`].
+    pub fn to_boolean_network_named(&self, scheme: NamingScheme) -> anyhow::Result<BooleanNetwork> {
+        let context = SymbolicContext::new(self, scheme)?;
+        BooleanNetwork::try_from(&context)
+    }
+
+    /// The names of the symbolic variables used to encode the levels of this model, in the
+    /// order of declaration. These are the same as the names of the [`BooleanNetwork`]
+    /// variables created by `BooleanNetwork::try_from` (i.e., [`NamingScheme::IdPrefixed`]).
+    ///
+    /// Every variable with levels `min..=max` is represented by variables `v<id>_<name>_b<level>`
+    /// for `level` in `(min + 1)..=max` (the variable is at level `l` if all bits up to `l` are
+    /// set). A variable with a constant range `c` is represented by one variable
+    /// `v<id>_<name>_b<c>`. Special characters in the name are replaced by `_`, and if the name
+    /// is empty, the identifier is `v<id>_b<level>`.
+    #[must_use]
+    pub fn symbolic_variable_names(&self) -> Vec<String> {
+        self.network
+            .variables
+            .iter()
+            .flat_map(|var| symbolic_levels(var).map(|level| var.mk_level_identifier(level)))
+            .collect()
+    }
+
+    /// Count the fixed points of this model, i.e., the states where the update function
+    /// of every variable evaluates to its current level.
+    ///
+    /// The count is computed symbolically, using the same encoding as the conversion into
+    /// a [`BooleanNetwork`] (variables with a constant range are fixed to the level given
+    /// by their update function). Hence, the states do not need to be enumerated.
+    ///
+    /// The operation fails if the model cannot be converted (see `BooleanNetwork::try_from`),
+    /// or if the count does not fit into `u128`.
+    pub fn count_fixed_points_symbolic(&self) -> anyhow::Result<u128> {
+        let context = SymbolicContext::new(self, NamingScheme::default())?;
+        context
+            .fixed_points(&HashMap::new())
+            .exact_cardinality()
+            .to_u128()
+            .ok_or_else(|| anyhow!("Number of fixed points does not fit into `u128`"))
+    }
+
+    /// Compute the stable states (fixed points) of this model when the variables in `inputs`
+    /// are fixed to the given levels, regardless of their update functions. Each stable state
+    /// maps the ID of every variable to its level, and the states are sorted.
+    ///
+    /// The stable states are computed symbolically (see
+    /// [`BmaModel::count_fixed_points_symbolic`]), but they are all enumerated in the result,
+    /// so this is only practical if the number of stable states is reasonable.
+    ///
+    /// The operation fails if the model cannot be converted (see `BooleanNetwork::try_from`),
+    /// or if `inputs` references a missing variable or a level outside of its range.
+    pub fn stable_states_under_inputs(
+        &self,
+        inputs: &HashMap<u32, u32>,
+    ) -> anyhow::Result<Vec<BTreeMap<u32, u32>>> {
+        for (id, level) in inputs {
+            let var = self
+                .network
+                .find_variable(*id)
+                .ok_or_else(|| anyhow!("Input variable with id `{id}` not found"))?;
+            if !var.levels().contains(level) {
+                bail!(
+                    "Level `{level}` is outside of the range `{:?}` of variable `{id}`",
+                    var.range
+                );
+            }
+        }
+
+        let context = SymbolicContext::new(self, NamingScheme::default())?;
+        let mut result = context
+            .fixed_points(inputs)
+            .sat_valuations()
+            .map(|valuation| {
+                context
+                    .variables
+                    .iter()
+                    .map(|(var, _)| (var.id, var.decode_level(&valuation)))
+                    .collect::<BTreeMap<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        result.sort();
+        Ok(result)
+    }
+
+    /// Same as `BooleanNetwork::try_from`, but also returns the mapping from the ID of every
+    /// BMA variable to the Boolean variables that encode its levels.
+    ///
+    /// For a variable with levels `min..=max`, the `i`-th Boolean variable represents level
+    /// `min + 1 + i`, and the variable is at level `l` if all Boolean variables up to `l` are
+    /// set (all are unset for level `min`). A variable with a constant range has a single
+    /// Boolean variable (see also [`BmaModel::symbolic_variable_names`]).
+    pub fn to_boolean_network_with_mapping(
+        &self,
+    ) -> anyhow::Result<(BooleanNetwork, HashMap<u32, Vec<VariableId>>)> {
+        let context = SymbolicContext::new(self, NamingScheme::default())?;
+        let bn = BooleanNetwork::try_from(&context)?;
+        let mapping = context
+            .variables
+            .iter()
+            .map(|(var, _)| (var.id, var.bdd_vars.iter().copied().map(cast_id).collect()))
+            .collect();
+        Ok((bn, mapping))
+    }
+
+    /// Convert this model into a string in the AEON `.aeon` format (i.e. the regulations and
+    /// update functions of the [`BooleanNetwork`] obtained through `BooleanNetwork::try_from`).
+    ///
+    /// The regulations are inferred from the update functions, such that the result can be
+    /// parsed back using `BooleanNetwork::try_from(&str)`.
+    pub fn to_aeon_string(&self) -> anyhow::Result<String> {
+        let bn = BooleanNetwork::try_from(self)?;
+        let bn = bn.infer_valid_graph().map_err(|e| anyhow!(e))?;
+        Ok(bn.to_string())
+    }
+}
+
 impl TryFrom<&SymbolicContext> for BooleanNetwork {
     type Error = anyhow::Error;
 
@@ -274,33 +446,29 @@ impl TryFrom<&SymbolicContext> for RegulatoryGraph {
     }
 }
 
-impl TryFrom<&BmaModel> for SymbolicContext {
-    type Error = anyhow::Error;
-
-    fn try_from(model: &BmaModel) -> Result<Self, Self::Error> {
+impl SymbolicContext {
+    /// Build the symbolic encoding of the given `model`, naming the symbolic variables
+    /// using the given [`NamingScheme`].
+    fn new(model: &BmaModel, scheme: NamingScheme) -> anyhow::Result<SymbolicContext> {
         // First, prepare the BDD context by declaring all symbolic variables.
 
         let mut builder = BddVariableSetBuilder::new();
+        let mut names = HashSet::new();
+        let mut make_variable = |var: &BmaVariable, level: u32| {
+            let name = scheme.level_identifier(var, level)?;
+            if !names.insert(name.clone()) {
+                bail!("Boolean network variable name `{name}` is not unique");
+            }
+            Ok(builder.make_variable(name.as_str()))
+        };
+
         let mut variables = Vec::new();
         for var in &model.network.variables {
-            let (min, max) = (var.min_level(), var.max_level());
-            if min == max {
-                // This is a constant. Constants are turned into Boolean "inputs" with a
-                // constant update function. These will need some special handling later on.
-                let name = var.mk_level_identifier(min);
-                let bdd_var = builder.make_variable(name.as_str());
-                variables.push(SymbolicVariable::new(var, vec![bdd_var]));
-            } else {
-                let mut bdd_variables = Vec::new();
-                // For a variable with N values, we only build N-1 BDD variables,
-                // because the lowest value is represented as all zeros.
-                for level in (min + 1)..=max {
-                    let name = var.mk_level_identifier(level);
-                    let bdd_var = builder.make_variable(name.as_str());
-                    bdd_variables.push(bdd_var);
-                }
-                variables.push(SymbolicVariable::new(var, bdd_variables));
+            let mut bdd_variables = Vec::new();
+            for level in symbolic_levels(var) {
+                bdd_variables.push(make_variable(var, level)?);
             }
+            variables.push(SymbolicVariable::new(var, bdd_variables));
         }
 
         let bdd_ctx = builder.build();
@@ -308,8 +476,20 @@ impl TryFrom<&BmaModel> for SymbolicContext {
         // Second, build all update functions.
 
         let mut variable_and_function = Vec::new();
-        for var in &variables {
-            let table = model.network.build_function_table(var.id)?;
+        for (bma_var, var) in model.network.variables.iter().zip(&variables) {
+            // Errors should point to the variable whose function cannot be converted.
+            let with_context = |e: anyhow::Error| {
+                anyhow!(
+                    "Cannot binarize variable `{}` (`{}`): {e}",
+                    bma_var.id,
+                    bma_var.name
+                )
+            };
+
+            let table = model
+                .network
+                .build_function_table(var.id)
+                .map_err(with_context)?;
 
             let symbolic_update = if var.is_constant() {
                 // For constant variables, we don't build the update function normally.
@@ -327,7 +507,8 @@ impl TryFrom<&BmaModel> for SymbolicContext {
                     SymbolicUpdateFunction(vec![(0, t), (const_level, f)])
                 }
             } else {
-                SymbolicUpdateFunction::for_bma_function(&bdd_ctx, &variables, var.range, &table)?
+                SymbolicUpdateFunction::for_bma_function(&bdd_ctx, &variables, var.range, &table)
+                    .map_err(with_context)?
             };
 
             variable_and_function.push((var.clone(), symbolic_update));
@@ -340,6 +521,47 @@ impl TryFrom<&BmaModel> for SymbolicContext {
     }
 }
 
+impl SymbolicContext {
+    /// Build a [`Bdd`] of all fixed points of the encoded model, where the variables in
+    /// `inputs` are fixed to the given levels instead of following their update functions.
+    ///
+    /// Precondition: all `inputs` must reference valid levels of existing variables.
+    fn fixed_points(&self, inputs: &HashMap<u32, u32>) -> Bdd {
+        let ctx = &self.bdd_ctx;
+        let mut fixed_points = ctx.mk_true();
+        for (var, update) in &self.variables {
+            let is_fixed = if var.is_constant() {
+                // Same as in the Boolean network (see `TryFrom<&SymbolicContext>`), unless
+                // the constant is an input (which can only be fixed to its own level).
+                let is_true = inputs.contains_key(&var.id) || !update.0[0].1.is_true();
+                ctx.mk_literal(var.bdd_vars[0], is_true)
+            } else if let Some(level) = inputs.get(&var.id) {
+                var.mk_exact_level(ctx, *level)
+            } else {
+                update.0.iter().fold(ctx.mk_false(), |acc, (level, bdd)| {
+                    acc.or(&bdd.and(&var.mk_exact_level(ctx, *level)))
+                })
+            };
+            fixed_points = fixed_points.and(&is_fixed);
+        }
+        fixed_points
+    }
+}
+
+/// The levels of the given variable that are represented by a symbolic (BDD) variable.
+///
+/// For a variable with N values, we only build N-1 BDD variables, because the lowest value
+/// is represented as all zeros. Constants are turned into Boolean "inputs" with a constant
+/// update function (these need special handling later on), so they have one BDD variable.
+fn symbolic_levels(var: &BmaVariable) -> RangeInclusive<u32> {
+    let (min, max) = (var.min_level(), var.max_level());
+    if min == max {
+        min..=max
+    } else {
+        (min + 1)..=max
+    }
+}
+
 impl SymbolicVariable {
     /// Fix BDD variables in the given [`BddPartialValuation`] such that they represent exactly
     /// all valuations that map to the given `level` in the symbolic variable encoding (or those
@@ -365,6 +587,28 @@ impl SymbolicVariable {
         }
     }
 
+    /// Build a [`Bdd`] of all valid valuations that encode exactly the given `level`
+    /// (i.e. all bits up to `level` are set and all bits above are unset).
+    ///
+    /// Precondition: `level` must be valid for this variable.
+    pub fn mk_exact_level(&self, ctx: &BddVariableSet, level: u32) -> Bdd {
+        let mut valuation = BddPartialValuation::empty();
+        for (i, l) in self.range().skip(1).enumerate() {
+            valuation.set_value(self.bdd_vars[i], level >= l);
+        }
+        ctx.mk_conjunctive_clause(&valuation)
+    }
+
+    /// Decode the level of this variable in the given valuation, assuming it is a valid
+    /// encoding of some level (see [`SymbolicVariable::mk_exact_level`]).
+    pub fn decode_level(&self, valuation: &BddValuation) -> u32 {
+        if self.is_constant() {
+            return self.range.0;
+        }
+        let active = self.bdd_vars.iter().filter(|var| valuation[**var]).count();
+        self.range.0 + u32::try_from(active).unwrap()
+    }
+
     /// A range of all variable levels.
     pub fn range(&self) -> RangeInclusive<u32> {
         self.range.0..=self.range.1
@@ -465,13 +709,248 @@ impl SymbolicUpdateFunction {
 
 #[cfg(test)]
 mod tests {
-    use crate::BmaModel;
+    use crate::RelationshipType::Activator;
+    use crate::update_function::BmaUpdateFunction;
+    use crate::{
+        BmaModel, BmaModelBuilder, BmaNetwork, BmaRelationship, BmaVariable, NamingScheme,
+    };
     use anyhow::anyhow;
+    use biodivine_lib_param_bn::fixed_points::FixedPoints;
     use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
     use biodivine_lib_param_bn::trap_spaces::{SymbolicSpaceContext, TrapSpaces};
     use biodivine_lib_param_bn::{BooleanNetwork, Space};
+    use num_traits::ToPrimitive;
     use std::cmp::max;
-    use std::collections::BTreeMap;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[test]
+    fn aeon_string_round_trip() {
+        let json_data = std::fs::read_to_string("./models/json-repo/ToyModelStable.json").unwrap();
+        let model = BmaModel::from_json_string(json_data.as_str()).unwrap();
+        let aeon = model.to_aeon_string().unwrap();
+        let bn = BooleanNetwork::try_from(aeon.as_str()).unwrap();
+        let expected = BooleanNetwork::try_from(&model).unwrap();
+        assert_eq!(bn.as_graph(), expected.as_graph());
+        // Re-parsing and printing the network again should be stable.
+        assert_eq!(
+            BooleanNetwork::try_from(bn.to_string().as_str()).unwrap(),
+            bn
+        );
+    }
+
+    #[test]
+    fn naming_schemes() {
+        let model = |a: &str, b: &str| {
+            BmaModelBuilder::new("test")
+                .add_variable(1, a, (0, 1))
+                .add_variable(2, b, (0, 2))
+                .add_relationship(1, 2, Activator)
+                .add_relationship(2, 1, Activator)
+                .build()
+                .unwrap()
+        };
+        let names = |bn: &BooleanNetwork| {
+            let mut names = bn
+                .variables()
+                .map(|v| bn.get_variable_name(v).clone())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+
+        let valid = model("a", "b");
+        let default_bn = BooleanNetwork::try_from(&valid).unwrap();
+        let bn = valid
+            .to_boolean_network_named(NamingScheme::IdPrefixed)
+            .unwrap();
+        assert_eq!(bn, default_bn);
+        assert_eq!(names(&bn), vec!["v1_a_b1", "v2_b_b1", "v2_b_b2"]);
+        let bn = valid
+            .to_boolean_network_named(NamingScheme::NameOnly)
+            .unwrap();
+        assert_eq!(names(&bn), vec!["a", "b_b1", "b_b2"]);
+        assert_eq!(
+            bn.as_graph().regulations().count(),
+            default_bn.as_graph().regulations().count()
+        );
+        let bn = valid
+            .to_boolean_network_named(NamingScheme::NameWithIdSuffix)
+            .unwrap();
+        assert_eq!(names(&bn), vec!["a_1", "b_2_b1", "b_2_b2"]);
+
+        // Invalid characters are only rejected by the `NameOnly` scheme.
+        let invalid = model("a(1)", "b");
+        assert!(
+            invalid
+                .to_boolean_network_named(NamingScheme::NameOnly)
+                .is_err()
+        );
+        let bn = invalid
+            .to_boolean_network_named(NamingScheme::NameWithIdSuffix)
+            .unwrap();
+        assert_eq!(names(&bn), vec!["a_1__1", "b_2_b1", "b_2_b2"]);
+
+        // Name clashes are reported as errors.
+        let clash = model("b_b1", "b");
+        assert!(
+            clash
+                .to_boolean_network_named(NamingScheme::NameOnly)
+                .is_err()
+        );
+        assert!(BooleanNetwork::try_from(&clash).is_ok());
+    }
+
+    #[test]
+    fn binarization_with_non_zero_min_level() {
+        let model = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (1, 3))
+            .add_relationship(1, 1, Activator)
+            .build()
+            .unwrap();
+        let bn = BooleanNetwork::try_from(&model).unwrap();
+        let names = bn
+            .variables()
+            .map(|v| bn.get_variable_name(v).clone())
+            .collect::<Vec<_>>();
+        // The lowest level `1` is represented by all bits being unset.
+        assert_eq!(names, vec!["v1_a_b2", "v1_a_b3"]);
+        // The default function is an identity, so every valid state is a fixed point.
+        let stg = SymbolicAsyncGraph::new(&bn).unwrap();
+        let fixed_points = FixedPoints::symbolic(&stg, stg.unit_colored_vertices());
+        assert_eq!(fixed_points.vertices().materialize().iter().count(), 3);
+    }
+
+    #[test]
+    fn symbolic_variable_names() {
+        let model = BmaModelBuilder::new("test")
+            .add_variable(1, "a(x)", (0, 2))
+            .add_variable(2, "", (0, 1))
+            .add_variable(3, "c", (1, 1))
+            .add_relationship(1, 1, Activator)
+            .add_relationship(1, 2, Activator)
+            .build()
+            .unwrap();
+        let names = model.symbolic_variable_names();
+        assert_eq!(names, vec!["v1_a_x__b1", "v1_a_x__b2", "v2_b1", "v3_c_b1"]);
+        let bn = BooleanNetwork::try_from(&model).unwrap();
+        let bn_names = bn
+            .variables()
+            .map(|v| bn.get_variable_name(v).clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, bn_names);
+    }
+
+    #[test]
+    fn binarization_error_context() {
+        let f = |s: &str| Some(BmaUpdateFunction::try_from(s).unwrap());
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 2), None),
+                BmaVariable::new(2, "b", (0, 2), f("var(1) / (var(1) - 1)")),
+            ],
+            vec![BmaRelationship::new_activator(1, 1, 2)],
+        );
+        let model = BmaModel {
+            network,
+            ..Default::default()
+        };
+        let error = BooleanNetwork::try_from(&model).unwrap_err().to_string();
+        assert!(
+            error.starts_with("Cannot binarize variable `2` (`b`): "),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn boolean_network_with_mapping() {
+        let model = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (1, 3))
+            .add_variable(2, "b", (0, 1))
+            .add_variable(3, "c", (2, 2))
+            .add_relationship(1, 1, Activator)
+            .add_relationship(1, 2, Activator)
+            .build()
+            .unwrap();
+        let (bn, mapping) = model.to_boolean_network_with_mapping().unwrap();
+        assert_eq!(mapping.len(), 3);
+        let names = |id: u32| {
+            mapping[&id]
+                .iter()
+                .map(|v| bn.get_variable_name(*v).clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(1), vec!["v1_a_b2", "v1_a_b3"]);
+        assert_eq!(names(2), vec!["v2_b_b1"]);
+        assert_eq!(names(3), vec!["v3_c_b2"]);
+    }
+
+    #[test]
+    fn count_fixed_points_symbolic() {
+        // `a` in [0,2] is an identity, `b` in [0,1] follows `a` (normalized and rounded),
+        // `c` in [1,3] is a constant input `2`.
+        let model = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (0, 2))
+            .add_variable(2, "b", (0, 1))
+            .add_variable(3, "c", (1, 3))
+            .add_relationship(1, 1, Activator)
+            .add_relationship(1, 2, Activator)
+            .set_formula(2, "var(1)")
+            .set_formula(3, "2")
+            .build()
+            .unwrap();
+        assert_eq!(model.count_fixed_points_symbolic().unwrap(), 3);
+
+        // Compare with the fixed points of the corresponding Boolean networks.
+        let paths = [
+            "./models/json-export-from-tool/ToyModelStable.json",
+            "./models/json-export-from-tool/Mutual Inhibition.json",
+            "./models/json-export-from-tool/Homeostasis.json",
+            "./models/json-export-from-tool/Sigmoidal.json",
+        ];
+        for path in paths {
+            let json_data = std::fs::read_to_string(path).unwrap();
+            let model = BmaModel::from_json_string(json_data.as_str()).unwrap();
+            let bn = BooleanNetwork::try_from(&model).unwrap();
+            let stg = SymbolicAsyncGraph::new(&bn).unwrap();
+            let fixed_points = FixedPoints::symbolic(&stg, stg.unit_colored_vertices());
+            let expected = fixed_points.vertices().exact_cardinality().to_u128();
+            assert_eq!(model.count_fixed_points_symbolic().ok(), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn stable_states_under_inputs() {
+        // Same model as in `count_fixed_points_symbolic`.
+        let model = BmaModelBuilder::new("test")
+            .add_variable(1, "a", (0, 2))
+            .add_variable(2, "b", (0, 1))
+            .add_variable(3, "c", (1, 3))
+            .add_relationship(1, 1, Activator)
+            .add_relationship(1, 2, Activator)
+            .set_formula(2, "var(1)")
+            .set_formula(3, "2")
+            .build()
+            .unwrap();
+        let all = model.stable_states_under_inputs(&HashMap::new()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all.iter().all(|state| state[&3] == 2));
+
+        let states = model
+            .stable_states_under_inputs(&HashMap::from([(1, 0)]))
+            .unwrap();
+        assert_eq!(states, vec![BTreeMap::from([(1, 0), (2, 0), (3, 2)])]);
+
+        // Fixing `c` to a level which is not stable on its own.
+        let states = model
+            .stable_states_under_inputs(&HashMap::from([(1, 2), (3, 3)]))
+            .unwrap();
+        assert_eq!(states, vec![BTreeMap::from([(1, 2), (2, 1), (3, 3)])]);
+
+        let invalid_level = HashMap::from([(2, 2)]);
+        assert!(model.stable_states_under_inputs(&invalid_level).is_err());
+        let invalid_var = HashMap::from([(4, 0)]);
+        assert!(model.stable_states_under_inputs(&invalid_var).is_err());
+    }
 
     #[test]
     fn basic_binarization_test() {
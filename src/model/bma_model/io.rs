@@ -0,0 +1,316 @@
+use crate::BmaModel;
+#[cfg(feature = "bn-conversion")]
+use biodivine_lib_param_bn::BooleanNetwork;
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Possible errors when loading a [`BmaModel`] from a file (see [`BmaModel::load_dir`]).
+#[derive(Error, Debug)]
+pub enum BmaIoError {
+    #[error("Cannot read `{path}`: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Invalid JSON model: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid XML model: {0}")]
+    Xml(#[from] serde_xml_rs::Error),
+    #[error("Unsupported model file `{0}` (expected `.json` or `.xml`)")]
+    UnsupportedFormat(PathBuf),
+    #[error("Format `{0}` cannot be used as input")]
+    UnsupportedInput(Format),
+    #[error("Cannot convert model: {0}")]
+    Conversion(String),
+}
+
+impl BmaIoError {
+    /// A stable identifier of the error kind (see [`crate::BmaModelError::code`]).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaIoError::Io { .. } => "io.read",
+            BmaIoError::Json(_) => "io.invalid_json",
+            BmaIoError::Xml(_) => "io.invalid_xml",
+            BmaIoError::UnsupportedFormat(_) => "io.unsupported_format",
+            BmaIoError::UnsupportedInput(_) => "io.unsupported_input",
+            BmaIoError::Conversion(_) => "io.conversion",
+        }
+    }
+}
+
+/// Model formats supported by [`BmaModel::convert`].
+///
+/// The `Aeon`, `SbmlQual`, and `Bnet` formats are handled through the `BooleanNetwork`
+/// conversion, so they require the `bn-conversion` feature (and only work for models that
+/// can be converted, see `BooleanNetwork::try_from`). The `Dot` format (see
+/// [`crate::BmaNetwork::to_dot`]) can be only used as output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Json,
+    Xml,
+    Aeon,
+    SbmlQual,
+    Bnet,
+    Dot,
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Format::Json => "json",
+            Format::Xml => "xml",
+            Format::Aeon => "aeon",
+            Format::SbmlQual => "sbml-qual",
+            Format::Bnet => "bnet",
+            Format::Dot => "dot",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl BmaModel {
+    /// Convert a model given as a string in the `from` format into a string in the `to`
+    /// format.
+    ///
+    /// This is a shorthand for the individual import and export methods (e.g.
+    /// [`BmaModel::from_xml_string`] and [`BmaModel::to_json_string`]). Models imported from
+    /// non-BMA formats have an empty name and a default layout (see
+    /// `BmaModel::from_aeon_string`).
+    pub fn convert(input: &str, from: Format, to: Format) -> Result<String, BmaIoError> {
+        let model = match from {
+            Format::Json => BmaModel::from_json_string(input)?,
+            Format::Xml => BmaModel::from_xml_string(input)?,
+            Format::Aeon | Format::SbmlQual | Format::Bnet => import_boolean_network(input, from)?,
+            Format::Dot => return Err(BmaIoError::UnsupportedInput(from)),
+        };
+        match to {
+            Format::Json => Ok(model.to_json_string()?),
+            Format::Xml => Ok(model.to_xml_string()?),
+            Format::Aeon | Format::SbmlQual | Format::Bnet => export_boolean_network(&model, to),
+            Format::Dot => Ok(model.network.to_dot()),
+        }
+    }
+}
+
+/// Import a model from one of the formats supported by [`BooleanNetwork`].
+#[cfg(feature = "bn-conversion")]
+fn import_boolean_network(input: &str, format: Format) -> Result<BmaModel, BmaIoError> {
+    let bn = match format {
+        Format::Aeon => BooleanNetwork::try_from(input),
+        Format::SbmlQual => BooleanNetwork::try_from_sbml(input).map(|(bn, _)| bn),
+        Format::Bnet => BooleanNetwork::try_from_bnet(input),
+        _ => unreachable!("Format `{format}` is not a Boolean network format."),
+    };
+    let bn = bn.map_err(BmaIoError::Conversion)?;
+    BmaModel::try_from(&bn).map_err(|e| BmaIoError::Conversion(e.to_string()))
+}
+
+/// Export a model into one of the formats supported by [`BooleanNetwork`].
+#[cfg(feature = "bn-conversion")]
+fn export_boolean_network(model: &BmaModel, format: Format) -> Result<String, BmaIoError> {
+    if format == Format::Aeon {
+        return model
+            .to_aeon_string()
+            .map_err(|e| BmaIoError::Conversion(e.to_string()));
+    }
+    let bn = BooleanNetwork::try_from(model)
+        .and_then(|bn| bn.infer_valid_graph().map_err(|e| anyhow::anyhow!(e)))
+        .map_err(|e| BmaIoError::Conversion(e.to_string()))?;
+    match format {
+        Format::SbmlQual => Ok(bn.to_sbml(None)),
+        Format::Bnet => bn.to_bnet(true).map_err(BmaIoError::Conversion),
+        _ => unreachable!("Format `{format}` is not a Boolean network format."),
+    }
+}
+
+#[cfg(not(feature = "bn-conversion"))]
+fn import_boolean_network(_input: &str, format: Format) -> Result<BmaModel, BmaIoError> {
+    Err(BmaIoError::Conversion(format!(
+        "Format `{format}` requires the `bn-conversion` feature"
+    )))
+}
+
+#[cfg(not(feature = "bn-conversion"))]
+fn export_boolean_network(_model: &BmaModel, format: Format) -> Result<String, BmaIoError> {
+    Err(BmaIoError::Conversion(format!(
+        "Format `{format}` requires the `bn-conversion` feature"
+    )))
+}
+
+impl BmaModel {
+    /// Load all `.json` and `.xml` model files stored directly in the given directory
+    /// (subdirectories are not searched).
+    ///
+    /// Each file path is paired with the result of parsing the file. The results are
+    /// sorted by path. Note that the models are only parsed, not validated. If the directory
+    /// itself cannot be read, the result contains a single error for the directory path.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> Vec<(PathBuf, Result<BmaModel, BmaIoError>)> {
+        let dir = dir.as_ref();
+        let io_error = |path: &Path, source| BmaIoError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => return vec![(dir.to_path_buf(), Err(io_error(dir, e)))],
+        };
+
+        let mut paths = Vec::new();
+        for entry in entries {
+            match entry {
+                Ok(entry) if model_format(&entry.path()).is_some() && entry.path().is_file() => {
+                    paths.push(entry.path());
+                }
+                Ok(_) => (),
+                Err(e) => return vec![(dir.to_path_buf(), Err(io_error(dir, e)))],
+            }
+        }
+        paths.sort();
+
+        paths
+            .into_iter()
+            .map(|path| {
+                let result = load_file(&path);
+                (path, result)
+            })
+            .collect()
+    }
+}
+
+impl BmaModel {
+    /// Lazily read models from newline-delimited JSON, where every line contains one model
+    /// in the BMA JSON format (see [`BmaModel::from_json_string`]). Blank lines are skipped.
+    ///
+    /// Each line is read and parsed only once the iterator reaches it. A line that cannot be
+    /// parsed produces an error, but the iteration continues with the next line. Errors of
+    /// the underlying reader are reported as [`serde_json::Error`] as well (see
+    /// [`serde_json::Error::io`]).
+    pub fn from_ndjson<R: BufRead>(
+        reader: R,
+    ) -> impl Iterator<Item = Result<BmaModel, serde_json::Error>> {
+        reader.lines().filter_map(|line| match line {
+            Err(e) => Some(Err(serde_json::Error::io(e))),
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(BmaModel::from_json_string(&line)),
+        })
+    }
+}
+
+/// Return the lowercase extension of a supported model file (`json` or `xml`).
+fn model_format(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    (extension == "json" || extension == "xml").then_some(extension)
+}
+
+/// Load a single model file, using the file extension to determine its format.
+fn load_file(path: &Path) -> Result<BmaModel, BmaIoError> {
+    let content = std::fs::read_to_string(path).map_err(|source| BmaIoError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    match model_format(path).as_deref() {
+        Some("json") => Ok(BmaModel::from_json_string(content.as_str())?),
+        Some("xml") => Ok(BmaModel::from_xml_string(content.as_str())?),
+        _ => Err(BmaIoError::UnsupportedFormat(path.to_path_buf())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BmaIoError, BmaModel, Format};
+
+    #[test]
+    fn load_model_repository() {
+        let loaded = BmaModel::load_dir("./models/xml-repo");
+        let expected = std::fs::read_dir("./models/xml-repo").unwrap().count();
+        assert_eq!(loaded.len(), expected);
+        assert!(loaded.iter().all(|(_, result)| result.is_ok()));
+        assert!(loaded.is_sorted_by(|(a, _), (b, _)| a <= b));
+
+        let missing = BmaModel::load_dir("./models/missing-folder");
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].1.as_ref().unwrap_err().code(), "io.read");
+    }
+
+    #[test]
+    fn load_invalid_files() {
+        let dir = std::env::temp_dir().join(format!("bma-load-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.json"), "{ not a model").unwrap();
+        std::fs::write(dir.join("broken.XML"), "<Model>").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        let model = BmaModel::default().to_json_string().unwrap();
+        std::fs::write(dir.join("valid.json"), model).unwrap();
+
+        let loaded = BmaModel::load_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let results = loaded
+            .iter()
+            .map(|(path, result)| {
+                let name = path.file_name().unwrap().to_str().unwrap();
+                (name, result.as_ref().map_err(BmaIoError::code).err())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                ("broken.XML", Some("io.invalid_xml")),
+                ("broken.json", Some("io.invalid_json")),
+                ("valid.json", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn read_ndjson() {
+        let mut model = BmaModel::empty("First");
+        let first = model.to_json_string().unwrap();
+        model.network.name = "Second".to_string();
+        let second = model.to_json_string().unwrap();
+        let input = format!("{first}\n\n{{ not a model\n{second}\n");
+
+        let results = BmaModel::from_ndjson(input.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().network.name, "First");
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().network.name, "Second");
+    }
+
+    #[test]
+    fn convert_between_formats() {
+        let xml = std::fs::read_to_string("./models/xml-repo/BooleanLoop.xml").unwrap();
+        let json = BmaModel::convert(&xml, Format::Xml, Format::Json).unwrap();
+        let expected = BmaModel::from_xml_string(&xml).unwrap();
+        let converted = BmaModel::from_json_string(&json).unwrap();
+        assert_eq!(converted.network, expected.network);
+
+        let dot = BmaModel::convert(&json, Format::Json, Format::Dot).unwrap();
+        assert_eq!(dot, expected.network.to_dot());
+
+        let error = BmaModel::convert(&dot, Format::Dot, Format::Json).unwrap_err();
+        assert_eq!(error.code(), "io.unsupported_input");
+        let error = BmaModel::convert("{ not a model", Format::Json, Format::Xml).unwrap_err();
+        assert_eq!(error.code(), "io.invalid_json");
+    }
+
+    #[test]
+    #[cfg(feature = "bn-conversion")]
+    fn convert_boolean_formats() {
+        let aeon = "a -> b\nb -| a\n$a: !b\n$b: a\n";
+        let model = BmaModel::convert(aeon, Format::Aeon, Format::Json).unwrap();
+        let model = BmaModel::from_json_string(&model).unwrap();
+        assert_eq!(model.network.variables.len(), 2);
+
+        let json = model.to_json_string().unwrap();
+        for format in [Format::Aeon, Format::SbmlQual, Format::Bnet] {
+            let output = BmaModel::convert(&json, Format::Json, format).unwrap();
+            let back = BmaModel::convert(&output, format, Format::Json).unwrap();
+            let back = BmaModel::from_json_string(&back).unwrap();
+            assert_eq!(back.network.variables.len(), 2, "{format}");
+        }
+    }
+}
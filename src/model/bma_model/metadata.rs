@@ -0,0 +1,81 @@
+use crate::BmaModel;
+
+/// Typed access to the provenance [`BmaModel::metadata`] entries that are present in
+/// the XML format (and written back by [`BmaModel::to_xml_string`]).
+impl BmaModel {
+    /// Metadata key of the `BioCheckVersion` XML attribute.
+    pub const BIOCHECK_VERSION_KEY: &'static str = "biocheck_version";
+    /// Metadata key of the `CreatedDate` XML element.
+    pub const CREATED_DATE_KEY: &'static str = "created_date";
+    /// Metadata key of the `ModifiedDate` XML element.
+    pub const MODIFIED_DATE_KEY: &'static str = "modified_date";
+
+    /// The version of the BMA tool that created this model (if known).
+    #[must_use]
+    pub fn biocheck_version(&self) -> Option<&str> {
+        self.metadata_value(Self::BIOCHECK_VERSION_KEY)
+    }
+
+    /// The date when this model was created (if known). The value is not validated.
+    #[must_use]
+    pub fn created_date(&self) -> Option<&str> {
+        self.metadata_value(Self::CREATED_DATE_KEY)
+    }
+
+    /// The date when this model was last modified (if known). The value is not validated.
+    #[must_use]
+    pub fn modified_date(&self) -> Option<&str> {
+        self.metadata_value(Self::MODIFIED_DATE_KEY)
+    }
+
+    /// Set the version of the BMA tool that created this model. Returns the previous value.
+    pub fn set_biocheck_version(&mut self, version: &str) -> Option<String> {
+        self.set_metadata_value(Self::BIOCHECK_VERSION_KEY, version)
+    }
+
+    /// Set the date when this model was created. Returns the previous value.
+    pub fn set_created_date(&mut self, date: &str) -> Option<String> {
+        self.set_metadata_value(Self::CREATED_DATE_KEY, date)
+    }
+
+    /// Set the date when this model was last modified. Returns the previous value.
+    pub fn set_modified_date(&mut self, date: &str) -> Option<String> {
+        self.set_metadata_value(Self::MODIFIED_DATE_KEY, date)
+    }
+
+    fn metadata_value(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    fn set_metadata_value(&mut self, key: &str, value: &str) -> Option<String> {
+        self.metadata.insert(key.to_string(), value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BmaModel;
+
+    #[test]
+    fn xml_provenance_metadata() {
+        let xml_data = std::fs::read_to_string("./models/xml-repo/BooleanLoop.xml").unwrap();
+        let mut model = BmaModel::from_xml_string(xml_data.as_str()).unwrap();
+        assert_eq!(model.biocheck_version(), Some("3"));
+        assert!(model.created_date().unwrap().starts_with("2012-10-20"));
+        assert_eq!(model.modified_date(), model.created_date());
+
+        assert_eq!(
+            model.set_modified_date("2024-01-01"),
+            model.metadata.get("created_date").cloned()
+        );
+        assert_eq!(model.set_biocheck_version("4"), Some("3".to_string()));
+        let exported = model.to_xml_string().unwrap();
+        assert!(exported.contains("BioCheckVersion=\"4\""));
+        assert!(exported.contains("<ModifiedDate>2024-01-01</ModifiedDate>"));
+
+        let imported = BmaModel::from_xml_string(&exported).unwrap();
+        assert_eq!(imported.biocheck_version(), Some("4"));
+        assert_eq!(imported.created_date(), model.created_date());
+        assert_eq!(imported.modified_date(), Some("2024-01-01"));
+    }
+}
@@ -1,16 +1,31 @@
+pub(crate) mod builder;
+pub(crate) mod constant_folding;
+pub(crate) mod containers;
+pub(crate) mod fingerprint;
+#[cfg(feature = "bn-conversion")]
 pub(crate) mod from_aeon;
+pub(crate) mod graphml;
+#[cfg(feature = "bn-conversion")]
 pub(crate) mod into_aeon;
+pub(crate) mod io;
+pub(crate) mod metadata;
+pub(crate) mod source_spans;
+pub(crate) mod statistics;
 
+use crate::model::bma_variable::RegulatorErrorType;
 use crate::serde::json::JsonBmaModel;
 use crate::serde::xml::XmlBmaModel;
+use crate::update_function::BmaUpdateFunction;
 use crate::{
-    BmaLayout, BmaLayoutError, BmaNetwork, BmaNetworkError, ContextualValidation, ErrorReporter,
-    Validation,
+    BmaLayout, BmaLayoutError, BmaLayoutVariable, BmaNetwork, BmaNetworkError,
+    BmaRelationshipError, BmaVariableError, CallbackReporter, ContextualValidation, ErrorReporter,
+    LimitReporter, Validation, VecReporter,
 };
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use thiserror::Error;
 
 /// Main structure with all the important parts of a BMA model.
@@ -24,6 +39,10 @@ use thiserror::Error;
 /// from a string. For serialization to JSON or XML, use custom methods
 /// [`BmaModel::to_json_string`], [`BmaModel::to_json_string_pretty`], or
 /// [`BmaModel::to_xml_string`].
+///
+/// The [`Default`] model (see also [`BmaModel::empty`]) has an unnamed network without
+/// variables and relationships, a layout without variables and containers (with an empty
+/// description and no zoom or pan), and no metadata. Such a model is valid.
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct BmaModel {
@@ -33,9 +52,9 @@ pub struct BmaModel {
     /// Layout can be empty, but it is recommended to provide it.
     pub layout: BmaLayout,
     /// Stores additional metadata like `biocheck_version` that is sometimes present in the XML.
-    /// Metadata is usually empty.
+    /// Metadata is usually empty. The keys are sorted, such that serialization is deterministic.
     #[serde(flatten)]
-    pub metadata: HashMap<String, String>,
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl BmaModel {
@@ -49,11 +68,38 @@ impl BmaModel {
         serde_json::to_string_pretty(&JsonBmaModel::from(self.clone()))
     }
 
+    /// Convert the `BmaModel` into a JSON string in the exact format that is exported
+    /// (and expected) by the BMA tool.
+    ///
+    /// The keys use the same capitalization as [`BmaModel::to_json_string`] (`Model`, `Variables`,
+    /// `RangeFrom`, `Formula`, ...), but the output also contains all the fields that the tool
+    /// writes, even when they are not used by this library (`AnnotatedGridCells` and `ltl`).
+    /// Integral numbers (e.g. positions and angles) are written without the decimal part.
+    pub fn to_json_string_tool_compatible(&self) -> Result<String, serde_json::Error> {
+        let value = JsonBmaModel::from(self.clone()).to_tool_json()?;
+        serde_json::to_string(&value)
+    }
+
     /// Create a new BMA model from a model string in the BMA JSON format.
     pub fn from_json_string(json_str: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str::<JsonBmaModel>(json_str).map(BmaModel::from)
     }
 
+    /// Convert the `BmaModel` into a [`serde_json::Value`] in the BMA JSON format.
+    ///
+    /// The result is the same as parsing the output of [`BmaModel::to_json_string`], but
+    /// it avoids the intermediate string, e.g. when the model is embedded into a larger
+    /// JSON document.
+    pub fn to_json_value(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(JsonBmaModel::from(self.clone()))
+    }
+
+    /// Create a new BMA model from a [`serde_json::Value`] in the BMA JSON format
+    /// (see also [`BmaModel::from_json_string`]).
+    pub fn from_json_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        serde_json::from_value::<JsonBmaModel>(value).map(BmaModel::from)
+    }
+
     /// Create a new BMA model from a model string in XML format.
     /// Internally, we use `serde_xml_rs` serialization into an intermediate `XmlBmaModel` structure.
     pub fn from_xml_string(xml_str: &str) -> Result<Self, serde_xml_rs::Error> {
@@ -68,7 +114,7 @@ impl BmaModel {
     /// Create a new BMA model with a given network, layout, and metadata.
     /// This is just a constructor wrapper, it does not check the validity of the model.
     #[must_use]
-    pub fn new(network: BmaNetwork, layout: BmaLayout, metadata: HashMap<String, String>) -> Self {
+    pub fn new(network: BmaNetwork, layout: BmaLayout, metadata: BTreeMap<String, String>) -> Self {
         BmaModel {
             network,
             layout,
@@ -76,12 +122,172 @@ impl BmaModel {
         }
     }
 
+    /// Create a new empty BMA model whose network has the given `name`. Otherwise, the model
+    /// is the same as [`BmaModel::default`]: it has no variables, relationships, layout
+    /// elements, or metadata.
+    #[must_use]
+    pub fn empty(name: &str) -> Self {
+        let mut model = BmaModel::default();
+        model.network.name = name.to_string();
+        model
+    }
+
     /// Check if all variables in the model are Boolean (max level is 1).
     #[must_use]
     pub fn is_boolean(&self) -> bool {
         self.get_max_var_level() <= 1
     }
 
+    /// Check if any update function of this model references a variable by its name
+    /// (see [`BmaUpdateFunction::uses_name_references`]).
+    ///
+    /// Valid update functions are stored with all names resolved to IDs, so for these, the
+    /// style of the original expression is recorded when the model is imported from JSON
+    /// or XML (functions created in code are never considered to use names). Invalid update
+    /// functions keep their original expression, which is checked directly. In both cases,
+    /// renaming a variable would break the function in the source file (or change how the
+    /// invalid expression is resolved).
+    #[must_use]
+    pub fn uses_name_references(&self) -> bool {
+        let styles = &self.network.reference_styles;
+        self.network.variables.iter().any(|var| match &var.formula {
+            Some(Ok(_)) => styles.by_name.contains(&var.id),
+            Some(Err(error)) => BmaUpdateFunction::uses_name_references(&error.expression),
+            None => false,
+        })
+    }
+
+    /// Get the description of the given variable (stored in its [`crate::BmaLayoutVariable`]).
+    ///
+    /// Returns `None` if the variable has no layout information.
+    #[must_use]
+    pub fn variable_description(&self, var_id: u32) -> Option<&str> {
+        self.layout
+            .find_variable(var_id)
+            .map(|v| v.description.as_str())
+    }
+
+    /// Set the description of the given variable. If the variable has no layout information,
+    /// a new [`crate::BmaLayoutVariable`] is created for it.
+    ///
+    /// Returns the previous description (if the layout variable existed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given `var_id` does not reference a network variable.
+    pub fn set_variable_description(&mut self, var_id: u32, description: &str) -> Option<String> {
+        let variable = self
+            .network
+            .find_variable(var_id)
+            .expect("Precondition violated: No variable with given id.");
+        if let Some(layout) = self.layout.variables.iter_mut().find(|v| v.id == var_id) {
+            return Some(std::mem::replace(
+                &mut layout.description,
+                description.to_string(),
+            ));
+        }
+        let mut layout = BmaLayoutVariable::new(var_id, variable.name.as_str(), None);
+        layout.description = description.to_string();
+        self.layout.variables.push(layout);
+        None
+    }
+
+    /// Duplicate the variable `source_id` and return the ID of the new variable (the largest
+    /// existing variable ID plus one), or `None` if the source variable does not exist.
+    ///
+    /// The copy has the same name and range. Its update function is copied as well, but
+    /// references to the source variable are replaced by references to the copy. Every
+    /// relationship of the source variable is copied (with new relationship IDs), including
+    /// self-loops, which become self-loops of the copy. If the source variable has layout
+    /// information, the copy is placed at the source position moved by `offset`.
+    ///
+    /// The result is also `None` (and the model is not modified) if the `offset` is not
+    /// finite, or if the new IDs or the new position cannot be represented.
+    pub fn clone_variable(&mut self, source_id: u32, offset: (f64, f64)) -> Option<u32> {
+        let source = self.network.find_variable(source_id)?;
+        // Non-finite (and out of range) offsets cannot be converted to decimals.
+        let offset = (
+            Decimal::try_from(offset.0).ok()?,
+            Decimal::try_from(offset.1).ok()?,
+        );
+        let new_id = self
+            .network
+            .variables
+            .iter()
+            .map(|v| v.id)
+            .chain(self.layout.variables.iter().map(|v| v.id))
+            .max()
+            .unwrap_or(0)
+            .checked_add(1)?;
+
+        let mut variable = source.clone();
+        variable.id = new_id;
+        if let Some(Ok(function)) = &variable.formula {
+            let replacement = BmaUpdateFunction::mk_variable(new_id);
+            variable.formula = Some(Ok(function.substitute_variable(source_id, &replacement)));
+        }
+
+        let first_id = match self.network.relationships.iter().map(|r| r.id).max() {
+            Some(id) => id.checked_add(1)?,
+            None => 0,
+        };
+        let mut relationship_ids = first_id..=u32::MAX;
+        let mut relationships = Vec::new();
+        for relationship in &self.network.relationships {
+            if relationship.from_variable != source_id && relationship.to_variable != source_id {
+                continue;
+            }
+            let mut copy = relationship.clone();
+            copy.id = relationship_ids.next()?;
+            if copy.from_variable == source_id {
+                copy.from_variable = new_id;
+            }
+            if copy.to_variable == source_id {
+                copy.to_variable = new_id;
+            }
+            relationships.push(copy);
+        }
+
+        let layout = match self.layout.find_variable(source_id) {
+            Some(layout) => {
+                let mut copy = layout.clone();
+                copy.id = new_id;
+                copy.position = (
+                    copy.position.0.checked_add(offset.0)?,
+                    copy.position.1.checked_add(offset.1)?,
+                );
+                Some(copy)
+            }
+            None => None,
+        };
+
+        self.network.variables.push(variable);
+        self.network.relationships.extend(relationships);
+        self.layout.variables.extend(layout);
+        Some(new_id)
+    }
+
+    /// Make all implicit information in the model explicit:
+    ///  - [`RelationshipType::Unknown`] relationships are resolved where possible (see
+    ///    [`BmaNetwork::resolve_unknown_relationships`]);
+    ///  - missing update functions are replaced by their defaults (see
+    ///    [`BmaNetwork::populate_missing_functions`]);
+    ///  - variables, relationships, layout variables, and containers are sorted by ID.
+    ///
+    /// Note that update functions always reference variables by ID once parsed (names are
+    /// resolved while reading the model), so no name references remain after flattening.
+    ///
+    /// [`RelationshipType::Unknown`]: crate::RelationshipType::Unknown
+    pub fn flatten(&mut self) {
+        // Unknown relationships must be resolved first, since they affect default functions.
+        self.network.resolve_unknown_relationships();
+        self.network.populate_missing_functions();
+        self.network.variables.sort_by_key(|v| v.id);
+        self.network.relationships.sort_by_key(|r| r.id);
+        self.layout.variables.sort_by_key(|v| v.id);
+        self.layout.containers.sort_by_key(|c| c.id);
+    }
+
     /// Get the maximum level across all variables in the BMA model.
     #[must_use]
     pub fn get_max_var_level(&self) -> u32 {
@@ -102,11 +308,214 @@ pub enum BmaModelError {
     Layout(#[from] BmaLayoutError),
 }
 
+impl BmaModelError {
+    /// A stable identifier of the error kind (e.g. `"variable.range_invalid"`), which can be
+    /// used to handle errors programmatically (e.g. to map them to localized messages)
+    /// instead of relying on the `Display` text.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaModelError::Network(e) => e.code(),
+            BmaModelError::Layout(e) => e.code(),
+        }
+    }
+}
+
 impl Validation for BmaModel {
     type Error = BmaModelError;
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, reporter: &mut R) {
         self.network.validate_all(&mut reporter.wrap());
+        if !reporter.is_full() {
+            self.layout.validate_all(self, &mut reporter.wrap());
+        }
+    }
+}
+
+impl BmaModel {
+    /// The same as [`Validation::validate`], but instead of
+    /// [`crate::BmaVariable::DEFAULT_MAX_RANGE_SIZE`], variable ranges can have at most
+    /// `max_range_size` levels before [`crate::BmaVariableError::RangeTooLarge`] is reported.
+    pub fn validate_with_max_range_size(
+        &self,
+        max_range_size: u32,
+    ) -> Result<(), Vec<BmaModelError>> {
+        let mut reporter = VecReporter::new();
+        self.network
+            .validate_all_with_max_range(max_range_size, &mut reporter.wrap());
         self.layout.validate_all(self, &mut reporter.wrap());
+        reporter.into_result()
+    }
+
+    /// The same as [`Validation::validate`], but instead of collecting the issues, `on_issue`
+    /// is called for each issue as soon as it is found. The issues are reported in the same
+    /// order as in [`Validation::validate`].
+    pub fn validate_with_callback<F: FnMut(&BmaModelError)>(&self, on_issue: F) {
+        let mut reporter = CallbackReporter::new(on_issue);
+        self.validate_all(&mut reporter);
+    }
+
+    /// The same as [`Validation::validate`], but at most `max` errors are reported (see
+    /// [`LimitReporter`]). Once the limit is reached, the remaining checks are skipped,
+    /// which avoids building the function tables of the remaining variables.
+    ///
+    /// A `max` of `0` is treated as `1`, i.e. an invalid model is never reported as valid.
+    pub fn validate_limited(&self, max: usize) -> Result<(), Vec<BmaModelError>> {
+        let mut reporter = LimitReporter::new(max);
+        self.validate_all(&mut reporter);
+        reporter.into_result()
+    }
+
+    /// Run only the validation checks of the network variable with the given `id`
+    /// (see [`ContextualValidation`] for [`crate::BmaVariable`]). This is much cheaper than
+    /// validating the whole model, because only the function table of this variable is built.
+    ///
+    /// If there are multiple variables with the same `id`, all of them are validated. If there
+    /// is no such variable, the result is empty.
+    #[must_use]
+    pub fn validate_variable(&self, id: u32) -> Vec<BmaVariableError> {
+        let mut reporter = VecReporter::new();
+        for variable in self.network.variables.iter().filter(|v| v.id == id) {
+            variable.validate_all(&self.network, &mut reporter);
+        }
+        reporter.into_result().err().unwrap_or_default()
+    }
+
+    /// Run only the validation checks of the network relationship with the given `id`
+    /// (see [`ContextualValidation`] for [`crate::BmaRelationship`]).
+    ///
+    /// If there are multiple relationships with the same `id`, all of them are validated.
+    /// If there is no such relationship, the result is empty.
+    #[must_use]
+    pub fn validate_relationship(&self, id: u32) -> Vec<BmaRelationshipError> {
+        let mut reporter = VecReporter::new();
+        for relationship in self.network.relationships.iter().filter(|r| r.id == id) {
+            relationship.validate_all(&self.network, &mut reporter);
+        }
+        reporter.into_result().err().unwrap_or_default()
+    }
+
+    /// Validate the network of this model (see [`Validation::validate`]) and group the variable
+    /// errors by the ID of the variable they concern (see [`BmaVariableError::variable_id`]).
+    ///
+    /// Variables without errors are not present in the result. Relationship errors are not
+    /// included (see [`BmaModel::validate_by_relationship`]).
+    #[must_use]
+    pub fn validate_by_variable(&self) -> HashMap<u32, Vec<BmaVariableError>> {
+        let mut result: HashMap<u32, Vec<BmaVariableError>> = HashMap::new();
+        for error in self.network_errors() {
+            if let BmaNetworkError::Variable(error) = error {
+                result.entry(error.variable_id()).or_default().push(error);
+            }
+        }
+        result
+    }
+
+    /// Validate the network of this model (see [`Validation::validate`]) and group the
+    /// relationship errors by the ID of the relationship they concern (see
+    /// [`BmaRelationshipError::relationship_id`]).
+    ///
+    /// Relationships without errors are not present in the result. Variable errors are not
+    /// included (see [`BmaModel::validate_by_variable`]).
+    #[must_use]
+    pub fn validate_by_relationship(&self) -> HashMap<u32, Vec<BmaRelationshipError>> {
+        let mut result: HashMap<u32, Vec<BmaRelationshipError>> = HashMap::new();
+        for error in self.network_errors() {
+            if let BmaNetworkError::Relationship(error) = error
+                && let Some(id) = error.relationship_id()
+            {
+                result.entry(id).or_default().push(error);
+            }
+        }
+        result
+    }
+
+    /// All validation errors of the network of this model.
+    fn network_errors(&self) -> Vec<BmaNetworkError> {
+        let mut reporter = VecReporter::new();
+        self.network.validate_all(&mut reporter);
+        reporter.into_result().err().unwrap_or_default()
+    }
+
+    /// Validate only the issues that would make the simulation of this model fail (e.g.
+    /// [`BmaNetwork::async_successors`] or [`BmaNetwork::evaluate`]). Unlike
+    /// [`Validation::validate`], the layout is not checked, and issues that do not affect the
+    /// computation (like unused or incorrectly signed relationships) are not reported.
+    ///
+    /// The reported issues are:
+    ///  - Update functions that cannot be parsed, or that cannot be evaluated for some input
+    ///    valuation (e.g. due to division by zero), including functions of constants that
+    ///    do not match the constant level.
+    ///  - Invalid variable ranges.
+    ///  - Update functions that reference missing variables or undeclared regulators.
+    ///  - Relationships whose regulator or target does not exist.
+    ///
+    /// Note that the function tables of variables with a range that is too large (see
+    /// [`BmaModel::validate_with_max_range_size`]) are not checked.
+    pub fn validate_for_simulation(&self) -> Result<(), Vec<BmaModelError>> {
+        let errors = self
+            .network_errors()
+            .into_iter()
+            .filter(|error| self.breaks_simulation(error))
+            .map(BmaModelError::from)
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Check whether the given network validation error can make the simulation fail
+    /// (see [`BmaModel::validate_for_simulation`]).
+    fn breaks_simulation(&self, error: &BmaNetworkError) -> bool {
+        match error {
+            BmaNetworkError::Variable(error) => match error {
+                BmaVariableError::RangeInvalid { .. }
+                | BmaVariableError::UpdateFunctionExpressionInvalid { .. }
+                | BmaVariableError::CannotBuildFunctionTable { .. } => true,
+                BmaVariableError::UpdateFunctionRegulatorInvalid { source, .. } => matches!(
+                    source,
+                    RegulatorErrorType::MissingVariable
+                        | RegulatorErrorType::MissingRelationship
+                        | RegulatorErrorType::SelfReferenceWithoutLoop
+                ),
+                // BMA tolerates non-constant functions of constants, only mismatched
+                // constant values make the evaluation fail.
+                BmaVariableError::ConstantWithUpdateFunction { id, .. } => {
+                    self.network.compile_variable(*id).is_err()
+                }
+                BmaVariableError::IdNotUnique { .. }
+                | BmaVariableError::RangeTooLarge { .. }
+                | BmaVariableError::ConstantWithRegulators { .. } => false,
+            },
+            BmaNetworkError::Relationship(error) => matches!(
+                error,
+                BmaRelationshipError::RegulatorVariableNotFound { .. }
+                    | BmaRelationshipError::TargetVariableNotFound { .. }
+            ),
+            BmaNetworkError::ModelStyleInconsistent { .. } => false,
+        }
+    }
+
+    /// Validate the model using [`Validation::validate`], but also report issues that
+    /// are normally tolerated, because they do not change the meaning of the model.
+    ///
+    /// On top of the regular validation, the strict-only checks are:
+    ///  - [`crate::BmaRelationshipError::DuplicateRelationship`]: Two relationships have the same
+    ///    regulator, target, and type (reported for every relationship but the first).
+    ///  - [`crate::BmaLayoutVariableError::NameMismatch`]: The name of a layout variable differs
+    ///    from the name of the corresponding network variable.
+    ///  - [`crate::BmaLayoutContainerError::DefaultedSize`]: The container size is missing
+    ///    or zero and is treated as `1`.
+    ///  - [`crate::BmaNetworkError::ModelStyleInconsistent`]: Some update functions (as written
+    ///    in the imported file) reference variables by name and others by ID, which suggests
+    ///    the model was assembled from inconsistent sources.
+    pub fn validate_strict(&self) -> Result<(), Vec<BmaModelError>> {
+        let mut reporter = VecReporter::new();
+        self.validate_all(&mut reporter);
+        self.network.validate_strict_all(&mut reporter.wrap());
+        self.layout.validate_strict_all(self, &mut reporter.wrap());
+        reporter.into_result()
     }
 }
 
@@ -114,6 +523,7 @@ impl Validation for BmaModel {
 mod tests {
     use crate::model::bma_variable::RegulatorErrorType::UnusedRelationship;
     use crate::model::tests::{simple_layout, simple_network};
+    use crate::update_function::BmaUpdateFunction;
     use crate::{
         BmaLayout, BmaLayoutContainer, BmaLayoutContainerError, BmaLayoutError, BmaLayoutVariable,
         BmaLayoutVariableError, BmaModel, BmaModelError, BmaNetwork, BmaNetworkError,
@@ -127,12 +537,19 @@ mod tests {
     use BmaVariableError::{RangeInvalid, UpdateFunctionRegulatorInvalid};
     use RelationshipType::{Activator, Inhibitor};
     use rust_decimal::Decimal;
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeMap, HashSet};
 
     #[test]
     fn default_model_is_valid() {
         let model = BmaModel::default();
         assert!(model.validate().is_ok());
+
+        let model = BmaModel::empty("Blank model");
+        assert!(model.validate().is_ok());
+        assert!(model.network.is_empty());
+        assert_eq!(model.network.name, "Blank model");
+        assert_eq!(model.layout, BmaLayout::default());
+        assert!(model.metadata.is_empty());
     }
 
     #[test]
@@ -140,7 +557,7 @@ mod tests {
         let model = BmaModel {
             network: simple_network(),
             layout: simple_layout(),
-            metadata: HashMap::default(),
+            metadata: BTreeMap::default(),
         };
         model.validate().unwrap();
         assert!(!model.is_boolean());
@@ -148,7 +565,176 @@ mod tests {
     }
 
     #[test]
-    fn complex_error_example() {
+    fn strict_validation() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            metadata: BTreeMap::default(),
+        };
+        // The simple layout uses different variable names.
+        assert_eq!(model.validate_strict().unwrap_err().len(), 2);
+        for layout_var in &mut model.layout.variables {
+            let bma_var = model.network.find_variable(layout_var.id).unwrap();
+            layout_var.name.clone_from(&bma_var.name);
+        }
+        model.validate_strict().unwrap();
+
+        model
+            .network
+            .relationships
+            .push(BmaRelationship::new_activator(7, 0, 3));
+        model.layout.variables[0].name = "renamed".to_string();
+        let network_name = model
+            .network
+            .find_variable(model.layout.variables[0].id)
+            .unwrap()
+            .name
+            .clone();
+
+        // The issues are tolerated by regular validation.
+        assert!(model.validate().is_ok());
+
+        let expected = vec![
+            Network(Relationship(BmaRelationshipError::DuplicateRelationship {
+                id: 7,
+                from_variable: 0,
+                to_variable: 3,
+                duplicate_of: 0,
+            })),
+            Layout(BmaLayoutError::Variable(
+                BmaLayoutVariableError::NameMismatch {
+                    id: model.layout.variables[0].id,
+                    name: "renamed".to_string(),
+                    network_name,
+                },
+            )),
+        ];
+        assert_eq!(model.validate_strict().unwrap_err(), expected);
+    }
+
+    #[test]
+    fn strict_model_style_inconsistent() {
+        let style_error = |result: Result<(), Vec<BmaModelError>>| {
+            result
+                .err()
+                .unwrap_or_default()
+                .into_iter()
+                .find(|e| e.code() == "network.model_style_inconsistent")
+        };
+
+        // The style of valid functions is recorded during import.
+        let json = r#"{"Model": {"Name": "m", "Variables": [
+            {"Id": 1, "Name": "a", "RangeFrom": 0, "RangeTo": 1, "Formula": "var(b)"},
+            {"Id": 2, "Name": "b", "RangeFrom": 0, "RangeTo": 1, "Formula": "var(a)"}
+        ], "Relationships": [
+            {"Id": 3, "FromVariable": 1, "ToVariable": 2, "Type": "Activator"},
+            {"Id": 4, "FromVariable": 2, "ToVariable": 1, "Type": "Activator"}
+        ]}}"#;
+        let model = BmaModel::from_json_string(json).unwrap();
+        model.validate().unwrap();
+        assert!(style_error(model.validate_strict()).is_none());
+
+        let mixed = BmaModel::from_json_string(&json.replace("var(a)", "var(1)")).unwrap();
+        mixed.validate().unwrap();
+        assert_eq!(
+            style_error(mixed.validate_strict()).unwrap(),
+            Network(BmaNetworkError::ModelStyleInconsistent {
+                by_name: vec![1],
+                by_id: vec![2],
+            })
+        );
+        // The parsed functions are the same, only the recorded style differs.
+        assert_eq!(mixed.network.variables, model.network.variables);
+        // Exported functions always reference variables by ID.
+        let exported = BmaModel::from_json_string(&mixed.to_json_string().unwrap()).unwrap();
+        assert!(style_error(exported.validate_strict()).is_none());
+
+        // Invalid functions are checked using their original expressions.
+        let mut model = BmaModel::new(simple_network(), simple_layout(), BTreeMap::new());
+        model.network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(var_B) +"));
+        model.network.variables[1].formula = Some(BmaUpdateFunction::try_from("var(var_A) *"));
+        assert!(style_error(model.validate_strict()).is_none());
+
+        model.network.variables[1].formula = Some(BmaUpdateFunction::try_from("var(0) *"));
+        assert_eq!(
+            style_error(model.validate_strict()).unwrap(),
+            Network(BmaNetworkError::ModelStyleInconsistent {
+                by_name: vec![3],
+                by_id: vec![0],
+            })
+        );
+        assert!(style_error(model.validate()).is_none());
+    }
+
+    #[test]
+    fn conflicting_signs() {
+        let model = BmaModel {
+            network: BmaNetwork::new(
+                vec![
+                    BmaVariable::new_boolean(1, "a", None),
+                    BmaVariable::new_boolean(2, "b", None),
+                ],
+                vec![
+                    BmaRelationship::new_activator(3, 1, 2),
+                    BmaRelationship::new_inhibitor(4, 1, 2),
+                    BmaRelationship::new_inhibitor(5, 1, 2),
+                ],
+            ),
+            ..Default::default()
+        };
+        let issues = model.validate_strict().unwrap_err();
+        let conflict = Network(Relationship(BmaRelationshipError::ConflictingSigns {
+            from: 1,
+            to: 2,
+        }));
+        assert_eq!(issues.iter().filter(|it| **it == conflict).count(), 1);
+        assert!(model.validate().unwrap_err().contains(&conflict));
+    }
+
+    #[test]
+    fn configurable_max_range_size() {
+        let model = BmaModel {
+            network: simple_network(),
+            ..Default::default()
+        };
+        model.validate_with_max_range_size(3).unwrap();
+        let issues = model.validate_with_max_range_size(2).unwrap_err();
+        assert_eq!(
+            issues,
+            vec![Network(Variable(BmaVariableError::RangeTooLarge {
+                id: 0,
+                range: (1, 3),
+                max_size: 2,
+            }))]
+        );
+    }
+
+    #[test]
+    fn error_codes() {
+        let error = Network(Variable(RangeInvalid {
+            id: 3,
+            range: (3, 2),
+        }));
+        assert_eq!(error.code(), "variable.range_invalid");
+        let error = Network(Relationship(IdNotUnique { id: 5 }));
+        assert_eq!(error.code(), "relationship.id_not_unique");
+        let error = Network(Variable(UpdateFunctionRegulatorInvalid {
+            id: 2,
+            regulator: 3,
+            expression: "var(3)".to_string(),
+            source: UnusedRelationship,
+        }));
+        assert_eq!(error.code(), "variable.regulator_unused_relationship");
+        let error = Layout(Container(BmaLayoutContainerError::IdNotUnique { id: 4 }));
+        assert_eq!(error.code(), "layout_container.id_not_unique");
+        let error = Layout(BmaLayoutError::Variable(
+            BmaLayoutVariableError::VariableNotFound { id: 1 },
+        ));
+        assert_eq!(error.code(), "layout_variable.variable_not_found");
+    }
+
+    /// A model with issues in every part, together with the expected validation result.
+    fn complex_error_example() -> (BmaModel, Vec<BmaModelError>) {
         let model = BmaModel {
             network: BmaNetwork {
                 name: String::default(),
@@ -162,6 +748,7 @@ mod tests {
                     BmaRelationship::new_inhibitor(5, 3, 2),
                     BmaRelationship::new_inhibitor(6, 3, 4),
                 ],
+                ..Default::default()
             },
             layout: BmaLayout {
                 variables: vec![
@@ -175,8 +762,9 @@ mod tests {
                 description: "Lorem ipsum".to_string(),
                 zoom_level: Some(Decimal::from(10) / Decimal::from(3)),
                 pan: None,
+                ..Default::default()
             },
-            metadata: HashMap::default(),
+            metadata: BTreeMap::default(),
         };
 
         let expected = vec![
@@ -202,6 +790,10 @@ mod tests {
                 id: 6,
                 to_variable: 4,
             })),
+            Network(Relationship(BmaRelationshipError::ConflictingSigns {
+                from: 2,
+                to: 3,
+            })),
             Layout(BmaLayoutError::Variable(
                 BmaLayoutVariableError::ContainerNotFound {
                     id: 2,
@@ -211,9 +803,61 @@ mod tests {
             Layout(Container(BmaLayoutContainerError::IdNotUnique { id: 4 })),
             Layout(Container(BmaLayoutContainerError::IdNotUnique { id: 4 })),
         ];
+        (model, expected)
+    }
 
+    #[test]
+    fn complex_error_example_is_reported() {
+        let (model, expected) = complex_error_example();
         let issues = model.validate().unwrap_err();
         assert_eq!(issues, expected);
+
+        let mut streamed = Vec::new();
+        model.validate_with_callback(|issue| streamed.push(issue.clone()));
+        assert_eq!(streamed, expected);
+        BmaModel::default().validate_with_callback(|issue| panic!("Unexpected issue: {issue}"));
+    }
+
+    #[test]
+    fn complex_error_example_partial_validation() {
+        let (model, expected) = complex_error_example();
+        // Limited validation reports the first issues in the same order.
+        for max in [1, 2, 5, expected.len(), 100] {
+            let limited = model.validate_limited(max).unwrap_err();
+            assert_eq!(limited, expected[..max.min(expected.len())]);
+        }
+        assert_eq!(model.validate_limited(0).unwrap_err(), expected[..1]);
+
+        // Incremental validation only reports issues of the given variable/relationship.
+        let variable_issues = model.validate_variable(3);
+        assert_eq!(variable_issues.len(), 2);
+        assert_eq!(
+            variable_issues[0],
+            RangeInvalid {
+                id: 3,
+                range: (3, 2)
+            }
+        );
+        assert!(model.validate_variable(1).is_empty());
+        let relationship_issues = model.validate_relationship(5);
+        assert_eq!(relationship_issues, vec![IdNotUnique { id: 5 }; 2]);
+        assert!(model.validate_relationship(4).is_empty());
+
+        // Grouped validation.
+        let by_variable = model.validate_by_variable();
+        assert_eq!(by_variable.len(), 2);
+        assert_eq!(by_variable[&2].len(), 1);
+        assert_eq!(by_variable[&3], variable_issues);
+        let by_relationship = model.validate_by_relationship();
+        assert_eq!(by_relationship.len(), 2);
+        assert_eq!(by_relationship[&5], relationship_issues);
+        assert_eq!(
+            by_relationship[&6],
+            vec![TargetVariableNotFound {
+                id: 6,
+                to_variable: 4
+            }]
+        );
     }
 
     #[test]
@@ -248,4 +892,210 @@ mod tests {
         let regulators = model.network.get_regulators(2, &None);
         assert_eq!(regulators, HashSet::from_iter(vec![1, 3]));
     }
+
+    #[test]
+    fn variable_descriptions() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            metadata: BTreeMap::default(),
+        };
+        model.layout.variables.retain(|v| v.id != 3);
+
+        assert_eq!(model.variable_description(0), Some(""));
+        assert_eq!(model.variable_description(3), None);
+        assert_eq!(
+            model.set_variable_description(0, "First"),
+            Some(String::new())
+        );
+        assert_eq!(model.set_variable_description(3, "Second"), None);
+        assert_eq!(model.variable_description(0), Some("First"));
+        assert_eq!(model.variable_description(3), Some("Second"));
+        assert_eq!(model.layout.find_variable(3).unwrap().name, "var_B");
+        model.validate().unwrap();
+    }
+
+    #[test]
+    fn clone_variable() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            metadata: BTreeMap::default(),
+        };
+        model.network.variables[0].formula =
+            Some(BmaUpdateFunction::try_from("min(var(0), 1 - var(3))"));
+        assert_eq!(model.clone_variable(5, (0.0, 0.0)), None);
+        assert_eq!(model.clone_variable(3, (10.0, -5.5)), Some(4));
+
+        let copy = model.network.find_variable(4).unwrap();
+        assert_eq!(copy.name, "var_B");
+        assert_eq!(copy.formula_string(), "min(var(0), (1 - var(4)))");
+        let copied = model
+            .network
+            .relationships
+            .iter()
+            .filter(|r| r.from_variable == 4 || r.to_variable == 4)
+            .map(|r| (r.id, r.from_variable, r.to_variable, r.r#type.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(copied, vec![(2, 0, 4, Activator), (3, 4, 4, Inhibitor)]);
+
+        let layout = model.layout.find_variable(4).unwrap();
+        assert_eq!(layout.position, (Decimal::from(10), Decimal::new(-55, 1)));
+        assert_eq!(layout.container_id, Some(13));
+        model.validate().unwrap();
+
+        // Invalid offsets and overflowing IDs or positions do not modify the model.
+        let original = model.clone();
+        assert_eq!(model.clone_variable(3, (f64::NAN, 0.0)), None);
+        assert_eq!(model.clone_variable(3, (0.0, f64::INFINITY)), None);
+        model.layout.variables[1].position.1 = Decimal::MAX;
+        assert_eq!(model.clone_variable(3, (0.0, 1.0)), None);
+        model.layout.variables[1].position.1 = original.layout.variables[1].position.1;
+        model.network.relationships[0].id = u32::MAX;
+        assert_eq!(model.clone_variable(3, (0.0, 0.0)), None);
+        model.network.relationships[0].id = original.network.relationships[0].id;
+        model.network.variables[0].id = u32::MAX;
+        assert_eq!(model.clone_variable(0, (0.0, 0.0)), None);
+        model.network.variables[0].id = original.network.variables[0].id;
+        assert_eq!(model, original);
+    }
+
+    #[test]
+    fn validate_for_simulation() {
+        let mut model = BmaModel::new(simple_network(), simple_layout(), BTreeMap::new());
+        // Layout issues and monotonicity mismatches do not affect simulation.
+        model.layout.variables[1].container_id = Some(100);
+        model.network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(0) + var(3)"));
+        assert!(model.validate().is_err());
+        assert!(model.validate_for_simulation().is_ok());
+
+        let codes = |model: &BmaModel| {
+            let errors = model.validate_for_simulation().unwrap_err();
+            errors.iter().map(BmaModelError::code).collect::<Vec<_>>()
+        };
+        model.network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(0) / var(3)"));
+        assert_eq!(codes(&model), vec!["variable.cannot_build_function_table"]);
+        model.network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(0) + var(5)"));
+        model.network.variables[1].range = (3, 1);
+        assert_eq!(
+            codes(&model),
+            vec![
+                "variable.regulator_missing_variable",
+                "variable.range_invalid"
+            ]
+        );
+    }
+
+    #[test]
+    fn uses_name_references() {
+        let mut model = BmaModel::new(simple_network(), simple_layout(), BTreeMap::new());
+        // Names in functions created in code are resolved to IDs, so they are not recorded.
+        let hint = [(0, "var_A".to_string()), (3, "var_B".to_string())];
+        let function = BmaUpdateFunction::parse_with_hint("var(var_A) + 1", &hint);
+        model.network.variables[0].formula = Some(function);
+        assert!(!model.uses_name_references());
+
+        let function = BmaUpdateFunction::parse_with_hint("var(var_A) + var(var_C)", &hint);
+        model.network.variables[0].formula = Some(function);
+        assert!(model.uses_name_references());
+
+        let function = BmaUpdateFunction::try_from("var(0) + var(5) +");
+        model.network.variables[0].formula = Some(function);
+        assert!(!model.uses_name_references());
+
+        // The style of valid functions is recorded during import.
+        let json = r#"{"Model": {"Name": "m", "Variables": [
+            {"Id": 1, "Name": "a", "RangeFrom": 0, "RangeTo": 1, "Formula": ""},
+            {"Id": 2, "Name": "b", "RangeFrom": 0, "RangeTo": 1, "Formula": "var(a)"}
+        ], "Relationships": [
+            {"Id": 3, "FromVariable": 1, "ToVariable": 2, "Type": "Activator"}
+        ]}}"#;
+        let model = BmaModel::from_json_string(json).unwrap();
+        model.validate().unwrap();
+        assert!(model.uses_name_references());
+        let model = BmaModel::from_json_string(&json.replace("var(a)", "var(1)")).unwrap();
+        assert!(!model.uses_name_references());
+    }
+
+    #[test]
+    fn flatten() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            metadata: BTreeMap::default(),
+        };
+        model.network.relationships[1].r#type = RelationshipType::Unknown("?".to_string());
+        model.network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(0) - var(3)"));
+        model.layout.variables.reverse();
+
+        model.flatten();
+        let ids = model.network.variables.iter().map(|v| v.id);
+        assert_eq!(ids.collect::<Vec<_>>(), vec![0, 3]);
+        let ids = model.layout.variables.iter().map(|v| v.id);
+        assert_eq!(ids.collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(model.network.relationships[1].r#type, Inhibitor);
+        assert!(model.network.variables_with_default_function().is_empty());
+        let default = model.network.build_default_update_function(0);
+        assert_eq!(
+            model.network.variables[0].formula_string(),
+            default.to_string()
+        );
+        model.validate().unwrap();
+    }
+
+    #[test]
+    fn json_value_conversion() {
+        let model = BmaModel::new(simple_network(), simple_layout(), BTreeMap::new());
+        let value = model.to_json_value().unwrap();
+        let string = model.to_json_string().unwrap();
+        assert_eq!(
+            value,
+            serde_json::from_str::<serde_json::Value>(&string).unwrap()
+        );
+
+        // Models can be embedded into (and extracted from) larger documents.
+        let project = serde_json::json!({ "models": [value] });
+        let embedded = project["models"][0].clone();
+        let expected = BmaModel::from_json_string(&string).unwrap();
+        assert_eq!(BmaModel::from_json_value(embedded).unwrap(), expected);
+        assert!(BmaModel::from_json_value(serde_json::json!([1, 2])).is_err());
+    }
+
+    #[test]
+    fn serialization_is_deterministic() {
+        let keys = [
+            "modified_date",
+            "biocheck_version",
+            "created_date",
+            "author",
+        ];
+        let mut first = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            metadata: BTreeMap::default(),
+        };
+        let mut second = first.clone();
+        for key in keys {
+            first
+                .metadata
+                .insert(key.to_string(), format!("{key}_value"));
+        }
+        for key in keys.iter().rev() {
+            second
+                .metadata
+                .insert((*key).to_string(), format!("{key}_value"));
+        }
+
+        let first_json = serde_json::to_string(&first).unwrap();
+        assert_eq!(first_json, serde_json::to_string(&second).unwrap());
+        let author = first_json.find("\"author\"").unwrap();
+        let version = first_json.find("\"biocheck_version\"").unwrap();
+        assert!(author < version);
+
+        let bma_json = first.to_json_string().unwrap();
+        assert_eq!(bma_json, second.to_json_string().unwrap());
+        assert!(bma_json.find("\"Model\"").unwrap() < bma_json.find("\"Layout\"").unwrap());
+        let parsed = BmaModel::from_json_string(&bma_json).unwrap();
+        assert_eq!(parsed.to_json_string().unwrap(), bma_json);
+    }
 }
@@ -0,0 +1,330 @@
+use crate::serde::quote_num::QuoteNum;
+use crate::{
+    BmaLayoutContainerError, BmaLayoutError, BmaLayoutVariableError, BmaModel, BmaModelError,
+    BmaNetworkError, Validation, VecReporter,
+};
+use serde::Deserialize;
+use serde_json::value::RawValue;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Approximate locations of model elements in the source file of a [`BmaModel`].
+///
+/// Each span is a range of byte offsets into the source string that covers the whole
+/// definition of the element (i.e. the JSON object or the XML element). If an ID is defined
+/// multiple times, the span of its first definition is used. Spans can be obtained using
+/// [`BmaModel::from_json_string_with_spans`] or [`BmaModel::from_xml_string_with_spans`],
+/// and then used to locate validation errors using [`BmaSourceSpans::span_of`].
+///
+/// In XML, variables and their layout are defined by the same element, hence
+/// `variables` and `layout_variables` contain the same spans.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BmaSourceSpans {
+    pub variables: HashMap<u32, Range<usize>>,
+    pub relationships: HashMap<u32, Range<usize>>,
+    pub layout_variables: HashMap<u32, Range<usize>>,
+    pub containers: HashMap<u32, Range<usize>>,
+}
+
+/// A [`BmaModelError`] together with the source span of the model element that caused it
+/// (see [`BmaModel::validate_with_spans`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpannedBmaModelError {
+    pub error: BmaModelError,
+    pub source_span: Option<Range<usize>>,
+}
+
+impl BmaSourceSpans {
+    /// Find the source span of the element that is responsible for the given validation error.
+    ///
+    /// Returns `None` if the element has no known span, or if the error is not tied to a single
+    /// element (e.g. [`crate::BmaRelationshipError::ConflictingSigns`]).
+    #[must_use]
+    pub fn span_of(&self, error: &BmaModelError) -> Option<Range<usize>> {
+        let (spans, id) = match error {
+            BmaModelError::Network(BmaNetworkError::Variable(e)) => {
+                (&self.variables, e.variable_id())
+            }
+            BmaModelError::Network(BmaNetworkError::Relationship(e)) => {
+                (&self.relationships, e.relationship_id()?)
+            }
+            BmaModelError::Network(BmaNetworkError::ModelStyleInconsistent { .. }) => return None,
+            BmaModelError::Layout(BmaLayoutError::Variable(e)) => {
+                (&self.layout_variables, layout_variable_error_id(e))
+            }
+            BmaModelError::Layout(BmaLayoutError::Container(e)) => {
+                (&self.containers, container_error_id(e))
+            }
+        };
+        spans.get(&id).cloned()
+    }
+
+    /// Compute the spans of model elements in a model string in the BMA JSON format.
+    pub(crate) fn from_json_string(json_str: &str) -> Result<Self, serde_json::Error> {
+        let raw = serde_json::from_str::<JsonSpans>(json_str)?;
+        let network = raw.model.unwrap_or_default();
+        let layout = raw.layout.unwrap_or_default();
+        Ok(BmaSourceSpans {
+            variables: json_spans(json_str, &network.variables)?,
+            relationships: json_spans(json_str, &network.relationships)?,
+            layout_variables: json_spans(json_str, &layout.variables)?,
+            containers: json_spans(json_str, &layout.containers)?,
+        })
+    }
+
+    /// Compute the spans of model elements in a model string in the BMA XML format.
+    ///
+    /// This only performs a lightweight scan of the XML tags and assumes the string
+    /// is otherwise a valid BMA XML model.
+    pub(crate) fn from_xml_string(xml_str: &str) -> Self {
+        let variables = xml_spans(xml_str, "Variable");
+        BmaSourceSpans {
+            layout_variables: variables.clone(),
+            variables,
+            relationships: xml_spans(xml_str, "Relationship"),
+            containers: xml_spans(xml_str, "Container"),
+        }
+    }
+}
+
+impl BmaModel {
+    /// The same as [`BmaModel::from_json_string`], but also computes the [`BmaSourceSpans`]
+    /// of model elements in `json_str`.
+    pub fn from_json_string_with_spans(
+        json_str: &str,
+    ) -> Result<(Self, BmaSourceSpans), serde_json::Error> {
+        let model = BmaModel::from_json_string(json_str)?;
+        let spans = BmaSourceSpans::from_json_string(json_str)?;
+        Ok((model, spans))
+    }
+
+    /// The same as [`BmaModel::from_xml_string`], but also computes the [`BmaSourceSpans`]
+    /// of model elements in `xml_str`.
+    pub fn from_xml_string_with_spans(
+        xml_str: &str,
+    ) -> Result<(Self, BmaSourceSpans), serde_xml_rs::Error> {
+        let model = BmaModel::from_xml_string(xml_str)?;
+        Ok((model, BmaSourceSpans::from_xml_string(xml_str)))
+    }
+
+    /// The same as [`Validation::validate`], but each error carries the source span of the
+    /// responsible model element (see [`BmaSourceSpans::span_of`]).
+    pub fn validate_with_spans(
+        &self,
+        spans: &BmaSourceSpans,
+    ) -> Result<(), Vec<SpannedBmaModelError>> {
+        let mut reporter = VecReporter::new();
+        self.validate_all(&mut reporter);
+        reporter.into_result().map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|error| SpannedBmaModelError {
+                    source_span: spans.span_of(&error),
+                    error,
+                })
+                .collect()
+        })
+    }
+}
+
+fn layout_variable_error_id(error: &BmaLayoutVariableError) -> u32 {
+    match error {
+        BmaLayoutVariableError::IdNotUnique { id }
+        | BmaLayoutVariableError::VariableNotFound { id }
+        | BmaLayoutVariableError::ContainerNotFound { id, .. }
+        | BmaLayoutVariableError::UnknownVariableType { id, .. }
+        | BmaLayoutVariableError::InvalidVariableType { id, .. }
+        | BmaLayoutVariableError::NameMismatch { id, .. }
+        | BmaLayoutVariableError::NonFiniteCoordinate { id } => *id,
+    }
+}
+
+fn container_error_id(error: &BmaLayoutContainerError) -> u32 {
+    match error {
+        BmaLayoutContainerError::IdNotUnique { id }
+        | BmaLayoutContainerError::DefaultedSize { id }
+        | BmaLayoutContainerError::NonFiniteCoordinate { id } => *id,
+    }
+}
+
+/// Minimal view of the JSON model which only keeps the raw text of the relevant objects.
+#[derive(Deserialize)]
+struct JsonSpans<'a> {
+    #[serde(default, borrow, rename = "Model", alias = "model")]
+    model: Option<JsonNetworkSpans<'a>>,
+    #[serde(default, borrow, rename = "Layout", alias = "layout")]
+    layout: Option<JsonLayoutSpans<'a>>,
+}
+
+#[derive(Deserialize, Default)]
+struct JsonNetworkSpans<'a> {
+    #[serde(default, borrow, rename = "Variables", alias = "variables")]
+    variables: Vec<&'a RawValue>,
+    #[serde(default, borrow, rename = "Relationships", alias = "relationships")]
+    relationships: Vec<&'a RawValue>,
+}
+
+#[derive(Deserialize, Default)]
+struct JsonLayoutSpans<'a> {
+    #[serde(default, borrow, rename = "Variables", alias = "variables")]
+    variables: Vec<&'a RawValue>,
+    #[serde(default, borrow, rename = "Containers", alias = "containers")]
+    containers: Vec<&'a RawValue>,
+}
+
+#[derive(Deserialize)]
+struct JsonId {
+    #[serde(rename = "Id", alias = "id")]
+    id: QuoteNum,
+}
+
+/// Compute the spans of the given raw JSON objects, which must be borrowed from `source`.
+fn json_spans(
+    source: &str,
+    objects: &[&RawValue],
+) -> Result<HashMap<u32, Range<usize>>, serde_json::Error> {
+    let mut result = HashMap::new();
+    for object in objects {
+        let text = object.get();
+        let id = serde_json::from_str::<JsonId>(text)?.id.into();
+        let start = text.as_ptr().addr() - source.as_ptr().addr();
+        result.entry(id).or_insert(start..start + text.len());
+    }
+    Ok(result)
+}
+
+/// Find the spans of all XML elements with the given `tag`, identified by their `Id`
+/// attribute (or `<Id>` child element).
+fn xml_spans(source: &str, tag: &str) -> HashMap<u32, Range<usize>> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut result = HashMap::new();
+    let mut position = 0;
+    while let Some(offset) = source[position..].find(&open) {
+        let start = position + offset;
+        position = start + open.len();
+        // Skip tags which only share a prefix (e.g. `<Variables>` when looking for `<Variable`).
+        let is_tag = source[position..].starts_with(|c: char| c.is_whitespace() || c == '>');
+        let Some(head_end) = source[position..].find('>').map(|i| position + i + 1) else {
+            break;
+        };
+        if !is_tag {
+            continue;
+        }
+        let end = if source[..head_end].ends_with("/>") {
+            head_end
+        } else {
+            match source[head_end..].find(&close) {
+                Some(i) => head_end + i + close.len(),
+                None => break,
+            }
+        };
+        let element = &source[start..end];
+        if let Some(id) =
+            xml_attribute_id(&source[start..head_end]).or_else(|| xml_child_id(element))
+        {
+            result.entry(id).or_insert(start..end);
+        }
+        position = head_end;
+    }
+    result
+}
+
+/// Read the `Id="..."` attribute of an XML start tag.
+fn xml_attribute_id(head: &str) -> Option<u32> {
+    let value = head
+        .split_whitespace()
+        .find_map(|attribute| attribute.strip_prefix("Id="))?;
+    let value = value.trim_end_matches(['/', '>']).trim_matches(['"', '\'']);
+    value.trim().parse().ok()
+}
+
+/// Read the `<Id>...</Id>` child element of an XML element.
+fn xml_child_id(element: &str) -> Option<u32> {
+    let start = element.find("<Id>")? + 4;
+    let end = start + element[start..].find("</Id>")?;
+    element[start..end].trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BmaModel, BmaModelError, BmaNetworkError, BmaRelationshipError};
+
+    #[test]
+    fn json_spans() {
+        let json = r#"{
+            "Model": {
+                "Name": "spans",
+                "Variables": [
+                    {"Id": 1, "Name": "a", "RangeFrom": 0, "RangeTo": 1, "Formula": ""},
+                    {"Id": "2", "Name": "b", "RangeFrom": 0, "RangeTo": 1, "Formula": "var(7)"}
+                ],
+                "Relationships": [
+                    {"Id": 5, "FromVariable": 1, "ToVariable": 2, "Type": "Activator"},
+                    {"Id": 6, "FromVariable": 3, "ToVariable": 2, "Type": "Activator"}
+                ]
+            }
+        }"#;
+        let (model, spans) = BmaModel::from_json_string_with_spans(json).unwrap();
+        assert_eq!(spans.variables.len(), 2);
+        assert_eq!(spans.relationships.len(), 2);
+        assert!(spans.layout_variables.is_empty());
+        assert!(json[spans.variables[&2].clone()].starts_with(r#"{"Id": "2""#));
+        assert!(json[spans.variables[&2].clone()].ends_with('}'));
+
+        let errors = model.validate_with_spans(&spans).unwrap_err();
+        assert!(!errors.is_empty());
+        for error in &errors {
+            let span = error.source_span.clone().unwrap();
+            let id = match &error.error {
+                BmaModelError::Network(BmaNetworkError::Variable(_)) => "\"2\"",
+                BmaModelError::Network(BmaNetworkError::Relationship(_)) => "6",
+                BmaModelError::Network(BmaNetworkError::ModelStyleInconsistent { .. })
+                | BmaModelError::Layout(_) => unreachable!(),
+            };
+            assert!(json[span].starts_with(&format!("{{\"Id\": {id},")));
+        }
+    }
+
+    #[test]
+    fn xml_spans() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<Model Id="1" Name="spans">
+  <Variables>
+    <Variable Id="1" Name="a">
+      <RangeFrom>0</RangeFrom>
+      <RangeTo>1</RangeTo>
+      <Formula></Formula>
+    </Variable>
+    <Variable Name="b">
+      <Id>2</Id>
+      <RangeFrom>0</RangeFrom>
+      <RangeTo>1</RangeTo>
+      <Formula>var(a)</Formula>
+    </Variable>
+  </Variables>
+  <Relationships>
+    <Relationship Id="3">
+      <FromVariableId>1</FromVariableId>
+      <ToVariableId>5</ToVariableId>
+      <Type>Activator</Type>
+    </Relationship>
+  </Relationships>
+</Model>"#;
+        let (model, spans) = BmaModel::from_xml_string_with_spans(xml).unwrap();
+        assert_eq!(spans.variables, spans.layout_variables);
+        assert!(xml[spans.variables[&1].clone()].starts_with(r#"<Variable Id="1""#));
+        assert!(xml[spans.variables[&2].clone()].contains("<Id>2</Id>"));
+        assert!(xml[spans.variables[&2].clone()].ends_with("</Variable>"));
+
+        let errors = model.validate_with_spans(&spans).unwrap_err();
+        let error = BmaModelError::from(BmaNetworkError::from(
+            BmaRelationshipError::TargetVariableNotFound {
+                id: 3,
+                to_variable: 5,
+            },
+        ));
+        let error = errors.iter().find(|e| e.error == error).unwrap();
+        assert!(xml[error.source_span.clone().unwrap()].starts_with(r#"<Relationship Id="3">"#));
+    }
+}
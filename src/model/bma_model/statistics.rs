@@ -0,0 +1,214 @@
+use crate::BmaModel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// A simple summary of the basic properties of a [`BmaModel`].
+///
+/// See [`BmaModel::statistics`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ModelStats {
+    /// Number of network variables.
+    pub variables: usize,
+    /// Number of network relationships.
+    pub relationships: usize,
+    /// Number of variables with a constant range.
+    pub constants: usize,
+    /// Number of variables that are not constants and have no regulators.
+    pub inputs: usize,
+    /// Number of variables that do not regulate any variable.
+    pub outputs: usize,
+    /// The maximal level of all variables (see [`BmaModel::get_max_var_level`]).
+    pub max_level: u32,
+    /// True if all variables are Boolean (see [`BmaModel::is_boolean`]).
+    pub is_boolean: bool,
+    /// Number of variables with an explicit update function (including invalid functions).
+    pub explicit_functions: usize,
+    /// Number of variables that use the default update function.
+    pub default_functions: usize,
+}
+
+impl BmaModel {
+    /// Compute a [`ModelStats`] summary of this model.
+    #[must_use]
+    pub fn statistics(&self) -> ModelStats {
+        let network = &self.network;
+        let constants = network
+            .variables
+            .iter()
+            .filter(|v| v.has_constant_range())
+            .count();
+        let inputs = network
+            .variables
+            .iter()
+            .filter(|v| !v.has_constant_range() && network.get_regulators(v.id, &None).is_empty())
+            .count();
+        let outputs = network
+            .variables
+            .iter()
+            .filter(|v| {
+                !network
+                    .relationships
+                    .iter()
+                    .any(|r| r.from_variable == v.id)
+            })
+            .count();
+        let default_functions = network
+            .variables
+            .iter()
+            .filter(|v| v.formula.is_none())
+            .count();
+
+        ModelStats {
+            variables: network.variables.len(),
+            relationships: network.relationships.len(),
+            constants,
+            inputs,
+            outputs,
+            max_level: self.get_max_var_level(),
+            is_boolean: self.is_boolean(),
+            explicit_functions: network.variables.len() - default_functions,
+            default_functions,
+        }
+    }
+
+    /// The number of states of this model, i.e., the product of the domain sizes
+    /// (`max_level - min_level + 1`) of all variables.
+    ///
+    /// Returns `None` if the number does not fit into `u128`. A variable with an invalid
+    /// range (`min_level > max_level`) has no levels, so the result is `0` in such case.
+    #[must_use]
+    pub fn state_space_size(&self) -> Option<u128> {
+        self.network.variables.iter().try_fold(1u128, |size, v| {
+            size.checked_mul(u128::from(v.range_size()))
+        })
+    }
+
+    /// The levels of the given variable that its update function can actually produce,
+    /// i.e., all distinct outputs of its function table (see
+    /// [`crate::BmaNetwork::build_function_table`], which also describes the possible
+    /// error states).
+    ///
+    /// If some level of the variable range is missing, the variable can never reach it
+    /// (unless it starts there), which usually indicates a modeling issue. See also
+    /// [`crate::BmaNetwork::is_effectively_constant`].
+    pub fn reachable_levels(&self, var_id: u32) -> anyhow::Result<BTreeSet<u32>> {
+        let table = self.network.build_function_table(var_id)?;
+        Ok(table.into_iter().map(|(_, output)| output).collect())
+    }
+}
+
+/// A compact one-line summary of the model, based on [`BmaModel::statistics`].
+///
+/// For example: `BmaModel "name": 3 vars (1 constant), 1 relationships, boolean=false, max_level=2`.
+impl Display for BmaModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let stats = self.statistics();
+        write!(
+            f,
+            "BmaModel {:?}: {} vars ({} constant), {} relationships, boolean={}, max_level={}",
+            self.network.name,
+            stats.variables,
+            stats.constants,
+            stats.relationships,
+            stats.is_boolean,
+            stats.max_level
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::update_function::BmaUpdateFunction;
+    use crate::{BmaModel, BmaNetwork, BmaRelationship, BmaVariable, ModelStats};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn model_statistics() {
+        let update = BmaUpdateFunction::try_from("var(1)").unwrap();
+        let mut model = BmaModel {
+            network: BmaNetwork::new(
+                vec![
+                    BmaVariable::new(1, "a", (0, 2), None),
+                    BmaVariable::new(2, "b", (0, 2), Some(update)),
+                    BmaVariable::new(3, "c", (1, 1), None),
+                ],
+                vec![BmaRelationship::new_activator(4, 1, 2)],
+            ),
+            ..Default::default()
+        };
+
+        let expected = ModelStats {
+            variables: 3,
+            relationships: 1,
+            constants: 1,
+            inputs: 1,
+            outputs: 2,
+            max_level: 2,
+            is_boolean: false,
+            explicit_functions: 1,
+            default_functions: 2,
+        };
+        assert_eq!(model.statistics(), expected);
+        assert_eq!(BmaModel::default().statistics().variables, 0);
+
+        model.network.name = "test".to_string();
+        let summary =
+            "BmaModel \"test\": 3 vars (1 constant), 1 relationships, boolean=false, max_level=2";
+        assert_eq!(model.to_string(), summary);
+    }
+
+    #[test]
+    fn state_space_size() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 2), None),
+                BmaVariable::new(2, "b", (1, 4), None),
+                BmaVariable::new(3, "c", (1, 1), None),
+            ],
+            vec![],
+        );
+        let mut model = BmaModel {
+            network: network.clone(),
+            ..Default::default()
+        };
+        assert_eq!(model.state_space_size(), Some(12));
+        assert_eq!(BmaModel::default().state_space_size(), Some(1));
+
+        // 2^128 states do not fit into `u128`.
+        network.variables = (0..128)
+            .map(|id| BmaVariable::new_boolean(id, "x", None))
+            .collect();
+        model.network = network.clone();
+        assert_eq!(model.state_space_size(), None);
+        network.variables.pop();
+        model.network = network;
+        assert_eq!(model.state_space_size(), Some(1u128 << 127));
+
+        model.network.variables[0].range = (2, 1);
+        assert_eq!(model.state_space_size(), Some(0));
+
+        // The full `u32` range has exactly `2^32` levels.
+        model.network.variables = vec![BmaVariable::new(1, "a", (0, u32::MAX), None)];
+        assert_eq!(model.state_space_size(), Some(1u128 << 32));
+    }
+
+    #[test]
+    fn reachable_levels() {
+        let update = BmaUpdateFunction::try_from("min(var(1), 1)").unwrap();
+        let model = BmaModel {
+            network: BmaNetwork::new(
+                vec![
+                    BmaVariable::new(1, "a", (0, 2), None),
+                    BmaVariable::new(2, "b", (0, 2), Some(update)),
+                ],
+                vec![BmaRelationship::new_activator(4, 1, 2)],
+            ),
+            ..Default::default()
+        };
+        assert_eq!(model.reachable_levels(2).unwrap(), BTreeSet::from([0, 1]));
+        // `a` has no regulators, so its default function is constant zero.
+        assert_eq!(model.reachable_levels(1).unwrap(), BTreeSet::from([0]));
+        assert!(model.reachable_levels(3).is_err());
+    }
+}
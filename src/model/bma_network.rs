@@ -1,24 +1,64 @@
 use crate::model::bma_relationship::BmaRelationshipError;
+use crate::model::bma_variable::infer_relationship_type;
+use crate::serde::json::{JsonBmaModel, JsonNetwork};
 use crate::update_function::{BmaUpdateFunction, InvalidBmaExpression, create_default_update_fn};
+use crate::utils::count_ids;
 use crate::{
-    BmaRelationship, BmaVariable, BmaVariableError, ContextualValidation, ErrorReporter,
-    RelationshipType, Validation,
+    BmaModel, BmaRelationship, BmaVariable, BmaVariableError, ErrorReporter, RelationshipType,
+    Validation,
 };
+use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use thiserror::Error;
 
 /// Named model with several [`BmaVariable`] objects that are connected through various
 /// [`BmaRelationship`] objects. The model name can be blank.
 ///
 /// This is the main part of [`crate::BmaModel`], and it is always required.
+///
+/// By default, update functions are evaluated using exact rational arithmetic. This can be
+/// changed using [`BmaNetwork::set_avg_integer_semantics`].
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct BmaNetwork {
     pub name: String,
     pub variables: Vec<BmaVariable>,
     pub relationships: Vec<BmaRelationship>,
+    #[serde(skip)]
+    pub(crate) avg_integer_semantics: bool,
+    #[serde(skip)]
+    pub(crate) reference_styles: ReferenceStyles,
+}
+
+/// Variable reference styles used by the update function expressions of a [`BmaNetwork`]
+/// in the imported file (see [`BmaNetworkError::ModelStyleInconsistent`] and
+/// [`crate::BmaModel::uses_name_references`]). Names are resolved to IDs during parsing,
+/// so this can only be recorded at import time.
+///
+/// This only describes how the model was written, not the model itself, so it is ignored when
+/// networks are compared or hashed (e.g. a model is still equal to its exported copy, where
+/// all references use IDs).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReferenceStyles {
+    /// IDs of variables whose update function references variables by name, i.e. `var(name)`.
+    pub by_name: BTreeSet<u32>,
+    /// IDs of variables whose update function references variables by ID, i.e. `var(id)`.
+    pub by_id: BTreeSet<u32>,
+}
+
+impl PartialEq for ReferenceStyles {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for ReferenceStyles {}
+
+impl Hash for ReferenceStyles {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
 }
 
 impl BmaNetwork {
@@ -29,15 +69,90 @@ impl BmaNetwork {
             name: String::default(),
             variables,
             relationships,
+            avg_integer_semantics: false,
+            reference_styles: ReferenceStyles::default(),
+        }
+    }
+
+    /// Record which variable reference styles are used by the original (imported) update
+    /// function expressions of the given variables (see [`ReferenceStyles`]).
+    pub(crate) fn record_reference_styles<'a>(
+        &mut self,
+        formulas: impl IntoIterator<Item = (u32, &'a str)>,
+    ) {
+        for (id, expression) in formulas {
+            if BmaUpdateFunction::uses_name_references(expression) {
+                self.reference_styles.by_name.insert(id);
+            }
+            if BmaUpdateFunction::uses_id_references(expression) {
+                self.reference_styles.by_id.insert(id);
+            }
         }
     }
 
+    /// If set, the result of every `avg` aggregation is truncated to an integer before it
+    /// is used in further arithmetic (reproducing the integer division of some BMA tool
+    /// versions). This flag is not part of the BMA file formats, so it is not preserved
+    /// by the JSON/XML import and export (or by serialization in general).
+    pub fn set_avg_integer_semantics(&mut self, value: bool) {
+        self.avg_integer_semantics = value;
+    }
+
+    /// Check whether the integer `avg` semantics is enabled
+    /// (see [`BmaNetwork::set_avg_integer_semantics`]).
+    #[must_use]
+    pub fn avg_integer_semantics(&self) -> bool {
+        self.avg_integer_semantics
+    }
+
+    /// Check if this network has no variables and no relationships (the name is ignored).
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.variables.is_empty() && self.relationships.is_empty()
+    }
+
     /// Find an instances of [`BmaVariable`] stored in this network, assuming it exists.
     #[must_use]
     pub fn find_variable(&self, id: u32) -> Option<&BmaVariable> {
         self.variables.iter().find(|v| v.id == id)
     }
 
+    /// Find all [`BmaRelationship`] objects from the `from` variable to the `to` variable.
+    ///
+    /// There can be more than one such relationship (e.g. with different types).
+    #[must_use]
+    pub fn find_relationship(&self, from: u32, to: u32) -> Vec<&BmaRelationship> {
+        self.relationships
+            .iter()
+            .filter(|r| r.from_variable == from && r.to_variable == to)
+            .collect()
+    }
+
+    /// Find an instance of [`BmaRelationship`] stored in this network, assuming it exists.
+    #[must_use]
+    pub fn find_relationship_by_id(&self, id: u32) -> Option<&BmaRelationship> {
+        self.relationships.iter().find(|r| r.id == id)
+    }
+
+    /// Get the incoming and outgoing relationships of the given variable (in this order),
+    /// computed in a single pass over all relationships.
+    ///
+    /// A self-loop is both incoming and outgoing, hence it appears in both lists.
+    #[must_use]
+    pub fn relationships_of(&self, var_id: u32) -> (Vec<&BmaRelationship>, Vec<&BmaRelationship>) {
+        let mut incoming = Vec::new();
+        let mut outgoing = Vec::new();
+        for r in &self.relationships {
+            if r.to_variable == var_id {
+                incoming.push(r);
+            }
+            if r.from_variable == var_id {
+                outgoing.push(r);
+            }
+        }
+        (incoming, outgoing)
+    }
+
     /// Get regulators of a particular variable, optionally filtered by regulator type.
     /// The regulators are represented by their IDs.
     ///
@@ -59,6 +174,360 @@ impl BmaNetwork {
     }
 }
 
+/// Serialization of the network without the layout.
+impl BmaNetwork {
+    /// Convert the network into a JSON string. The result is the same as the `Model`
+    /// object of the BMA JSON format (see [`crate::BmaModel::to_json_string`]), so it can
+    /// be embedded into a full model later.
+    pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(&JsonNetwork::from(self.clone()))
+    }
+
+    /// Create a new network from the JSON string of the `Model` object of the BMA JSON
+    /// format (see [`crate::BmaModel::from_json_string`]).
+    pub fn from_json_string(json_str: &str) -> Result<Self, serde_json::Error> {
+        let network = serde_json::from_str::<JsonNetwork>(json_str)?;
+        let model = JsonBmaModel {
+            network,
+            layout: None,
+        };
+        Ok(BmaModel::from(model).network)
+    }
+}
+
+/// Comparison of networks up to their representation.
+impl BmaNetwork {
+    /// The set of `(regulator, target, type)` triples describing the interactions of this
+    /// network, independent of the order and IDs of its relationships.
+    pub(crate) fn interaction_signature(&self) -> BTreeSet<(u32, u32, String)> {
+        self.relationships
+            .iter()
+            .map(|r| (r.from_variable, r.to_variable, r.r#type.to_string()))
+            .collect()
+    }
+
+    /// Check whether the two networks describe the same dynamics, regardless of the order
+    /// of their variables and relationships.
+    ///
+    /// The networks must have the same variables (compared by ID and range), the same
+    /// interactions (regulator, target, and type), the same integer semantics, and every variable
+    /// must have the same function table (see [`BmaNetwork::build_function_table`]). Hence,
+    /// the way in which the update functions are written does not matter. Update functions
+    /// whose table cannot be built are compared as strings. Variable names, relationship IDs,
+    /// and the network name are ignored.
+    #[must_use]
+    pub fn eq_ignoring_order(&self, other: &BmaNetwork) -> bool {
+        let ranges = |network: &BmaNetwork| {
+            network
+                .variables
+                .iter()
+                .map(|v| (v.id, v.range))
+                .collect::<BTreeMap<_, _>>()
+        };
+        if self.avg_integer_semantics != other.avg_integer_semantics
+            || self.variables.len() != other.variables.len()
+            || ranges(self) != ranges(other)
+            || self.interaction_signature() != other.interaction_signature()
+        {
+            return false;
+        }
+
+        self.variables.iter().all(|variable| {
+            let Some(other_variable) = other.find_variable(variable.id) else {
+                return false;
+            };
+            match (
+                self.build_function_table(variable.id),
+                other.build_function_table(variable.id),
+            ) {
+                (Ok(table), Ok(other_table)) => table == other_table,
+                (Err(_), Err(_)) => variable.formula_string() == other_variable.formula_string(),
+                _ => false,
+            }
+        })
+    }
+
+    /// Group variables by the "shape" of their update functions. Two variables have the same
+    /// shape if their function tables (see [`BmaNetwork::build_function_table`]) are the same
+    /// once the regulators are replaced by their positions (regulators are ordered by ID).
+    ///
+    /// The key describes the shape using the ranges of the regulators, the range of the
+    /// variable, and the output levels for all input valuations (in lexicographic order),
+    /// e.g. `[0..1, 0..1] -> 0..1: [0, 0, 0, 1]` for a Boolean conjunction. The variable IDs
+    /// in each group are sorted. Variables whose function table cannot be built are omitted.
+    #[must_use]
+    pub fn function_classes(&self) -> HashMap<String, Vec<u32>> {
+        let mut result: HashMap<String, Vec<u32>> = HashMap::new();
+        for variable in &self.variables {
+            let Ok(mut table) = self.build_function_table(variable.id) else {
+                continue;
+            };
+            table.sort_by(|(a, _), (b, _)| a.values().cmp(b.values()));
+            let mut regulators = Vec::from_iter(self.get_regulators(variable.id, &None));
+            regulators.sort_unstable();
+            let inputs = regulators
+                .iter()
+                .filter_map(|id| self.find_variable(*id))
+                .map(|var| format!("{}..{}", var.min_level(), var.max_level()))
+                .collect::<Vec<_>>();
+            let outputs = table.iter().map(|(_, output)| *output).collect::<Vec<_>>();
+            let key = format!(
+                "[{}] -> {}..{}: {outputs:?}",
+                inputs.join(", "),
+                variable.min_level(),
+                variable.max_level()
+            );
+            result.entry(key).or_default().push(variable.id);
+        }
+        for ids in result.values_mut() {
+            ids.sort_unstable();
+        }
+        result
+    }
+}
+
+/// Utility methods for cleaning up the network structure.
+impl BmaNetwork {
+    /// Remove relationships whose regulator has no influence on the function table of the
+    /// target variable (i.e. the cases reported as `UnusedRelationship` during validation).
+    /// Returns the IDs of the removed relationships.
+    ///
+    /// To ensure the network does not become invalid, the method does not remove
+    /// relationships that:
+    ///  - target a variable with constant range or an invalid update function;
+    ///  - target a variable whose function table cannot be built;
+    ///  - start in a regulator with constant range;
+    ///  - start in a regulator that syntactically appears in the update function.
+    pub fn prune_unused_relationships(&mut self) -> Vec<u32> {
+        let mut unused = HashSet::new();
+        for variable in &self.variables {
+            if variable.has_constant_range() {
+                continue;
+            }
+            let function = match &variable.formula {
+                None => self.build_default_update_function(variable.id),
+                Some(Ok(function)) => function.clone(),
+                Some(Err(_)) => continue,
+            };
+            let Ok(mut table) = self.build_function_table(variable.id) else {
+                continue;
+            };
+            let syntactic_regulators = function.collect_variables();
+            for regulator in self.get_regulators(variable.id, &None) {
+                let is_constant = self
+                    .find_variable(regulator)
+                    .is_none_or(BmaVariable::has_constant_range);
+                if is_constant || syntactic_regulators.contains(&regulator) {
+                    continue;
+                }
+                if infer_relationship_type(&mut table, regulator).is_empty() {
+                    unused.insert((regulator, variable.id));
+                }
+            }
+        }
+
+        let mut removed = Vec::new();
+        self.relationships.retain(|r| {
+            let is_unused = unused.contains(&(r.from_variable, r.to_variable));
+            if is_unused {
+                removed.push(r.id);
+            }
+            !is_unused
+        });
+        removed
+    }
+
+    /// Compute the declared regulators of the given variable which actually influence its
+    /// output level, i.e., the regulators whose inferred relationship type (based on the
+    /// function table of the variable) is not empty. Regulators that are both activating and
+    /// inhibiting (non-monotonic) are included as well.
+    ///
+    /// Variables with a constant range have no essential regulators. The operation fails
+    /// if the function table cannot be built (see [`BmaNetwork::build_function_table`]).
+    pub fn essential_regulators(&self, var_id: u32) -> anyhow::Result<HashSet<u32>> {
+        let mut table = self.build_function_table(var_id)?;
+        let mut result = HashSet::new();
+        for regulator in self.get_regulators(var_id, &None) {
+            if !infer_relationship_type(&mut table, regulator).is_empty() {
+                result.insert(regulator);
+            }
+        }
+        Ok(result)
+    }
+
+    /// For every relationship (in the order of the network), compute its ID, its declared
+    /// type, and the types observed in the function table of the target variable (see
+    /// [`BmaNetwork::build_function_table`]).
+    ///
+    /// This is the same information that is used to report `BadMonotonicity` during
+    /// validation. The observed types are empty if the regulator does not influence the
+    /// target, and both types are observed if the regulator is non-monotonic. They are also
+    /// empty if the function table of the target cannot be built.
+    #[must_use]
+    pub fn relationship_sign_report(&self) -> Vec<(u32, RelationshipType, Vec<RelationshipType>)> {
+        let mut tables = HashMap::new();
+        self.relationships
+            .iter()
+            .map(|r| {
+                let table = tables
+                    .entry(r.to_variable)
+                    .or_insert_with(|| self.build_function_table(r.to_variable).ok());
+                let observed = table
+                    .as_mut()
+                    .map(|table| infer_relationship_type(table, r.from_variable))
+                    .unwrap_or_default();
+                (r.id, r.r#type.clone(), observed)
+            })
+            .collect()
+    }
+
+    /// Replace [`RelationshipType::Unknown`] relationships with [`RelationshipType::Activator`]
+    /// or [`RelationshipType::Inhibitor`] based on the function table of the target variable.
+    /// Returns the number of resolved relationships.
+    ///
+    /// Only relationships whose target has a valid explicit update function are resolved
+    /// (the default update function itself depends on relationship types). Relationships where
+    /// the regulator has no influence, or where it is both activating and inhibiting, are left
+    /// as [`RelationshipType::Unknown`].
+    pub fn resolve_unknown_relationships(&mut self) -> usize {
+        let mut resolved = HashMap::new();
+        for relationship in &self.relationships {
+            if !matches!(relationship.r#type, RelationshipType::Unknown(_))
+                || resolved.contains_key(&(relationship.from_variable, relationship.to_variable))
+            {
+                continue;
+            }
+            let Some(target) = self.find_variable(relationship.to_variable) else {
+                continue;
+            };
+            if !matches!(target.formula, Some(Ok(_))) {
+                continue;
+            }
+            let Ok(mut table) = self.build_function_table(target.id) else {
+                continue;
+            };
+            let signs = infer_relationship_type(&mut table, relationship.from_variable);
+            if let [sign] = &signs[..] {
+                let key = (relationship.from_variable, relationship.to_variable);
+                resolved.insert(key, sign.clone());
+            }
+        }
+
+        let mut count = 0;
+        for relationship in &mut self.relationships {
+            if !matches!(relationship.r#type, RelationshipType::Unknown(_)) {
+                continue;
+            }
+            let key = (relationship.from_variable, relationship.to_variable);
+            if let Some(sign) = resolved.get(&key) {
+                relationship.r#type = sign.clone();
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Change the range of the variable `id` to `new_range` and return the sorted IDs of
+    /// the other variables whose dynamics may be affected by this change.
+    ///
+    /// If the variable has an explicit constant update function outside the new range, the
+    /// constant is clamped into the new range. Since BMA normalizes input levels to the range
+    /// of the target variable (see [`BmaVariable::normalize_input_level`]), changing the range
+    /// also changes how this variable is seen by its targets. The affected variables are
+    /// the targets of its relationships and the variables whose update function references it.
+    ///
+    /// The operation fails if the variable does not exist or if `new_range` is not
+    /// a valid interval (i.e. `new_range.0 > new_range.1`).
+    pub fn rescale_variable(&mut self, id: u32, new_range: (u32, u32)) -> anyhow::Result<Vec<u32>> {
+        if new_range.0 > new_range.1 {
+            return Err(anyhow!(
+                "Range `{new_range:?}` is invalid; must be an interval"
+            ));
+        }
+        let variable = self
+            .variables
+            .iter_mut()
+            .find(|v| v.id == id)
+            .ok_or_else(|| anyhow!("Variable with id `{id}` not found"))?;
+        variable.range = new_range;
+        if let Some(Ok(function)) = &variable.formula
+            && let Some(value) = function.as_constant()
+        {
+            let clamped = i64::from(value).clamp(i64::from(new_range.0), i64::from(new_range.1));
+            let clamped = i32::try_from(clamped).unwrap_or(i32::MAX);
+            if clamped != value {
+                variable.formula = Some(Ok(BmaUpdateFunction::mk_constant(clamped)));
+            }
+        }
+
+        let mut affected = self
+            .relationships
+            .iter()
+            .filter(|r| r.from_variable == id)
+            .map(|r| r.to_variable)
+            .chain(
+                self.variables
+                    .iter()
+                    .filter(|v| {
+                        v.try_get_update_function()
+                            .is_some_and(|f| f.collect_variables().contains(&id))
+                    })
+                    .map(|v| v.id),
+            )
+            .filter(|target| *target != id)
+            .collect::<Vec<_>>();
+        affected.sort_unstable();
+        affected.dedup();
+        Ok(affected)
+    }
+
+    /// Assign sequential IDs to all relationships, starting with `start` and preserving
+    /// the order of relationships. This ensures that relationship IDs are unique (e.g. after
+    /// a network is assembled from several sources).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new IDs do not fit into `u32`.
+    pub fn renumber_relationships(&mut self, start: u32) {
+        let mut next_id = start;
+        for (i, relationship) in self.relationships.iter_mut().enumerate() {
+            if i > 0 {
+                next_id = next_id
+                    .checked_add(1)
+                    .expect("Relationship IDs do not fit into `u32`.");
+            }
+            relationship.id = next_id;
+        }
+    }
+
+    /// Find variables that are not connected to the rest of the network. That is, variables
+    /// with no incoming and no outgoing relationships, whose update function (explicit or
+    /// default) does not depend on any variable. Such variables do not influence the rest
+    /// of the network and can be typically removed.
+    ///
+    /// Variables with an invalid update function are not reported. The IDs are sorted.
+    /// See also [`BmaNetwork::prune_unused_relationships`].
+    #[must_use]
+    pub fn isolated_variables(&self) -> Vec<u32> {
+        let mut result = self
+            .variables
+            .iter()
+            .filter(|variable| {
+                let (incoming, outgoing) = self.relationships_of(variable.id);
+                incoming.is_empty() && outgoing.is_empty()
+            })
+            .filter(|variable| match &variable.formula {
+                None => true, // Default function without regulators is constant.
+                Some(Ok(function)) => function.collect_variables().is_empty(),
+                Some(Err(_)) => false,
+            })
+            .map(|variable| variable.id)
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        result
+    }
+}
+
 /// Utility methods for dealing with default functions.
 impl BmaNetwork {
     /// Build the default update function which is used by BMA if no other function is provided.
@@ -89,6 +558,37 @@ impl BmaNetwork {
         variable.formula.replace(Ok(update))
     }
 
+    /// Return the sorted IDs of all variables that have no explicit update function, i.e.,
+    /// BMA uses the default update function for them
+    /// (see [`BmaNetwork::build_default_update_function`]).
+    #[must_use]
+    pub fn variables_with_default_function(&self) -> Vec<u32> {
+        let mut result = self
+            .variables
+            .iter()
+            .filter(|v| v.formula.is_none())
+            .map(|v| v.id)
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        result
+    }
+
+    /// Return the sorted IDs of all variables that have an explicit update function. This
+    /// includes variables whose function could not be parsed.
+    ///
+    /// This is the complement of [`BmaNetwork::variables_with_default_function`].
+    #[must_use]
+    pub fn variables_with_explicit_function(&self) -> Vec<u32> {
+        let mut result = self
+            .variables
+            .iter()
+            .filter(|v| v.formula.is_some())
+            .map(|v| v.id)
+            .collect::<Vec<_>>();
+        result.sort_unstable();
+        result
+    }
+
     /// Add default update functions for all variables where the update function is missing.
     pub fn populate_missing_functions(&mut self) {
         let missing_var_ids = self
@@ -110,20 +610,117 @@ pub enum BmaNetworkError {
     Variable(#[from] BmaVariableError),
     #[error(transparent)]
     Relationship(#[from] BmaRelationshipError),
+    #[error(
+        "Update functions reference variables both by name (variables `{by_name:?}`) and by ID (variables `{by_id:?}`)"
+    )]
+    ModelStyleInconsistent { by_name: Vec<u32>, by_id: Vec<u32> },
+}
+
+impl BmaNetworkError {
+    /// A stable identifier of the error kind (see [`crate::BmaModelError::code`]).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaNetworkError::Variable(e) => e.code(),
+            BmaNetworkError::Relationship(e) => e.code(),
+            BmaNetworkError::ModelStyleInconsistent { .. } => "network.model_style_inconsistent",
+        }
+    }
 }
 
 impl Validation for BmaNetwork {
     type Error = BmaNetworkError;
 
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, reporter: &mut R) {
+        self.validate_all_with_max_range(BmaVariable::DEFAULT_MAX_RANGE_SIZE, reporter);
+    }
+}
+
+impl BmaNetwork {
+    /// Internal version of [`Validation::validate_all`] where the maximal number
+    /// of levels in variable ranges can be configured
+    /// (see [`crate::BmaModel::validate_with_max_range_size`]).
+    pub(crate) fn validate_all_with_max_range<R: ErrorReporter<BmaNetworkError>>(
+        &self,
+        max_range_size: u32,
+        reporter: &mut R,
+    ) {
         // Check all variables.
+        let variable_ids = count_ids(&self.variables, |x| x.id);
         for var in &self.variables {
-            var.validate_all(self, &mut reporter.wrap());
+            if reporter.is_full() {
+                return;
+            }
+            var.validate_all_with_max_range(
+                self,
+                &variable_ids,
+                max_range_size,
+                &mut reporter.wrap(),
+            );
         }
 
         // Check all relationships.
+        let relationship_ids = count_ids(&self.relationships, |x| x.id);
         for relationship in &self.relationships {
-            relationship.validate_all(self, &mut reporter.wrap());
+            if reporter.is_full() {
+                return;
+            }
+            relationship.validate_all_with_id_counts(self, &relationship_ids, &mut reporter.wrap());
+        }
+
+        // Report every pair of variables with both an activation and an inhibition (once).
+        let mut activations = HashSet::new();
+        let mut inhibitions = HashSet::new();
+        for r in &self.relationships {
+            match r.r#type {
+                RelationshipType::Activator => activations.insert((r.from_variable, r.to_variable)),
+                RelationshipType::Inhibitor => inhibitions.insert((r.from_variable, r.to_variable)),
+                RelationshipType::Unknown(_) => continue,
+            };
+        }
+        let mut conflicting = activations.intersection(&inhibitions).collect::<Vec<_>>();
+        conflicting.sort_unstable();
+        for (from, to) in conflicting {
+            reporter.report(BmaRelationshipError::ConflictingSigns {
+                from: *from,
+                to: *to,
+            });
+        }
+    }
+}
+
+impl BmaNetwork {
+    /// Strict-only checks of the network (see [`crate::BmaModel::validate_strict`]).
+    pub(crate) fn validate_strict_all<R: ErrorReporter<BmaNetworkError>>(&self, reporter: &mut R) {
+        for relationship in &self.relationships {
+            relationship.validate_strict_all(self, &mut reporter.wrap());
+        }
+
+        // Report if some update functions reference variables by name and others by ID.
+        // Valid functions have names resolved to IDs, so their style is recorded during
+        // import. Invalid functions still contain the original expression.
+        let mut by_name = Vec::new();
+        let mut by_id = Vec::new();
+        for variable in &self.variables {
+            let expression = match &variable.formula {
+                Some(Err(error)) => Some(error.expression.as_str()),
+                _ => None,
+            };
+            if self.reference_styles.by_name.contains(&variable.id)
+                || expression.is_some_and(BmaUpdateFunction::uses_name_references)
+            {
+                by_name.push(variable.id);
+            }
+            if self.reference_styles.by_id.contains(&variable.id)
+                || expression.is_some_and(BmaUpdateFunction::uses_id_references)
+            {
+                by_id.push(variable.id);
+            }
+        }
+        if !by_name.is_empty() && !by_id.is_empty() {
+            by_name.sort_unstable();
+            by_id.sort_unstable();
+            reporter.report(BmaNetworkError::ModelStyleInconsistent { by_name, by_id });
         }
     }
 }
@@ -131,12 +728,19 @@ impl Validation for BmaNetwork {
 #[cfg(test)]
 mod tests {
     use crate::model::tests::simple_network;
-    use crate::{BmaNetwork, Validation};
+    use crate::update_function::BmaUpdateFunction;
+    use crate::{
+        BmaModel, BmaNetwork, BmaNetworkError, BmaRelationship, BmaRelationshipError, BmaVariable,
+        RelationshipType, Validation,
+    };
+    use std::collections::HashSet;
 
     #[test]
     fn default_network_is_valid() {
         let network = BmaNetwork::default();
         assert!(network.validate().is_ok());
+        assert!(network.is_empty());
+        assert!(!simple_network().is_empty());
     }
 
     #[test]
@@ -144,4 +748,339 @@ mod tests {
         let network = simple_network();
         assert!(network.validate().is_ok());
     }
+
+    #[test]
+    fn find_relationship() {
+        let mut network = simple_network();
+        network
+            .relationships
+            .push(BmaRelationship::new_inhibitor(2, 0, 3));
+
+        let found = network.find_relationship(0, 3);
+        let ids = found.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![0, 2]);
+        assert!(network.find_relationship(3, 0).is_empty());
+
+        assert_eq!(network.find_relationship_by_id(1).unwrap().to_variable, 3);
+        assert!(network.find_relationship_by_id(5).is_none());
+    }
+
+    #[test]
+    fn network_json() {
+        let mut network = simple_network();
+        network.variables[0].formula = Some(BmaUpdateFunction::try_from("1 - var(3)"));
+        let json = network.to_json_string().unwrap();
+        assert_eq!(BmaNetwork::from_json_string(&json).unwrap(), network);
+
+        // The network can be embedded into a full model.
+        let model = BmaModel::from_json_string(&format!(r#"{{"Model": {json}}}"#)).unwrap();
+        assert_eq!(model.network, network);
+        assert!(model.to_json_string().unwrap().contains(&json));
+
+        // Names in update functions are resolved using the network variables.
+        let json = r#"{"Name": "n", "Variables": [
+            {"Id": 1, "Name": "a", "RangeFrom": 0, "RangeTo": 1, "Formula": "var(a)"}
+        ], "Relationships": [{"Id": 1, "FromVariable": 1, "ToVariable": 1, "Type": "Activator"}]}"#;
+        let network = BmaNetwork::from_json_string(json).unwrap();
+        assert_eq!(network.variables[0].formula_string(), "var(1)");
+        assert!(BmaNetwork::from_json_string("42").is_err());
+    }
+
+    #[test]
+    fn rescale_variable() {
+        let f = |s: &str| Some(BmaUpdateFunction::try_from(s).unwrap());
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 4), f("4")),
+                BmaVariable::new(2, "b", (0, 1), f("var(1)")),
+                BmaVariable::new(3, "c", (0, 1), f("1 - var(1)")),
+                BmaVariable::new(4, "d", (0, 1), f("var(4)")),
+            ],
+            vec![
+                BmaRelationship::new_activator(1, 1, 2),
+                BmaRelationship::new_activator(2, 4, 4),
+                BmaRelationship::new_inhibitor(3, 1, 3),
+            ],
+        );
+
+        assert_eq!(network.rescale_variable(1, (1, 2)).unwrap(), vec![2, 3]);
+        let a = network.find_variable(1).unwrap();
+        assert_eq!(a.range, (1, 2));
+        assert_eq!(a.formula_string(), "2");
+        network.validate().unwrap();
+
+        // Self-loops are not reported.
+        assert!(network.rescale_variable(4, (0, 3)).unwrap().is_empty());
+        assert!(network.rescale_variable(4, (3, 0)).is_err());
+        assert!(network.rescale_variable(5, (0, 1)).is_err());
+    }
+
+    #[test]
+    fn eq_ignoring_order() {
+        let network = simple_network();
+        let mut other = simple_network();
+        other.variables.reverse();
+        other.relationships.reverse();
+        other.relationships[0].id = 10;
+        other.variables[0].name = "renamed".to_string();
+        assert!(network.eq_ignoring_order(&other));
+
+        // The default function of `var_B` is equivalent to `var(0) - var(3)`.
+        let var_b = other.variables.iter_mut().find(|v| v.id == 3).unwrap();
+        var_b.formula = Some(BmaUpdateFunction::try_from("var(0) - var(3)"));
+        assert!(network.eq_ignoring_order(&other));
+        let var_b = other.variables.iter_mut().find(|v| v.id == 3).unwrap();
+        var_b.formula = Some(BmaUpdateFunction::try_from("var(0)"));
+        assert!(!network.eq_ignoring_order(&other));
+
+        let mut other = simple_network();
+        other.relationships[0].r#type = RelationshipType::Inhibitor;
+        assert!(!network.eq_ignoring_order(&other));
+        let mut other = simple_network();
+        other.variables[1].range = (0, 3);
+        assert!(!network.eq_ignoring_order(&other));
+    }
+
+    #[test]
+    fn function_classes() {
+        let f = |s: &str| BmaUpdateFunction::try_from(s).ok();
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new_boolean(1, "a", None),
+                BmaVariable::new_boolean(2, "b", None),
+                BmaVariable::new_boolean(3, "c", f("min(var(1), var(2))")),
+                BmaVariable::new_boolean(4, "d", f("var(1) * var(3)")),
+                BmaVariable::new_boolean(5, "e", f("max(var(1), var(2))")),
+            ],
+            vec![
+                BmaRelationship::new_activator(0, 1, 3),
+                BmaRelationship::new_activator(1, 2, 3),
+                BmaRelationship::new_activator(2, 1, 4),
+                BmaRelationship::new_activator(3, 3, 4),
+                BmaRelationship::new_activator(4, 1, 5),
+                BmaRelationship::new_activator(5, 2, 5),
+            ],
+        );
+        let classes = network.function_classes();
+        assert_eq!(classes.len(), 3);
+        assert_eq!(classes["[] -> 0..1: [0]"], vec![1, 2]);
+        assert_eq!(classes["[0..1, 0..1] -> 0..1: [0, 0, 0, 1]"], vec![3, 4]);
+        assert_eq!(classes["[0..1, 0..1] -> 0..1: [0, 1, 1, 1]"], vec![5]);
+    }
+
+    #[test]
+    fn relationships_of() {
+        let network = simple_network();
+        let ids = |list: Vec<&BmaRelationship>| list.iter().map(|r| r.id).collect::<Vec<_>>();
+
+        let (incoming, outgoing) = network.relationships_of(3);
+        assert_eq!(ids(incoming), vec![0, 1]);
+        assert_eq!(ids(outgoing), vec![1]);
+
+        let (incoming, outgoing) = network.relationships_of(0);
+        assert!(incoming.is_empty());
+        assert_eq!(ids(outgoing), vec![0]);
+
+        let (incoming, outgoing) = network.relationships_of(7);
+        assert!(incoming.is_empty() && outgoing.is_empty());
+    }
+
+    #[test]
+    fn prune_unused_relationships() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 1), None),
+                BmaVariable::new(2, "b", (0, 1), None),
+                BmaVariable::new(3, "c", (0, 1), None),
+                BmaVariable::new(4, "d", (1, 1), None),
+            ],
+            vec![
+                BmaRelationship::new_activator(10, 1, 3),
+                BmaRelationship::new_activator(11, 2, 3),
+                BmaRelationship::new_activator(12, 1, 2),
+                BmaRelationship::new_activator(13, 4, 3),
+            ],
+        );
+        let function = BmaUpdateFunction::try_from("var(1)").unwrap();
+        network.variables[2].formula = Some(Ok(function));
+        assert!(network.validate().is_err());
+
+        assert_eq!(network.prune_unused_relationships(), vec![11]);
+        assert_eq!(network.relationships.len(), 3);
+        assert!(network.validate().is_ok());
+        assert!(network.prune_unused_relationships().is_empty());
+    }
+
+    #[test]
+    fn renumber_relationships() {
+        let mut network = simple_network();
+        network
+            .relationships
+            .push(BmaRelationship::new_activator(0, 3, 0));
+        let relationships = |n: &BmaNetwork| {
+            n.relationships
+                .iter()
+                .map(|r| (r.id, r.from_variable, r.to_variable))
+                .collect::<Vec<_>>()
+        };
+        assert!(network.validate().is_err());
+        network.renumber_relationships(10);
+        assert_eq!(
+            relationships(&network),
+            vec![(10, 0, 3), (11, 3, 3), (12, 3, 0)]
+        );
+        assert!(network.validate().is_ok());
+
+        // The last ID can be `u32::MAX`.
+        network.renumber_relationships(u32::MAX - 2);
+        assert_eq!(network.relationships[2].id, u32::MAX);
+    }
+
+    #[test]
+    fn isolated_variables() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(5, "a", (0, 1), None),
+                BmaVariable::new(2, "b", (0, 1), None),
+                BmaVariable::new(3, "c", (0, 1), None),
+                BmaVariable::new(4, "d", (1, 1), None),
+                BmaVariable::new(1, "e", (0, 2), None),
+            ],
+            vec![BmaRelationship::new_activator(10, 5, 2)],
+        );
+        network.variables[2].formula = Some(BmaUpdateFunction::try_from("2 - 1"));
+        network.variables[3].formula = Some(BmaUpdateFunction::try_from("var(4)"));
+        assert_eq!(network.isolated_variables(), vec![1, 3]);
+
+        network.variables[2].formula = Some(BmaUpdateFunction::try_from("var(3) +"));
+        assert_eq!(network.isolated_variables(), vec![1]);
+    }
+
+    #[test]
+    fn default_and_explicit_functions() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(3, "a", (0, 1), None),
+                BmaVariable::new(1, "b", (0, 1), None),
+                BmaVariable::new(2, "c", (0, 1), None),
+            ],
+            vec![BmaRelationship::new_activator(10, 3, 1)],
+        );
+        network.variables[1].formula = Some(BmaUpdateFunction::try_from("var(3)"));
+        network.variables[2].formula = Some(BmaUpdateFunction::try_from("var(3) +"));
+        assert_eq!(network.variables_with_default_function(), vec![3]);
+        assert_eq!(network.variables_with_explicit_function(), vec![1, 2]);
+
+        network.populate_missing_functions();
+        assert!(network.variables_with_default_function().is_empty());
+        assert_eq!(network.variables_with_explicit_function(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn validate_many_relationships() {
+        // 100 variables in a cycle, where each edge is repeated 100 times. The last
+        // relationship reuses the ID of the first one.
+        let variables = (0..100)
+            .map(|id| BmaVariable::new_boolean(id, &format!("v_{id}"), None))
+            .collect();
+        let relationships = (0..10_000u32)
+            .map(|i| BmaRelationship::new_activator(i % 9_999, i % 100, (i + 1) % 100))
+            .collect();
+        let network = BmaNetwork::new(variables, relationships);
+        let errors = network.validate().unwrap_err();
+        let expected = BmaNetworkError::Relationship(BmaRelationshipError::IdNotUnique { id: 0 });
+        assert_eq!(errors, vec![expected.clone(), expected]);
+    }
+
+    #[test]
+    fn relationship_sign_report() {
+        let mut network = simple_network();
+        network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(0) * var(3)"));
+        network
+            .relationships
+            .push(BmaRelationship::new_activator(2, 3, 5));
+        let report = network.relationship_sign_report();
+        assert_eq!(
+            report,
+            vec![
+                (
+                    0,
+                    RelationshipType::Activator,
+                    vec![RelationshipType::Activator]
+                ),
+                (
+                    1,
+                    RelationshipType::Inhibitor,
+                    vec![RelationshipType::Activator]
+                ),
+                (2, RelationshipType::Activator, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn essential_regulators() {
+        let f = |s: &str| Some(BmaUpdateFunction::try_from(s));
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 1), None),
+                BmaVariable::new(2, "b", (0, 2), None),
+                BmaVariable::new(3, "c", (0, 1), None),
+                BmaVariable::new(4, "d", (1, 1), None),
+            ],
+            vec![
+                BmaRelationship::new_activator(10, 1, 3),
+                BmaRelationship::new_activator(11, 2, 3),
+                BmaRelationship::new_inhibitor(12, 3, 3),
+                BmaRelationship::new_activator(13, 1, 4),
+            ],
+        );
+        // `b` is non-monotonic, `c` has no influence.
+        network.variables[2].formula = f("var(1) * abs(2 * var(2) - 1) + 0 * var(3)");
+        let expected = HashSet::from([1, 2]);
+        assert_eq!(network.essential_regulators(3).unwrap(), expected);
+        assert!(network.essential_regulators(4).unwrap().is_empty());
+        assert!(network.essential_regulators(5).is_err());
+    }
+
+    #[test]
+    fn resolve_unknown_relationships() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 1), None),
+                BmaVariable::new(2, "b", (0, 1), None),
+                BmaVariable::new(3, "c", (0, 2), None),
+                BmaVariable::new(4, "d", (0, 1), None),
+            ],
+            vec![
+                BmaRelationship::new_activator(10, 1, 3),
+                BmaRelationship::new_activator(11, 2, 3),
+                BmaRelationship::new_activator(12, 4, 3),
+                BmaRelationship::new_activator(13, 1, 4),
+            ],
+        );
+        for relationship in &mut network.relationships {
+            relationship.r#type = RelationshipType::Unknown(String::new());
+        }
+        let function = BmaUpdateFunction::try_from("var(1) - var(2) + abs(var(4) - 1)").unwrap();
+        network.variables[2].formula = Some(Ok(function));
+
+        // Relationship `12` is non-monotonic and `13` targets a default function.
+        assert_eq!(network.resolve_unknown_relationships(), 2);
+        let types = network
+            .relationships
+            .iter()
+            .map(|r| r.r#type.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                RelationshipType::Activator,
+                RelationshipType::Inhibitor,
+                RelationshipType::Unknown(String::new()),
+                RelationshipType::Unknown(String::new()),
+            ]
+        );
+        assert_eq!(network.resolve_unknown_relationships(), 0);
+    }
 }
@@ -0,0 +1,413 @@
+use crate::BmaNetwork;
+use crate::update_function::CompiledUpdate;
+use anyhow::anyhow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+/// Pre-computed asynchronous dynamics of a [`BmaNetwork`].
+///
+/// States are represented as vectors of levels, where the levels are ordered by the
+/// IDs of the corresponding variables.
+pub(crate) struct AsyncDynamics {
+    /// Sorted variable IDs (i.e. the meaning of each state vector position).
+    variables: Vec<u32>,
+    /// Variable ranges, in the same order as `variables`.
+    ranges: Vec<(u32, u32)>,
+    /// Compiled update function of each variable, together with the positions of its
+    /// regulators in the state vector.
+    updates: Vec<(CompiledUpdate, Vec<usize>)>,
+}
+
+impl AsyncDynamics {
+    /// Compile the update functions of all variables in the given network.
+    pub(crate) fn new(network: &BmaNetwork) -> anyhow::Result<AsyncDynamics> {
+        let mut variables = network.variables.iter().map(|v| v.id).collect::<Vec<_>>();
+        variables.sort_unstable();
+        variables.dedup();
+        if variables.len() != network.variables.len() {
+            return Err(anyhow!("Variable IDs are not unique"));
+        }
+
+        let mut ranges = Vec::with_capacity(variables.len());
+        let mut updates = Vec::with_capacity(variables.len());
+        for id in &variables {
+            let var = network
+                .find_variable(*id)
+                .expect("Invariant violation: Variable must exist.");
+            if var.min_level() > var.max_level() {
+                return Err(anyhow!(
+                    "Variable `{id}` has invalid range `{:?}`",
+                    var.range
+                ));
+            }
+            let update = network.compile_variable(*id)?;
+            let positions = update
+                .regulators()
+                .iter()
+                .map(|reg| {
+                    variables
+                        .binary_search(reg)
+                        .expect("Invariant violation: Regulator must exist.")
+                })
+                .collect::<Vec<_>>();
+            ranges.push(var.range);
+            updates.push((update, positions));
+        }
+
+        Ok(AsyncDynamics {
+            variables,
+            ranges,
+            updates,
+        })
+    }
+
+    /// Convert a valuation into a state vector, checking that all variables have valid levels.
+    pub(crate) fn encode(&self, valuation: &BTreeMap<u32, u32>) -> anyhow::Result<Vec<u32>> {
+        if let Some(id) = valuation
+            .keys()
+            .find(|id| self.variables.binary_search(id).is_err())
+        {
+            return Err(anyhow!("Variable with id `{id}` not found"));
+        }
+        let mut state = Vec::with_capacity(self.variables.len());
+        for (id, (min, max)) in self.variables.iter().zip(&self.ranges) {
+            let level = *valuation
+                .get(id)
+                .ok_or_else(|| anyhow!("Missing level of variable `{id}`"))?;
+            if level < *min || level > *max {
+                return Err(anyhow!(
+                    "Level `{level}` of variable `{id}` is outside of range `{:?}`",
+                    (min, max)
+                ));
+            }
+            state.push(level);
+        }
+        Ok(state)
+    }
+
+    /// Convert a state vector back into a valuation.
+    pub(crate) fn decode(&self, state: &[u32]) -> BTreeMap<u32, u32> {
+        self.variables
+            .iter()
+            .copied()
+            .zip(state.iter().copied())
+            .collect()
+    }
+
+    /// Compute the asynchronous successors of the given state.
+    pub(crate) fn successors(&self, state: &[u32]) -> anyhow::Result<Vec<Vec<u32>>> {
+        let mut result = Vec::new();
+        let mut inputs = Vec::new();
+        for (i, (update, positions)) in self.updates.iter().enumerate() {
+            inputs.clear();
+            inputs.extend(positions.iter().map(|p| state[*p]));
+            // Unlike in function tables (where a constant variable can also output `0`), the
+            // successor must stay within the variable range, so the output is clamped
+            // (e.g. a constant `(3,3)` with update function `0` stays at `3`).
+            let (min, max) = self.ranges[i];
+            let target = update.try_eval(&inputs)?.clamp(min, max);
+            let level = state[i];
+            let next = match target.cmp(&level) {
+                Ordering::Greater => level + 1,
+                Ordering::Less => level - 1,
+                Ordering::Equal => continue,
+            };
+            let mut successor = state.to_vec();
+            successor[i] = next;
+            result.push(successor);
+        }
+        Ok(result)
+    }
+}
+
+impl BmaNetwork {
+    /// Compute the successors of the given `state` under the asynchronous BMA semantics.
+    ///
+    /// In each successor, exactly one variable moves one level towards the value of its
+    /// update function, truncated to the variable range (variables whose level already matches
+    /// this value do not produce a successor). Variables without an update function use the default function
+    /// (see [`BmaNetwork::build_default_update_function`]).
+    ///
+    /// The `state` must assign a level within the variable range to every variable
+    /// of the network. The operation also fails if any of the update functions cannot be
+    /// evaluated (see [`BmaNetwork::compile_variable`]).
+    pub fn async_successors(
+        &self,
+        state: &BTreeMap<u32, u32>,
+    ) -> anyhow::Result<Vec<BTreeMap<u32, u32>>> {
+        let dynamics = AsyncDynamics::new(self)?;
+        let state = dynamics.encode(state)?;
+        let successors = dynamics.successors(&state)?;
+        Ok(successors.iter().map(|s| dynamics.decode(s)).collect())
+    }
+
+    /// Compute the states reachable from the `initial` states under the asynchronous BMA
+    /// semantics (see [`BmaNetwork::async_successors`]) using breadth-first search.
+    ///
+    /// Each state is represented as a vector of levels, ordered by the IDs of the
+    /// corresponding variables. The search stops once `max_states` states are discovered.
+    /// The second item of the result is `true` if the search was truncated this way (i.e.
+    /// the returned set may not contain all reachable states).
+    pub fn reachable_states(
+        &self,
+        initial: &[BTreeMap<u32, u32>],
+        max_states: usize,
+    ) -> anyhow::Result<(HashSet<Vec<u32>>, bool)> {
+        let dynamics = AsyncDynamics::new(self)?;
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        for state in initial {
+            let state = dynamics.encode(state)?;
+            if visited.contains(&state) {
+                continue;
+            }
+            if visited.len() >= max_states {
+                return Ok((visited, true));
+            }
+            visited.insert(state.clone());
+            queue.push_back(state);
+        }
+
+        while let Some(state) = queue.pop_front() {
+            for successor in dynamics.successors(&state)? {
+                if visited.contains(&successor) {
+                    continue;
+                }
+                if visited.len() >= max_states {
+                    return Ok((visited, true));
+                }
+                visited.insert(successor.clone());
+                queue.push_back(successor);
+            }
+        }
+
+        Ok((visited, false))
+    }
+
+    /// Export the asynchronous state-transition graph of this network (see
+    /// [`BmaNetwork::async_successors`]) restricted to the states reachable from the
+    /// `initial` states (see [`BmaNetwork::reachable_states`]) in the DOT format.
+    ///
+    /// Every reachable state is a node labeled with the levels of all variables (ordered
+    /// by variable ID). Nodes are numbered in the lexicographic order of these levels. Each edge
+    /// is labeled with the name (or ID, if the name is empty) of the variable that changed,
+    /// followed by `+` or `-` depending on the direction of the change.
+    ///
+    /// The operation fails if more than `max_states` states are reachable, if the `initial`
+    /// states are not valid (see [`BmaNetwork::async_successors`]), or if any update function
+    /// cannot be evaluated (see [`BmaNetwork::compile_variable`]).
+    pub fn transition_graph_dot(
+        &self,
+        initial: &[BTreeMap<u32, u32>],
+        max_states: usize,
+    ) -> anyhow::Result<String> {
+        let (states, truncated) = self.reachable_states(initial, max_states)?;
+        if truncated {
+            return Err(anyhow!("More than `{max_states}` states are reachable"));
+        }
+        let mut states = states.into_iter().collect::<Vec<_>>();
+        states.sort_unstable();
+        let dynamics = AsyncDynamics::new(self)?;
+        let names = dynamics
+            .variables
+            .iter()
+            .map(|id| {
+                let var = self
+                    .find_variable(*id)
+                    .expect("Invariant violation: Variable must exist.");
+                if var.name.is_empty() {
+                    id.to_string()
+                } else {
+                    var.name.replace('\\', "\\\\").replace('"', "\\\"")
+                }
+            })
+            .collect::<Vec<_>>();
+        let index = states
+            .iter()
+            .enumerate()
+            .map(|(i, state)| (state.as_slice(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut result = String::from("digraph {\n");
+        for (i, state) in states.iter().enumerate() {
+            let label = names
+                .iter()
+                .zip(state)
+                .map(|(name, level)| format!("{name}={level}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(result, "  s{i} [label=\"{label}\"];").unwrap();
+        }
+        for (i, state) in states.iter().enumerate() {
+            for successor in dynamics.successors(state)? {
+                let (changed, direction) = state
+                    .iter()
+                    .zip(&successor)
+                    .enumerate()
+                    .find_map(|(k, (old, new))| match new.cmp(old) {
+                        Ordering::Greater => Some((k, '+')),
+                        Ordering::Less => Some((k, '-')),
+                        Ordering::Equal => None,
+                    })
+                    .expect("Invariant violation: Successor must differ from its state.");
+                let j = index.get(successor.as_slice()).ok_or_else(|| {
+                    anyhow!("Invariant violation: Successor `{successor:?}` was not reached")
+                })?;
+                let name = &names[changed];
+                writeln!(result, "  s{i} -> s{j} [label=\"{name}{direction}\"];").unwrap();
+            }
+        }
+        result.push_str("}\n");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::update_function::BmaUpdateFunction;
+    use crate::{BmaNetwork, BmaRelationship, BmaVariable, Validation};
+    use std::collections::{BTreeMap, HashSet};
+
+    /// `a` in `[0,2]` is a (default) input; `b` in `[0,2]` copies `a`.
+    fn copy_network() -> BmaNetwork {
+        let b_update = BmaUpdateFunction::try_from("var(1)").unwrap();
+        BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 2), None),
+                BmaVariable::new(2, "b", (0, 2), Some(b_update)),
+            ],
+            vec![BmaRelationship::new_activator(3, 1, 2)],
+        )
+    }
+
+    #[test]
+    fn async_successors() {
+        let network = copy_network();
+        // `a` has no regulators, so it goes down to `0`. `b` moves towards `a`.
+        let state = BTreeMap::from([(1, 2), (2, 0)]);
+        let successors = network.async_successors(&state).unwrap();
+        assert_eq!(
+            successors,
+            vec![
+                BTreeMap::from([(1, 1), (2, 0)]),
+                BTreeMap::from([(1, 2), (2, 1)]),
+            ]
+        );
+
+        let fixed_point = BTreeMap::from([(1, 0), (2, 0)]);
+        assert!(network.async_successors(&fixed_point).unwrap().is_empty());
+
+        assert!(network.async_successors(&BTreeMap::from([(1, 0)])).is_err());
+        let out_of_range = BTreeMap::from([(1, 3), (2, 0)]);
+        assert!(network.async_successors(&out_of_range).is_err());
+    }
+
+    #[test]
+    fn successors_stay_in_range() {
+        // A valid constant whose update function is `0` (tolerated by validation).
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "c", (3, 3), Some(BmaUpdateFunction::mk_constant(0))),
+                BmaVariable::new_boolean(2, "a", None),
+            ],
+            vec![],
+        );
+        assert!(network.validate().is_ok());
+        let state = BTreeMap::from([(1, 3), (2, 1)]);
+        let successors = network.async_successors(&state).unwrap();
+        assert_eq!(successors, vec![BTreeMap::from([(1, 3), (2, 0)])]);
+
+        let (states, truncated) = network.reachable_states(&[state], 100).unwrap();
+        assert!(!truncated);
+        assert_eq!(states, HashSet::from([vec![3, 1], vec![3, 0]]));
+    }
+
+    #[test]
+    fn transition_graph_dot() {
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new_boolean(1, "a", None),
+                BmaVariable::new_boolean(
+                    2,
+                    "",
+                    Some(BmaUpdateFunction::try_from("var(1)").unwrap()),
+                ),
+            ],
+            vec![BmaRelationship::new_activator(3, 1, 2)],
+        );
+        let expected = "digraph {
+  s0 [label=\"a=0, 2=0\"];
+  s1 [label=\"a=0, 2=1\"];
+  s2 [label=\"a=1, 2=0\"];
+  s3 [label=\"a=1, 2=1\"];
+  s1 -> s0 [label=\"2-\"];
+  s2 -> s0 [label=\"a-\"];
+  s2 -> s3 [label=\"2+\"];
+  s3 -> s1 [label=\"a-\"];
+}
+";
+        let initial = [BTreeMap::from([(1, 1), (2, 0)])];
+        assert_eq!(network.transition_graph_dot(&initial, 4).unwrap(), expected);
+        assert!(network.transition_graph_dot(&initial, 3).is_err());
+
+        // Only the reachable part of the graph is exported.
+        let initial = [BTreeMap::from([(1, 0), (2, 1)])];
+        let expected = "digraph {
+  s0 [label=\"a=0, 2=0\"];
+  s1 [label=\"a=0, 2=1\"];
+  s1 -> s0 [label=\"2-\"];
+}
+";
+        assert_eq!(network.transition_graph_dot(&initial, 4).unwrap(), expected);
+        let initial = [BTreeMap::from([(1, 2), (2, 0)])];
+        assert!(copy_network().transition_graph_dot(&initial, 9).is_ok());
+    }
+
+    #[test]
+    fn transition_graph_dot_of_truncated_constant() {
+        let network = BmaNetwork::new(
+            vec![BmaVariable::new(
+                1,
+                "c",
+                (3, 3),
+                Some(BmaUpdateFunction::mk_constant(0)),
+            )],
+            vec![],
+        );
+        let initial = [BTreeMap::from([(1, 3)])];
+        let expected = "digraph {\n  s0 [label=\"c=3\"];\n}\n";
+        assert_eq!(network.transition_graph_dot(&initial, 1).unwrap(), expected);
+    }
+
+    #[test]
+    fn reachable_states() {
+        let network = copy_network();
+        let initial = vec![BTreeMap::from([(1, 2), (2, 0)])];
+
+        let (states, truncated) = network.reachable_states(&initial, 100).unwrap();
+        assert!(!truncated);
+        let expected = HashSet::from([
+            vec![2, 0],
+            vec![1, 0],
+            vec![0, 0],
+            vec![2, 1],
+            vec![1, 1],
+            vec![0, 1],
+            vec![2, 2],
+            vec![1, 2],
+            vec![0, 2],
+        ]);
+        assert_eq!(states, expected);
+
+        // From `a=0`, `a` cannot grow again.
+        let low_initial = vec![BTreeMap::from([(1, 0), (2, 2)])];
+        let (states, truncated) = network.reachable_states(&low_initial, 100).unwrap();
+        assert!(!truncated);
+        assert_eq!(states, HashSet::from([vec![0, 2], vec![0, 1], vec![0, 0]]));
+
+        let (states, truncated) = network.reachable_states(&initial, 3).unwrap();
+        assert!(truncated);
+        assert_eq!(states.len(), 3);
+    }
+}
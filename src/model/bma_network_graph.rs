@@ -0,0 +1,184 @@
+use crate::{BmaNetwork, RelationshipType};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+impl BmaNetwork {
+    /// Assign variables to layers of a hierarchical layout, such that every relationship
+    /// points from a lower layer to a higher layer.
+    ///
+    /// The layers are computed using longest-path layering: variables without regulators
+    /// (inputs) are in layer `0`, and every other variable is one layer above its highest
+    /// regulator. Variables that regulate no other variable (outputs) are then moved to the
+    /// last layer. Self-loops are ignored, as are relationships that reference missing
+    /// variables. Variable IDs within each layer are sorted.
+    ///
+    /// If the network contains a cycle (of length at least two), the method returns the
+    /// IDs of the variables on one such cycle instead (in the order of the relationships,
+    /// starting with the smallest ID).
+    pub fn topological_layers(&self) -> Result<Vec<Vec<u32>>, Vec<u32>> {
+        let variables = self.variables.iter().map(|v| v.id).collect::<BTreeSet<_>>();
+        let mut regulators: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+        let mut targets: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+        for var in &variables {
+            regulators.insert(*var, BTreeSet::new());
+            targets.insert(*var, BTreeSet::new());
+        }
+        for r in &self.relationships {
+            let (from, to) = (r.from_variable, r.to_variable);
+            if from != to && variables.contains(&from) && variables.contains(&to) {
+                regulators.entry(to).or_default().insert(from);
+                targets.entry(from).or_default().insert(to);
+            }
+        }
+
+        // Kahn's algorithm, computing the longest path to each variable.
+        let mut layer: BTreeMap<u32, usize> = BTreeMap::new();
+        let mut remaining: BTreeMap<u32, usize> = regulators
+            .iter()
+            .map(|(var, regs)| (*var, regs.len()))
+            .collect();
+        let mut ready = remaining
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(var, _)| *var)
+            .collect::<Vec<_>>();
+        while let Some(var) = ready.pop() {
+            remaining.remove(&var);
+            let var_layer = regulators[&var]
+                .iter()
+                .map(|r| layer[r] + 1)
+                .max()
+                .unwrap_or(0);
+            layer.insert(var, var_layer);
+            for target in &targets[&var] {
+                let count = remaining
+                    .get_mut(target)
+                    .expect("Invariant violation: Target cannot be processed before regulator.");
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(*target);
+                }
+            }
+        }
+
+        if let Some((start, _)) = remaining.first_key_value() {
+            // Every remaining variable has a remaining regulator, so following them
+            // backwards must eventually revisit a variable.
+            let mut path = vec![*start];
+            loop {
+                let last = path[path.len() - 1];
+                let regulator = *regulators[&last]
+                    .iter()
+                    .find(|r| remaining.contains_key(r))
+                    .expect("Invariant violation: Remaining variable has remaining regulator.");
+                if let Some(position) = path.iter().position(|v| *v == regulator) {
+                    let mut cycle = path[position..].to_vec();
+                    cycle.reverse();
+                    let smallest = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap_or(0);
+                    cycle.rotate_left(smallest);
+                    return Err(cycle);
+                }
+                path.push(regulator);
+            }
+        }
+
+        let last_layer = layer.values().copied().max().unwrap_or(0);
+        let layer_count = if layer.is_empty() { 0 } else { last_layer + 1 };
+        let mut result = vec![Vec::new(); layer_count];
+        for (var, var_layer) in layer {
+            let is_output = targets[&var].is_empty() && !regulators[&var].is_empty();
+            let var_layer = if is_output { last_layer } else { var_layer };
+            result[var_layer].push(var);
+        }
+        for layer in &mut result {
+            layer.sort_unstable();
+        }
+        Ok(result)
+    }
+
+    /// Export the influence graph of this network in the DOT format.
+    ///
+    /// Every variable is a node `v<id>` labeled with its name (or ID, if the name is empty),
+    /// and every relationship is an edge. Activators use the `normal` arrowhead, inhibitors
+    /// use the `tee` arrowhead, and unknown relationships use the `odot` arrowhead.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph {\n");
+        for var in &self.variables {
+            let label = if var.name.is_empty() {
+                var.id.to_string()
+            } else {
+                var.name.replace('\\', "\\\\").replace('"', "\\\"")
+            };
+            writeln!(result, "  v{} [label=\"{label}\"];", var.id).unwrap();
+        }
+        for r in &self.relationships {
+            let arrowhead = match r.r#type {
+                RelationshipType::Activator => "normal",
+                RelationshipType::Inhibitor => "tee",
+                RelationshipType::Unknown(_) => "odot",
+            };
+            writeln!(
+                result,
+                "  v{} -> v{} [arrowhead={arrowhead}];",
+                r.from_variable, r.to_variable
+            )
+            .unwrap();
+        }
+        result.push_str("}\n");
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BmaNetwork, BmaRelationship, BmaVariable, RelationshipType};
+
+    fn network(variables: &[u32], edges: &[(u32, u32)]) -> BmaNetwork {
+        let variables = variables
+            .iter()
+            .map(|id| BmaVariable::new_boolean(*id, &format!("v_{id}"), None))
+            .collect();
+        let relationships = edges
+            .iter()
+            .zip(0u32..)
+            .map(|((from, to), id)| BmaRelationship::new_activator(id, *from, *to))
+            .collect();
+        BmaNetwork::new(variables, relationships)
+    }
+
+    #[test]
+    fn layers_of_cascade() {
+        // 1 -> 2 -> 3 -> 4, 1 -> 5 (output), 6 (isolated), 2 -> 2 (self-loop)
+        let network = network(
+            &[1, 2, 3, 4, 5, 6],
+            &[(1, 2), (2, 3), (3, 4), (1, 5), (2, 2)],
+        );
+        let layers = network.topological_layers().unwrap();
+        assert_eq!(layers, vec![vec![1, 6], vec![2], vec![3], vec![4, 5]]);
+
+        assert_eq!(
+            BmaNetwork::default().topological_layers(),
+            Ok(Vec::<Vec<u32>>::new())
+        );
+    }
+
+    #[test]
+    fn layers_of_cyclic_network() {
+        // 1 -> 2 -> 3 -> 4 -> 2
+        let network = network(&[1, 2, 3, 4], &[(1, 2), (2, 3), (3, 4), (4, 2)]);
+        let cycle = network.topological_layers().unwrap_err();
+        assert_eq!(cycle, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn influence_graph_dot() {
+        let mut network = network(&[1, 2], &[(1, 2), (2, 2)]);
+        network.relationships[1].r#type = RelationshipType::Inhibitor;
+        network.variables[1].name = String::new();
+        assert_eq!(
+            network.to_dot(),
+            "digraph {\n  v1 [label=\"v_1\"];\n  v2 [label=\"2\"];\n  v1 -> v2 [arrowhead=normal];\n  v2 -> v2 [arrowhead=tee];\n}\n"
+        );
+    }
+}
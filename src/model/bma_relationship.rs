@@ -1,7 +1,11 @@
-use crate::utils::is_unique_id;
+use crate::utils::count_ids;
 use crate::{BmaNetwork, BmaVariable, ContextualValidation, ErrorReporter};
+#[cfg(feature = "bn-conversion")]
 use biodivine_lib_param_bn::Monotonicity;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use thiserror::Error;
 
 /// A relationship of a given [`RelationshipType`] between two [`BmaVariable`] objects.
@@ -26,26 +30,27 @@ pub struct BmaRelationship {
 }
 
 impl BmaRelationship {
-    /// Make a new [`RelationshipType::Activator`] relationship between two variables.
+    /// Make a new relationship of the given [`RelationshipType`] between two variables.
     #[must_use]
-    pub fn new_activator(id: u32, from: u32, to: u32) -> Self {
+    pub fn new(id: u32, from: u32, to: u32, kind: RelationshipType) -> Self {
         BmaRelationship {
             id,
             from_variable: from,
             to_variable: to,
-            r#type: RelationshipType::Activator,
+            r#type: kind,
         }
     }
 
+    /// Make a new [`RelationshipType::Activator`] relationship between two variables.
+    #[must_use]
+    pub fn new_activator(id: u32, from: u32, to: u32) -> Self {
+        Self::new(id, from, to, RelationshipType::Activator)
+    }
+
     /// Make a new [`RelationshipType::Inhibitor`] relationship between two variables.
     #[must_use]
     pub fn new_inhibitor(id: u32, from: u32, to: u32) -> Self {
-        BmaRelationship {
-            id,
-            from_variable: from,
-            to_variable: to,
-            r#type: RelationshipType::Inhibitor,
-        }
+        Self::new(id, from, to, RelationshipType::Inhibitor)
     }
 
     /// Find the regulator variable (`from_variable`) in the enclosing [`BmaNetwork`], assuming
@@ -67,6 +72,29 @@ impl ContextualValidation<BmaNetwork> for BmaRelationship {
     type Error = BmaRelationshipError;
 
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, context: &BmaNetwork, reporter: &mut R) {
+        // This is not a validation error; this violates the whole contract of the validation
+        // mechanism and is therefore allowed to fail (instead of returning an error).
+        assert!(
+            context.relationships.contains(self),
+            "Precondition violation: validated relationship is not part of the `BmaNetwork`."
+        );
+
+        let id_counts = count_ids(&context.relationships, |x| x.id);
+        self.validate_all_with_id_counts(context, &id_counts, reporter);
+    }
+}
+
+impl BmaRelationship {
+    /// Internal version of [`ContextualValidation::validate_all`] which uses precomputed
+    /// `id_counts` of all relationships in the `context` network (see [`count_ids`]). This way,
+    /// the uniqueness of relationship IDs can be checked in linear time when validating
+    /// the whole network.
+    pub(crate) fn validate_all_with_id_counts<R: ErrorReporter<BmaRelationshipError>>(
+        &self,
+        context: &BmaNetwork,
+        id_counts: &HashMap<u32, usize>,
+        reporter: &mut R,
+    ) {
         // Ensure that regulator and target exist in the enclosing BmaNetwork.
 
         if self.find_regulator_variable(context).is_none() {
@@ -85,15 +113,7 @@ impl ContextualValidation<BmaNetwork> for BmaRelationship {
 
         // Ensure that the relationship id is unique within the enclosing BmaNetwork.
 
-        let Ok(is_unique) = is_unique_id(&context.relationships, self, |x| x.id) else {
-            // This is not a validation error; this violates the whole contract of the validation
-            // mechanism and is therefore allowed to fail (instead of returning an error).
-            panic!(
-                "Precondition violation: validated relationship is not part of the `BmaNetwork`."
-            )
-        };
-
-        if !is_unique {
+        if id_counts.get(&self.id).copied().unwrap_or_default() > 1 {
             reporter.report(BmaRelationshipError::IdNotUnique { id: self.id });
         }
 
@@ -106,6 +126,32 @@ impl ContextualValidation<BmaNetwork> for BmaRelationship {
     }
 }
 
+impl BmaRelationship {
+    /// Checks that are only performed by [`crate::BmaModel::validate_strict`]: The relationship
+    /// must not duplicate an earlier relationship (same regulator, target, and type).
+    pub(crate) fn validate_strict_all<R: ErrorReporter<BmaRelationshipError>>(
+        &self,
+        context: &BmaNetwork,
+        reporter: &mut R,
+    ) {
+        let first = context.relationships.iter().find(|r| {
+            r.from_variable == self.from_variable
+                && r.to_variable == self.to_variable
+                && r.r#type == self.r#type
+        });
+        if let Some(first) = first
+            && !std::ptr::eq(first, self)
+        {
+            reporter.report(BmaRelationshipError::DuplicateRelationship {
+                id: self.id,
+                from_variable: self.from_variable,
+                to_variable: self.to_variable,
+                duplicate_of: first.id,
+            });
+        }
+    }
+}
+
 /// Possible validation errors for [`BmaRelationship`].
 #[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BmaRelationshipError {
@@ -117,10 +163,63 @@ pub enum BmaRelationshipError {
     TargetVariableNotFound { id: u32, to_variable: u32 },
     #[error("(Relationship: `{id}`) Unknown relationship type `{value}`")]
     UnknownRelationshipType { id: u32, value: String },
+    #[error(
+        "(Relationship: `{id}`) Relationship from `{from_variable}` to `{to_variable}` duplicates relationship `{duplicate_of}`"
+    )]
+    DuplicateRelationship {
+        id: u32,
+        from_variable: u32,
+        to_variable: u32,
+        duplicate_of: u32,
+    },
+    #[error(
+        "(Relationship: `{from}` -> `{to}`) Both activation and inhibition are declared between the same variables"
+    )]
+    ConflictingSigns { from: u32, to: u32 },
+}
+
+impl BmaRelationshipError {
+    /// A stable identifier of the error kind (see [`crate::BmaModelError::code`]).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaRelationshipError::IdNotUnique { .. } => "relationship.id_not_unique",
+            BmaRelationshipError::RegulatorVariableNotFound { .. } => {
+                "relationship.regulator_variable_not_found"
+            }
+            BmaRelationshipError::TargetVariableNotFound { .. } => {
+                "relationship.target_variable_not_found"
+            }
+            BmaRelationshipError::UnknownRelationshipType { .. } => {
+                "relationship.unknown_relationship_type"
+            }
+            BmaRelationshipError::DuplicateRelationship { .. } => {
+                "relationship.duplicate_relationship"
+            }
+            BmaRelationshipError::ConflictingSigns { .. } => "relationship.conflicting_signs",
+        }
+    }
+
+    /// The ID of the relationship that this error concerns, or `None` if the error is not
+    /// tied to a single relationship (i.e. [`BmaRelationshipError::ConflictingSigns`]).
+    #[must_use]
+    pub fn relationship_id(&self) -> Option<u32> {
+        match self {
+            BmaRelationshipError::IdNotUnique { id }
+            | BmaRelationshipError::RegulatorVariableNotFound { id, .. }
+            | BmaRelationshipError::TargetVariableNotFound { id, .. }
+            | BmaRelationshipError::UnknownRelationshipType { id, .. }
+            | BmaRelationshipError::DuplicateRelationship { id, .. } => Some(*id),
+            BmaRelationshipError::ConflictingSigns { .. } => None,
+        }
+    }
 }
 
 /// The type of [`BmaRelationship`] between two variables in a [`BmaNetwork`].
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+///
+/// Types are ordered as `Activator < Inhibitor < Unknown`, with unknown types ordered
+/// by their string value, such that they can be used as keys of sorted collections.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
 pub enum RelationshipType {
     #[default]
     Activator,
@@ -128,6 +227,37 @@ pub enum RelationshipType {
     Unknown(String),
 }
 
+/// Error returned when parsing an unsupported [`RelationshipType`] string.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+#[error("Unknown relationship type `{0}` (expected `Activator`, `Inhibitor`, or `Unknown`)")]
+pub struct ParseRelationshipTypeError(pub String);
+
+impl FromStr for RelationshipType {
+    type Err = ParseRelationshipTypeError;
+
+    /// Parse a relationship type. The matching is case-insensitive (i.e. it also accepts
+    /// the lowercase spelling used in some JSON files). The value `Unknown` is parsed
+    /// as [`RelationshipType::Unknown`] with the original spelling; any other value is an error.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "activator" => Ok(RelationshipType::Activator),
+            "inhibitor" => Ok(RelationshipType::Inhibitor),
+            "unknown" => Ok(RelationshipType::Unknown(s.to_string())),
+            _ => Err(ParseRelationshipTypeError(s.to_string())),
+        }
+    }
+}
+
+impl Display for RelationshipType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelationshipType::Activator => f.write_str("Activator"),
+            RelationshipType::Inhibitor => f.write_str("Inhibitor"),
+            RelationshipType::Unknown(value) => f.write_str(value),
+        }
+    }
+}
+
 /*
    For serialization, we need to override the default behavior, which in XML is to
    serialize/deserialize using tags, not string values.
@@ -138,11 +268,7 @@ impl Serialize for RelationshipType {
     where
         S: Serializer,
     {
-        match self {
-            RelationshipType::Activator => serializer.serialize_str("Activator"),
-            RelationshipType::Inhibitor => serializer.serialize_str("Inhibitor"),
-            RelationshipType::Unknown(s) => serializer.serialize_str(s),
-        }
+        serializer.serialize_str(&self.to_string())
     }
 }
 
@@ -160,6 +286,7 @@ impl<'de> Deserialize<'de> for RelationshipType {
     }
 }
 
+#[cfg(feature = "bn-conversion")]
 impl TryFrom<RelationshipType> for Monotonicity {
     type Error = ();
 
@@ -172,6 +299,7 @@ impl TryFrom<RelationshipType> for Monotonicity {
     }
 }
 
+#[cfg(feature = "bn-conversion")]
 impl From<Monotonicity> for RelationshipType {
     fn from(val: Monotonicity) -> Self {
         match val {
@@ -183,9 +311,70 @@ impl From<Monotonicity> for RelationshipType {
 
 #[cfg(test)]
 mod tests {
-    use crate::model::bma_relationship::BmaRelationshipError;
+    use crate::model::bma_relationship::{BmaRelationshipError, ParseRelationshipTypeError};
     use crate::{BmaNetwork, BmaRelationship, BmaVariable, ContextualValidation, RelationshipType};
+    #[cfg(feature = "bn-conversion")]
     use biodivine_lib_param_bn::Monotonicity;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn relationship_type_from_str() {
+        for kind in [RelationshipType::Activator, RelationshipType::Inhibitor] {
+            assert_eq!(
+                kind.to_string().parse::<RelationshipType>(),
+                Ok(kind.clone())
+            );
+        }
+        assert_eq!("inhibitor".parse(), Ok(RelationshipType::Inhibitor));
+        assert_eq!("ACTIVATOR".parse(), Ok(RelationshipType::Activator));
+        let unknown = "Unknown".parse::<RelationshipType>().unwrap();
+        assert_eq!(unknown, RelationshipType::Unknown("Unknown".to_string()));
+        assert_eq!(unknown.to_string(), "Unknown");
+        assert_eq!(
+            "Foo".parse::<RelationshipType>(),
+            Err(ParseRelationshipTypeError("Foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn typed_constructor() {
+        let edges = [
+            (1, 2, RelationshipType::Activator),
+            (2, 1, RelationshipType::Inhibitor),
+            (2, 2, RelationshipType::Unknown("Foo".to_string())),
+        ];
+        let relationships = edges
+            .into_iter()
+            .zip(1..)
+            .map(|((from, to, kind), id)| BmaRelationship::new(id, from, to, kind))
+            .collect::<Vec<_>>();
+        assert_eq!(relationships[0], BmaRelationship::new_activator(1, 1, 2));
+        assert_eq!(relationships[1], BmaRelationship::new_inhibitor(2, 2, 1));
+        assert_eq!(relationships[2].id, 3);
+        assert_eq!(
+            relationships[2].r#type,
+            RelationshipType::Unknown("Foo".to_string())
+        );
+    }
+
+    #[test]
+    fn relationship_type_order() {
+        let unknown = |s: &str| RelationshipType::Unknown(s.to_string());
+        let types = BTreeSet::from([
+            unknown("b"),
+            RelationshipType::Inhibitor,
+            unknown("a"),
+            RelationshipType::Activator,
+            RelationshipType::Inhibitor,
+        ]);
+        let expected = vec![
+            RelationshipType::Activator,
+            RelationshipType::Inhibitor,
+            unknown("a"),
+            unknown("b"),
+        ];
+        assert_eq!(types.into_iter().collect::<Vec<_>>(), expected);
+    }
 
     #[test]
     fn variable_getters() {
@@ -203,6 +392,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "bn-conversion")]
     fn relationship_conversions() {
         assert_eq!(
             Monotonicity::try_from(RelationshipType::Activator).unwrap(),
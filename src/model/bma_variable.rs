@@ -1,6 +1,6 @@
 use crate::update_function::{BmaUpdateFunction, FunctionTable, InvalidBmaExpression};
-use crate::utils::is_unique_id;
-use crate::{BmaNetwork, ContextualValidation, ErrorReporter, RelationshipType};
+use crate::utils::{count_ids, take_if_not_blank};
+use crate::{BmaLayout, BmaNetwork, ContextualValidation, ErrorReporter, RelationshipType};
 use BmaVariableError::{
     CannotBuildFunctionTable, ConstantWithRegulators, ConstantWithUpdateFunction,
     UpdateFunctionRegulatorInvalid,
@@ -9,7 +9,8 @@ use RelationshipType::{Activator, Inhibitor};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::RangeInclusive;
 use thiserror::Error;
 
 /// A discrete variable identified by an integer `id`. Each [`BmaVariable`] consists
@@ -21,6 +22,8 @@ use thiserror::Error;
 ///  - Variable `name` can be blank and is not required to be unique.
 ///  - Variable `range` must be a valid range. However, a range that only contains a single
 ///    value is allowed, in which case the variable is considered constant.
+///  - Variable `range` must not have more than [`BmaVariable::DEFAULT_MAX_RANGE_SIZE`] levels
+///    (the limit can be adjusted using [`crate::BmaModel::validate_with_max_range_size`]).
 ///
 /// Note that when `formula` is not specified, the typical interpretation is to assign
 /// such a variable the "default" update function based on its associated relationships
@@ -47,12 +50,33 @@ pub struct BmaVariable {
 }
 
 impl BmaVariable {
+    /// The default maximal number of levels of a variable range that is accepted during
+    /// validation (see [`BmaVariableError::RangeTooLarge`]).
+    pub const DEFAULT_MAX_RANGE_SIZE: u32 = 255;
+
     /// Create a new *boolean* [`BmaVariable`] with the given `name`.
     #[must_use]
     pub fn new_boolean(id: u32, name: &str, formula: Option<BmaUpdateFunction>) -> Self {
         Self::new(id, name, (0, 1), formula)
     }
 
+    /// Create a new *constant* [`BmaVariable`] with the given `name` and `value`.
+    ///
+    /// The variable has range `(value, value)` and a constant update function `value`,
+    /// which is consistent with the expectations on constants checked during validation
+    /// (see [`BmaVariable::has_constant_range`]). If `value` cannot be represented in
+    /// an update function (i.e., it exceeds [`i32::MAX`]), the update function is left empty.
+    ///
+    /// The corresponding layout variable can be created using
+    /// [`crate::BmaLayoutVariable::new_constant`].
+    #[must_use]
+    pub fn new_constant(id: u32, name: &str, value: u32) -> Self {
+        let formula = i32::try_from(value)
+            .ok()
+            .map(BmaUpdateFunction::mk_constant);
+        Self::new(id, name, (value, value), formula)
+    }
+
     /// Create a new [`BmaVariable`] with the given `name` and `range`.
     pub fn new(
         id: u32,
@@ -80,6 +104,25 @@ impl BmaVariable {
         self.range.1
     }
 
+    /// The number of levels this variable can take, i.e., `max_level - min_level + 1`.
+    ///
+    /// If the range is invalid (`min_level > max_level`), the result is `0`. The result is
+    /// a `u64`, such that the full `u32` range (which has `2^32` levels) is also exact.
+    #[must_use]
+    pub fn range_size(&self) -> u64 {
+        self.max_level()
+            .checked_sub(self.min_level())
+            .map_or(0, |diff| u64::from(diff) + 1)
+    }
+
+    /// Iterate over all levels this variable can take (from `min_level` to `max_level`).
+    ///
+    /// If the range is invalid (`min_level > max_level`), the iterator is empty.
+    #[must_use]
+    pub fn levels(&self) -> RangeInclusive<u32> {
+        self.min_level()..=self.max_level()
+    }
+
     #[must_use]
     pub fn formula_string(&self) -> String {
         if let Some(formula) = &self.formula {
@@ -115,12 +158,29 @@ impl BmaVariable {
         self.formula.as_ref().and_then(|it| it.as_ref().ok())
     }
 
+    /// The effective name of this variable, as it should be shown to the user.
+    ///
+    /// A name is *blank* if it is empty or contains only whitespace. The result is the
+    /// (trimmed) name of this variable if it is not blank, otherwise the (trimmed) name of the
+    /// corresponding variable in the `layout` if that one is not blank, and `v{id}` otherwise.
+    #[must_use]
+    pub fn display_name(&self, layout: &BmaLayout) -> String {
+        take_if_not_blank(&self.name)
+            .or_else(|| {
+                layout
+                    .find_variable(self.id)
+                    .and_then(|it| take_if_not_blank(&it.name))
+            })
+            .unwrap_or_else(|| format!("v{}", self.id))
+    }
+
     /// Create a string identifier that contains the variable ID, variable name (if set) and
     /// given level in a human-readable format.
     ///
     /// # Panics
     /// The given `level` must be valid in the range of this variable.
     #[must_use]
+    #[cfg(feature = "bn-conversion")]
     pub(crate) fn mk_level_identifier(&self, level: u32) -> String {
         assert!(level >= self.range.0 && level <= self.range.1);
         if self.name.is_empty() {
@@ -136,10 +196,12 @@ impl BmaVariable {
     }
 }
 
+#[cfg(feature = "bn-conversion")]
 const NOT_IN_VAR_NAME: [char; 11] = ['!', '&', '|', '^', '=', '<', '>', '(', ')', '?', ':'];
 
 /// Make sure a name is safe for use with lib-bdd and lib-param-bn
-fn sanitize_name(name: &str) -> String {
+#[cfg(feature = "bn-conversion")]
+pub(crate) fn sanitize_name(name: &str) -> String {
     name.chars()
         .map(|it| {
             if NOT_IN_VAR_NAME.contains(&it) {
@@ -171,7 +233,15 @@ pub enum BmaVariableError {
     #[error("(Variable id: `{id}`) Range `{range:?}` is invalid; must be an interval")]
     RangeInvalid { id: u32, range: (u32, u32) },
     #[error(
-        "(Variable id: `{id}`) Variable appears to be a constant (`{value}`), but has update function `{expression}`"
+        "(Variable id: `{id}`) Range `{range:?}` is too large; at most `{max_size}` levels allowed"
+    )]
+    RangeTooLarge {
+        id: u32,
+        range: (u32, u32),
+        max_size: u32,
+    },
+    #[error(
+        "(Variable id: `{id}`) Variable appears to be a constant (`{value}`), so its update function must be empty, `0`, or `{value}`, but found `{expression}`"
     )]
     ConstantWithUpdateFunction {
         id: u32,
@@ -210,6 +280,53 @@ pub enum BmaVariableError {
     },
 }
 
+impl BmaVariableError {
+    /// A stable identifier of the error kind (see [`crate::BmaModelError::code`]).
+    ///
+    /// For [`BmaVariableError::UpdateFunctionRegulatorInvalid`], the code also
+    /// distinguishes the type of the regulator issue (e.g. a missing variable).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaVariableError::IdNotUnique { .. } => "variable.id_not_unique",
+            BmaVariableError::RangeInvalid { .. } => "variable.range_invalid",
+            BmaVariableError::RangeTooLarge { .. } => "variable.range_too_large",
+            ConstantWithUpdateFunction { .. } => "variable.constant_with_update_function",
+            ConstantWithRegulators { .. } => "variable.constant_with_regulators",
+            BmaVariableError::UpdateFunctionExpressionInvalid { .. } => {
+                "variable.update_function_expression_invalid"
+            }
+            UpdateFunctionRegulatorInvalid { source, .. } => match source {
+                RegulatorErrorType::MissingVariable => "variable.regulator_missing_variable",
+                RegulatorErrorType::MissingRelationship => {
+                    "variable.regulator_missing_relationship"
+                }
+                RegulatorErrorType::SelfReferenceWithoutLoop => {
+                    "variable.regulator_self_reference_without_loop"
+                }
+                RegulatorErrorType::UnusedRelationship => "variable.regulator_unused_relationship",
+                RegulatorErrorType::BadMonotonicity { .. } => "variable.regulator_bad_monotonicity",
+            },
+            CannotBuildFunctionTable { .. } => "variable.cannot_build_function_table",
+        }
+    }
+
+    /// The ID of the variable that this error concerns.
+    #[must_use]
+    pub fn variable_id(&self) -> u32 {
+        match self {
+            BmaVariableError::IdNotUnique { id }
+            | BmaVariableError::RangeInvalid { id, .. }
+            | BmaVariableError::RangeTooLarge { id, .. }
+            | ConstantWithUpdateFunction { id, .. }
+            | ConstantWithRegulators { id, .. }
+            | BmaVariableError::UpdateFunctionExpressionInvalid { id, .. }
+            | UpdateFunctionRegulatorInvalid { id, .. }
+            | CannotBuildFunctionTable { id, .. } => *id,
+        }
+    }
+}
+
 /// Possible validation error type for [`BmaVariable`] concerning function regulators.
 #[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum RegulatorErrorType {
@@ -217,6 +334,10 @@ pub enum RegulatorErrorType {
     MissingVariable,
     #[error("Variable not declared as regulator")]
     MissingRelationship,
+    /// A special case of [`RegulatorErrorType::MissingRelationship`] where the function
+    /// references its own variable, but the network has no self-loop relationship.
+    #[error("Variable references itself, but there is no self-loop relationship")]
+    SelfReferenceWithoutLoop,
     #[error("Variable does not influence function output")]
     UnusedRelationship,
     #[error("Declared monotonicity is `{declared:?}`, but observed monotonicity is `{observed:?}`")]
@@ -230,6 +351,43 @@ impl ContextualValidation<BmaNetwork> for BmaVariable {
     type Error = BmaVariableError;
 
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, context: &BmaNetwork, reporter: &mut R) {
+        // This is not a validation error; this violates the whole contract of the validation
+        // mechanism and is therefore allowed to fail (instead of returning an error).
+        assert!(
+            context.variables.contains(self),
+            "Precondition violation: validated variable is not part of the `BmaNetwork`."
+        );
+
+        let id_counts = count_ids(&context.variables, |x| x.id);
+        self.validate_all_with_max_range(
+            context,
+            &id_counts,
+            BmaVariable::DEFAULT_MAX_RANGE_SIZE,
+            reporter,
+        );
+    }
+}
+
+impl BmaVariable {
+    /// Check whether the range of this variable has more than `max_size` levels.
+    fn is_range_too_large(&self, max_size: u32) -> bool {
+        let size = u64::from(self.range.1).saturating_sub(u64::from(self.range.0)) + 1;
+        size > u64::from(max_size)
+    }
+
+    /// Internal version of [`ContextualValidation::validate_all`] where the maximal number
+    /// of levels in the variable range can be configured
+    /// (see [`crate::BmaModel::validate_with_max_range_size`]).
+    ///
+    /// The `id_counts` are precomputed counts of all variable IDs in the `context` network
+    /// (see [`count_ids`]), such that ID uniqueness can be checked in constant time.
+    pub(crate) fn validate_all_with_max_range<R: ErrorReporter<BmaVariableError>>(
+        &self,
+        context: &BmaNetwork,
+        id_counts: &HashMap<u32, usize>,
+        max_range_size: u32,
+        reporter: &mut R,
+    ) {
         // Ensure that the variable range is a valid interval (start <= end).
         // Single-value ranges are allowed.
         if self.range.0 > self.range.1 {
@@ -239,14 +397,18 @@ impl ContextualValidation<BmaNetwork> for BmaVariable {
             });
         }
 
-        // Ensure that the variable id is unique within the enclosing BmaNetwork.
-        let Ok(is_unique) = is_unique_id(&context.variables, self, |x| x.id) else {
-            // This is not a validation error; this violates the whole contract of the validation
-            // mechanism and is therefore allowed to fail (instead of returning an error).
-            panic!("Precondition violation: validated variable is not part of the `BmaNetwork`.")
-        };
+        // Ensure that the range is not absurdly large. Such variables would make the function
+        // table (and any other explicit representation) unreasonably large.
+        if self.is_range_too_large(max_range_size) {
+            reporter.report(BmaVariableError::RangeTooLarge {
+                id: self.id,
+                range: self.range,
+                max_size: max_range_size,
+            });
+        }
 
-        if !is_unique {
+        // Ensure that the variable id is unique within the enclosing BmaNetwork.
+        if id_counts.get(&self.id).copied().unwrap_or_default() > 1 {
             reporter.report(BmaVariableError::IdNotUnique { id: self.id });
         }
 
@@ -257,13 +419,18 @@ impl ContextualValidation<BmaNetwork> for BmaVariable {
             });
         }
 
+        // The remaining checks build the function table, which can be expensive.
+        if reporter.is_full() {
+            return;
+        }
+
         let mut regulators = Vec::from_iter(context.get_regulators(self.id, &None));
         regulators.sort_unstable();
 
         if self.has_constant_range() {
             validate_constant_variable_update(self, &regulators, reporter);
         } else {
-            validate_dynamic_variable_update(context, self, &regulators, reporter);
+            validate_dynamic_variable_update(context, self, &regulators, max_range_size, reporter);
         }
     }
 }
@@ -272,6 +439,7 @@ fn validate_dynamic_variable_update<R: ErrorReporter<BmaVariableError>>(
     context: &BmaNetwork,
     variable: &BmaVariable,
     regulators: &[u32],
+    max_range_size: u32,
     reporter: &mut R,
 ) {
     // For non-constant variables, we need to make sure they use valid regulators
@@ -283,11 +451,13 @@ fn validate_dynamic_variable_update<R: ErrorReporter<BmaVariableError>>(
     // 1. All used variables exist and are regulators.
     let mut has_valid_regulators = true;
     if let Some(formula) = variable.try_get_update_function() {
-        let syntactic_regulators = formula.collect_variables();
+        let syntactic_regulators = formula.variables_in_order();
 
         for reg_var in syntactic_regulators {
             let error_type = if context.find_variable(reg_var).is_none() {
                 Some(RegulatorErrorType::MissingVariable)
+            } else if reg_var == variable.id && !regulators.contains(&reg_var) {
+                Some(RegulatorErrorType::SelfReferenceWithoutLoop)
             } else if !regulators.contains(&reg_var) {
                 Some(RegulatorErrorType::MissingRelationship)
             } else {
@@ -320,6 +490,18 @@ fn validate_dynamic_variable_update<R: ErrorReporter<BmaVariableError>>(
         Some(Err(_)) => return,
     };
 
+    // Building the function table is not feasible if the ranges are too large (this
+    // is reported as a separate error).
+    let has_large_range = variable.is_range_too_large(max_range_size)
+        || regulators.iter().any(|id| {
+            context
+                .find_variable(*id)
+                .is_some_and(|it| it.is_range_too_large(max_range_size))
+        });
+    if has_large_range {
+        return;
+    }
+
     // 2. All declared regulations have valid monotonicity and essentiality.
 
     let function_table = context.build_function_table(variable.id);
@@ -332,6 +514,15 @@ fn validate_dynamic_variable_update<R: ErrorReporter<BmaVariableError>>(
         Ok(mut function_table) => {
             let declared_activators = context.get_regulators(variable.id, &Some(Activator));
             let declared_inhibitors = context.get_regulators(variable.id, &Some(Inhibitor));
+            // Relationships of unknown type accept any observed sign, but only if there is
+            // no relationship of a known type from the same regulator.
+            let declared_unknown = context
+                .relationships
+                .iter()
+                .filter(|r| r.to_variable == variable.id)
+                .filter(|r| matches!(r.r#type, RelationshipType::Unknown(_)))
+                .map(|r| r.from_variable)
+                .collect::<HashSet<_>>();
 
             for reg_var in regulators {
                 let regulator = context
@@ -360,7 +551,8 @@ fn validate_dynamic_variable_update<R: ErrorReporter<BmaVariableError>>(
                     if declared_inhibitors.contains(reg_var) {
                         declared.push(Inhibitor);
                     }
-                    if declared != observed {
+                    let only_unknown = declared.is_empty() && declared_unknown.contains(reg_var);
+                    if !only_unknown && declared != observed {
                         reporter.report(UpdateFunctionRegulatorInvalid {
                             id: variable.id,
                             regulator: *reg_var,
@@ -421,7 +613,10 @@ fn validate_constant_variable_update<R: ErrorReporter<BmaVariableError>>(
 ///
 /// The reason why we need a mutable reference to `table` is that we need to sort it. Otherwise,
 /// it is not modified.
-fn infer_relationship_type(table: &mut FunctionTable, regulator: u32) -> Vec<RelationshipType> {
+pub(crate) fn infer_relationship_type(
+    table: &mut FunctionTable,
+    regulator: u32,
+) -> Vec<RelationshipType> {
     // If there is at least one regulator, the table should have at least two entries.
     // If that's not the case, there are no regulators and that means this one is unused.
     if table.len() <= 1 {
@@ -499,10 +694,13 @@ fn compare_two_inputs(
 #[cfg(test)]
 mod tests {
     use crate::BmaVariableError::CannotBuildFunctionTable;
-    use crate::RelationshipType::{Activator, Inhibitor};
+    use crate::RelationshipType::{Activator, Inhibitor, Unknown};
     use crate::model::bma_variable::{BmaVariableError, RegulatorErrorType};
     use crate::update_function::BmaUpdateFunction;
-    use crate::{BmaNetwork, BmaRelationship, BmaVariable, ContextualValidation};
+    use crate::{
+        BmaLayout, BmaLayoutVariable, BmaNetwork, BmaRelationship, BmaVariable,
+        ContextualValidation,
+    };
     use BmaVariableError::{
         ConstantWithRegulators, ConstantWithUpdateFunction, IdNotUnique, RangeInvalid,
         UpdateFunctionRegulatorInvalid,
@@ -525,6 +723,21 @@ mod tests {
         assert_eq!(variable.max_level(), 3);
     }
 
+    #[test]
+    fn range_size_and_levels() {
+        let variable = BmaVariable::new(0, "v", (1, 3), None);
+        assert_eq!(variable.range_size(), 3);
+        assert_eq!(variable.levels().collect::<Vec<_>>(), vec![1, 2, 3]);
+        let variable = BmaVariable::new_constant(0, "c", 4);
+        assert_eq!(variable.range_size(), 1);
+        assert_eq!(variable.levels().collect::<Vec<_>>(), vec![4]);
+        let variable = BmaVariable::new(0, "v", (3, 1), None);
+        assert_eq!(variable.range_size(), 0);
+        assert_eq!(variable.levels().count(), 0);
+        let variable = BmaVariable::new(0, "v", (0, u32::MAX), None);
+        assert_eq!(variable.range_size(), 1u64 << 32);
+    }
+
     #[test]
     fn name_or_default() {
         let variable = BmaVariable {
@@ -539,6 +752,22 @@ mod tests {
         assert_eq!(variable.name, "");
     }
 
+    #[test]
+    fn display_name() {
+        let mut layout = BmaLayout::default();
+        let variable = BmaVariable::new_boolean(3, "  foo ", None);
+        assert_eq!(variable.display_name(&layout), "foo");
+
+        let variable = BmaVariable::new_boolean(3, " \t\n", None);
+        assert_eq!(variable.display_name(&layout), "v3");
+        layout
+            .variables
+            .push(BmaLayoutVariable::new(3, "   ", None));
+        assert_eq!(variable.display_name(&layout), "v3");
+        layout.variables[0].name = " bar".to_string();
+        assert_eq!(variable.display_name(&layout), "bar");
+    }
+
     #[test]
     fn default_serde() {
         let formula = BmaUpdateFunction::try_from("var(0) - var(1)").unwrap();
@@ -600,6 +829,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn range_too_large() {
+        let variable = BmaVariable::new(0, "v1", (0, u32::MAX), None);
+        let network = network_for_variable(&variable);
+
+        let issues = variable.validate(&network).unwrap_err();
+        assert_eq!(
+            issues,
+            vec![BmaVariableError::RangeTooLarge {
+                id: 0,
+                range: (0, u32::MAX),
+                max_size: BmaVariable::DEFAULT_MAX_RANGE_SIZE,
+            }]
+        );
+
+        // The largest allowed range.
+        let variable = BmaVariable::new(0, "v1", (1, 255), None);
+        let network = network_for_variable(&variable);
+        assert!(variable.validate(&network).is_ok());
+    }
+
     /// Two variables with the same ID are not allowed.
     #[test]
     fn duplicate_ids() {
@@ -633,6 +883,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn new_constant() {
+        let variable = BmaVariable::new_constant(3, "c", 2);
+        assert_eq!(variable.range, (2, 2));
+        assert!(variable.has_constant_range());
+        assert_eq!(variable.formula_string(), "2");
+        let network = network_for_variable(&variable);
+        assert!(variable.validate(&network).is_ok());
+
+        let variable = BmaVariable::new_constant(3, "c", u32::MAX);
+        assert_eq!(variable.range, (u32::MAX, u32::MAX));
+        assert!(variable.formula.is_none());
+    }
+
     #[test]
     fn constant_with_update_function() {
         let update = BmaUpdateFunction::try_from("var(0) + var(1)").unwrap();
@@ -648,6 +912,26 @@ mod tests {
                 expression: "(var(0) + var(1))".to_string(),
             }]
         );
+        assert_eq!(
+            issues[0].to_string(),
+            "(Variable id: `0`) Variable appears to be a constant (`3`), so its update function \
+            must be empty, `0`, or `3`, but found `(var(0) + var(1))`"
+        );
+    }
+
+    #[test]
+    fn invalid_expression() {
+        let mut variable = BmaVariable::new(0, "v1", (0, 1), None);
+        variable.formula = Some(BmaUpdateFunction::try_from("var(0) + )"));
+        let network = network_for_variable(&variable);
+
+        let issues = variable.validate(&network).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        let BmaVariableError::UpdateFunctionExpressionInvalid { id: 0, source } = &issues[0] else {
+            unreachable!("Unexpected error: {}", issues[0]);
+        };
+        assert_eq!(source.position(), Some(9));
+        assert!(issues[0].to_string().contains("at position `9`"));
     }
 
     #[test]
@@ -671,11 +955,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unknown_regulators_in_expression_order() {
+        let update = BmaUpdateFunction::try_from("var(7) + var(3) - var(5) * var(3)").unwrap();
+        let variable = BmaVariable::new(0, "v1", (0, 3), Some(update));
+        let network = network_for_variable(&variable);
+
+        let regulators = variable
+            .validate(&network)
+            .unwrap_err()
+            .into_iter()
+            .map(|issue| match issue {
+                UpdateFunctionRegulatorInvalid { regulator, .. } => regulator,
+                issue => panic!("Unexpected issue: {issue}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(regulators, vec![7, 3, 5]);
+    }
+
     #[test]
     fn missing_relationship() {
+        let update = BmaUpdateFunction::try_from("var(1)").unwrap();
+        let variable = BmaVariable::new(0, "v1", (0, 3), Some(update));
+        let mut network = network_for_variable(&variable);
+        network
+            .variables
+            .push(BmaVariable::new(1, "v2", (0, 3), None));
+
+        let issues = variable.validate(&network).unwrap_err();
+        assert_eq!(
+            issues,
+            vec![UpdateFunctionRegulatorInvalid {
+                id: 0,
+                regulator: 1,
+                expression: "var(1)".to_string(),
+                source: RegulatorErrorType::MissingRelationship,
+            },]
+        );
+    }
+
+    #[test]
+    fn self_reference_without_loop() {
         let update = BmaUpdateFunction::try_from("var(0)").unwrap();
         let variable = BmaVariable::new(0, "v1", (0, 3), Some(update));
-        let network = network_for_variable(&variable);
+        let mut network = network_for_variable(&variable);
 
         let issues = variable.validate(&network).unwrap_err();
         assert_eq!(
@@ -684,9 +1007,18 @@ mod tests {
                 id: 0,
                 regulator: 0,
                 expression: "var(0)".to_string(),
-                source: RegulatorErrorType::MissingRelationship,
+                source: RegulatorErrorType::SelfReferenceWithoutLoop,
             },]
         );
+        assert_eq!(
+            issues[0].code(),
+            "variable.regulator_self_reference_without_loop"
+        );
+
+        network
+            .relationships
+            .push(BmaRelationship::new_activator(0, 0, 0));
+        variable.validate(&network).unwrap();
     }
 
     #[test]
@@ -772,6 +1104,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unknown_relationship_accepts_any_sign() {
+        let update = BmaUpdateFunction::try_from("var(0) + var(1)").unwrap();
+        let variable = BmaVariable::new(0, "v1", (0, 3), Some(update));
+        let mut network = network_for_variable(&variable);
+        network
+            .variables
+            .push(BmaVariable::new(1, "v2", (0, 3), None));
+        let mut relationship = BmaRelationship::new_activator(0, 0, 0);
+        relationship.r#type = Unknown("Unknown".to_string());
+        network.relationships.push(relationship);
+        network
+            .relationships
+            .push(BmaRelationship::new_activator(1, 1, 0));
+        assert!(variable.validate(&network).is_ok());
+
+        // Unknown relationships are still reported if they are unused.
+        let update = BmaUpdateFunction::try_from("var(1)").unwrap();
+        let variable = BmaVariable::new(0, "v1", (0, 3), Some(update));
+        network.variables[0] = variable.clone();
+        let issues = variable.validate(&network).unwrap_err();
+        assert_eq!(
+            issues,
+            vec![UpdateFunctionRegulatorInvalid {
+                id: 0,
+                regulator: 0,
+                expression: "var(1)".to_string(),
+                source: RegulatorErrorType::UnusedRelationship,
+            }]
+        );
+    }
+
+    #[test]
+    fn unknown_relationship_with_known_sign() {
+        // The regulator has both an unknown and an inhibiting relationship, so the
+        // observed sign must still match the inhibition.
+        let update = BmaUpdateFunction::try_from("var(1)").unwrap();
+        let variable = BmaVariable::new(0, "v1", (0, 3), Some(update));
+        let mut network = network_for_variable(&variable);
+        network
+            .variables
+            .push(BmaVariable::new(1, "v2", (0, 3), None));
+        let mut relationship = BmaRelationship::new_activator(0, 1, 0);
+        relationship.r#type = Unknown("Unknown".to_string());
+        network.relationships.push(relationship);
+        network
+            .relationships
+            .push(BmaRelationship::new_inhibitor(1, 1, 0));
+        let issues = variable.validate(&network).unwrap_err();
+        assert_eq!(
+            issues,
+            vec![UpdateFunctionRegulatorInvalid {
+                id: 0,
+                regulator: 1,
+                expression: "var(1)".to_string(),
+                source: RegulatorErrorType::BadMonotonicity {
+                    declared: vec![Inhibitor],
+                    observed: vec![Activator],
+                },
+            }]
+        );
+
+        // With an activating relationship instead, the variable is valid.
+        network.relationships[1] = BmaRelationship::new_activator(1, 1, 0);
+        assert!(variable.validate(&network).is_ok());
+    }
+
     #[test]
     fn dual_monotonicity() {
         // Basically an XOR on integer domains:
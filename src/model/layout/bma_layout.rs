@@ -1,10 +1,15 @@
+use crate::utils::count_ids;
 use crate::{
     BmaLayoutContainer, BmaLayoutContainerError, BmaLayoutVariable, BmaLayoutVariableError,
     BmaModel, ContextualValidation, ErrorReporter,
 };
+use anyhow::anyhow;
+use num_traits::ToPrimitive;
 use rust_decimal::Decimal;
+use rust_decimal::RoundingStrategy::MidpointAwayFromZero;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
+use std::collections::BTreeSet;
 use thiserror::Error;
 
 /// A layout describing positions and types of variables and containers.
@@ -19,6 +24,15 @@ pub struct BmaLayout {
     pub description: String,
     pub zoom_level: Option<Decimal>,
     pub pan: Option<(Decimal, Decimal)>,
+    /// IDs of layout variables whose imported position or angle was not a finite number
+    /// (such values are replaced by `0` during import). These are reported during validation
+    /// as [`BmaLayoutVariableError::NonFiniteCoordinate`].
+    #[serde(skip)]
+    pub(crate) non_finite_variables: BTreeSet<u32>,
+    /// Same as `non_finite_variables`, but for container positions (reported as
+    /// [`BmaLayoutContainerError::NonFiniteCoordinate`]).
+    #[serde(skip)]
+    pub(crate) non_finite_containers: BTreeSet<u32>,
 }
 
 impl BmaLayout {
@@ -33,6 +47,151 @@ impl BmaLayout {
     pub fn find_container(&self, id: u32) -> Option<&BmaLayoutContainer> {
         self.containers.iter().find(|v| v.id == id)
     }
+
+    /// Move all variables and containers by the given offset (in canvas coordinates).
+    ///
+    /// Since container positions are given in grid cells, containers are moved by
+    /// `dx / CELL_WIDTH` and `dy / CELL_HEIGHT` (see [`BmaLayoutContainer::CELL_WIDTH`]).
+    /// To keep containers aligned with the grid, use multiples of the cell dimensions.
+    ///
+    /// The operation fails if the offset is not finite, or if some of the moved positions
+    /// cannot be represented. In such case, the layout is not modified.
+    pub fn translate(&mut self, dx: f64, dy: f64) -> anyhow::Result<()> {
+        if !dx.is_finite() || !dy.is_finite() {
+            return Err(anyhow!(
+                "Translation offset must be finite, got `({dx}, {dy})`"
+            ));
+        }
+        let offset = |value: f64| {
+            Decimal::try_from(value)
+                .map_err(|_| anyhow!("Translation offset `{value}` cannot be represented"))
+        };
+        let (dx, dy) = (offset(dx)?, offset(dy)?);
+        let overflow = || anyhow!("Translated layout coordinates are out of range");
+        let translate = |(x, y): (Decimal, Decimal), (dx, dy): (Decimal, Decimal)| {
+            Ok::<_, anyhow::Error>((
+                x.checked_add(dx).ok_or_else(overflow)?,
+                y.checked_add(dy).ok_or_else(overflow)?,
+            ))
+        };
+
+        let variables = self
+            .variables
+            .iter()
+            .map(|var| translate(var.position, (dx, dy)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let cell_offset = (
+            dx / BmaLayoutContainer::CELL_WIDTH,
+            dy / BmaLayoutContainer::CELL_HEIGHT,
+        );
+        let containers = self
+            .containers
+            .iter()
+            .map(|container| translate(container.position, cell_offset))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        for (var, position) in self.variables.iter_mut().zip(variables) {
+            var.position = position;
+        }
+        for (container, position) in self.containers.iter_mut().zip(containers) {
+            container.position = position;
+        }
+        Ok(())
+    }
+
+    /// Scale the positions of all variables and containers (relative to the origin),
+    /// as well as the container sizes, by the given `factor`.
+    ///
+    /// Container sizes are rounded to the nearest integer (half away from zero), but they
+    /// are always at least `1`.
+    ///
+    /// The operation fails if the `factor` is not a finite positive number, or if some of
+    /// the scaled values cannot be represented. In such case, the layout is not modified.
+    pub fn scale(&mut self, factor: f64) -> anyhow::Result<()> {
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(anyhow!(
+                "Scaling factor must be finite and positive, got `{factor}`"
+            ));
+        }
+        let factor = Decimal::try_from(factor)
+            .map_err(|_| anyhow!("Scaling factor `{factor}` cannot be represented"))?;
+        let overflow = || anyhow!("Scaled layout coordinates are out of range");
+        let scale = |(x, y): (Decimal, Decimal)| -> anyhow::Result<(Decimal, Decimal)> {
+            Ok((
+                x.checked_mul(factor).ok_or_else(overflow)?,
+                y.checked_mul(factor).ok_or_else(overflow)?,
+            ))
+        };
+
+        let variables = self
+            .variables
+            .iter()
+            .map(|var| scale(var.position))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let containers = self
+            .containers
+            .iter()
+            .map(|container| {
+                let size = Decimal::from(container.effective_size())
+                    .checked_mul(factor)
+                    .ok_or_else(overflow)?
+                    .round_dp_with_strategy(0, MidpointAwayFromZero)
+                    .to_u32()
+                    .ok_or_else(|| {
+                        anyhow!("Scaled size of container `{}` is too large", container.id)
+                    })?;
+                Ok((scale(container.position)?, size.max(1)))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        for (var, position) in self.variables.iter_mut().zip(variables) {
+            var.position = position;
+        }
+        for (container, (position, size)) in self.containers.iter_mut().zip(containers) {
+            container.position = position;
+            container.size = size;
+        }
+        Ok(())
+    }
+
+    /// Assign each variable to the container whose area contains the variable position
+    /// (see [`BmaLayoutContainer::contains_position`]). Variables outside of all containers
+    /// are not assigned to any container.
+    ///
+    /// If the position lies within multiple (overlapping) containers, the container with
+    /// the smallest area is used (if there are still multiple candidates, the one with
+    /// the smallest `id` is used). Unspecified container sizes are treated as `1` (see
+    /// [`BmaLayoutContainer::effective_size`]).
+    pub fn assign_containers_by_position(&mut self) {
+        for var in &mut self.variables {
+            var.container_id = self
+                .containers
+                .iter()
+                .filter(|c| c.contains_position(var.position))
+                .min_by_key(|c| (c.effective_size(), c.id))
+                .map(|c| c.id);
+        }
+    }
+
+    /// IDs of all [`BmaLayoutVariable`] objects that belong to the given container.
+    #[must_use]
+    pub fn container_members(&self, container_id: u32) -> Vec<u32> {
+        self.variables
+            .iter()
+            .filter(|v| v.container_id == Some(container_id))
+            .map(|v| v.id)
+            .collect()
+    }
+
+    /// IDs of all [`BmaLayoutVariable`] objects that do not belong to any container.
+    #[must_use]
+    pub fn variables_without_container(&self) -> Vec<u32> {
+        self.variables
+            .iter()
+            .filter(|v| v.container_id.is_none())
+            .map(|v| v.id)
+            .collect()
+    }
 }
 
 /// Possible validation errors for [`BmaLayout`].
@@ -44,16 +203,51 @@ pub enum BmaLayoutError {
     Container(#[from] BmaLayoutContainerError),
 }
 
+impl BmaLayoutError {
+    /// A stable identifier of the error kind (see [`crate::BmaModelError::code`]).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaLayoutError::Variable(e) => e.code(),
+            BmaLayoutError::Container(e) => e.code(),
+        }
+    }
+}
+
 impl ContextualValidation<BmaModel> for BmaLayout {
     type Error = BmaLayoutError;
 
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, context: &BmaModel, reporter: &mut R) {
+        let variable_ids = count_ids(&self.variables, |x| x.id);
         for var in &self.variables {
-            var.validate_all(context, &mut reporter.wrap());
+            if reporter.is_full() {
+                return;
+            }
+            var.validate_all_with_id_counts(context, &variable_ids, &mut reporter.wrap());
         }
 
+        let container_ids = count_ids(&self.containers, |x| x.id);
         for container in &self.containers {
-            container.validate_all(self, &mut reporter.wrap());
+            if reporter.is_full() {
+                return;
+            }
+            container.validate_all_with_id_counts(self, &container_ids, &mut reporter.wrap());
+        }
+    }
+}
+
+impl BmaLayout {
+    /// Strict-only checks of the layout (see [`BmaModel::validate_strict`]).
+    pub(crate) fn validate_strict_all<R: ErrorReporter<BmaLayoutError>>(
+        &self,
+        context: &BmaModel,
+        reporter: &mut R,
+    ) {
+        for container in &self.containers {
+            container.validate_strict_all(&mut reporter.wrap());
+        }
+        for var in &self.variables {
+            var.validate_strict_all(context, &mut reporter.wrap());
         }
     }
 }
@@ -61,8 +255,12 @@ impl ContextualValidation<BmaModel> for BmaLayout {
 #[cfg(test)]
 mod tests {
     use crate::model::tests::{simple_layout, simple_network};
-    use crate::{BmaLayout, BmaModel, BmaNetwork, ContextualValidation};
-    use std::collections::HashMap;
+    use crate::{
+        BmaLayout, BmaLayoutContainer, BmaLayoutContainerError, BmaLayoutError, BmaLayoutVariable,
+        BmaLayoutVariableError, BmaModel, BmaNetwork, BmaVariable, ContextualValidation,
+    };
+    use rust_decimal::Decimal;
+    use std::collections::BTreeMap;
 
     #[test]
     fn default_layout_is_valid() {
@@ -70,7 +268,7 @@ mod tests {
         let model = BmaModel {
             network: BmaNetwork::default(),
             layout: layout.clone(),
-            metadata: HashMap::default(),
+            metadata: BTreeMap::default(),
         };
         assert!(layout.validate(&model).is_ok());
     }
@@ -82,11 +280,146 @@ mod tests {
         let model = BmaModel {
             network,
             layout: layout.clone(),
-            metadata: HashMap::default(),
+            metadata: BTreeMap::default(),
         };
         assert!(layout.validate(&model).is_ok());
     }
 
+    #[test]
+    fn duplicate_variable_ids() {
+        let mut model = BmaModel {
+            network: simple_network(),
+            layout: simple_layout(),
+            metadata: BTreeMap::default(),
+        };
+        // The tool sometimes exports the same layout entry twice.
+        let duplicate = model.layout.variables[0].clone();
+        model.layout.variables.push(duplicate);
+        let issues = model.layout.validate(&model).unwrap_err();
+        let expected = BmaLayoutError::Variable(BmaLayoutVariableError::IdNotUnique { id: 0 });
+        assert_eq!(issues, vec![expected.clone(), expected]);
+        assert_eq!(issues[0].code(), "layout_variable.id_not_unique");
+    }
+
+    #[test]
+    fn validate_many_layout_items() {
+        // 10 000 layout variables and containers, where the last one of each reuses
+        // the ID of the first one.
+        let variables = (0..10_000)
+            .map(|id| BmaVariable::new_boolean(id, &format!("v_{id}"), None))
+            .collect();
+        let layout = BmaLayout {
+            variables: (0..10_000u32)
+                .map(|i| BmaLayoutVariable::new(i % 9_999, &format!("v_{i}"), None))
+                .collect(),
+            containers: (0..10_000u32)
+                .map(|i| BmaLayoutContainer::new(i % 9_999, &format!("c_{i}")))
+                .collect(),
+            ..Default::default()
+        };
+        let model = BmaModel {
+            network: BmaNetwork::new(variables, vec![]),
+            layout,
+            metadata: BTreeMap::default(),
+        };
+        let issues = model.layout.validate(&model).unwrap_err();
+        let variable = BmaLayoutError::Variable(BmaLayoutVariableError::IdNotUnique { id: 0 });
+        let container = BmaLayoutError::Container(BmaLayoutContainerError::IdNotUnique { id: 0 });
+        assert_eq!(
+            issues,
+            vec![variable.clone(), variable, container.clone(), container]
+        );
+    }
+
+    #[test]
+    fn container_members() {
+        let layout = simple_layout();
+        assert_eq!(layout.container_members(13), vec![3]);
+        assert!(layout.container_members(0).is_empty());
+        assert_eq!(layout.variables_without_container(), vec![0]);
+    }
+
+    #[test]
+    fn translate_and_scale() {
+        let mut layout = simple_layout();
+        layout.variables[0].position = (Decimal::from(100), Decimal::from(50));
+        layout.containers[0].position = (Decimal::from(1), Decimal::from(2));
+        layout.containers[0].size = 3;
+
+        layout.translate(500.0, -280.0).unwrap();
+        assert_eq!(
+            layout.variables[0].position,
+            (Decimal::from(600), Decimal::from(-230))
+        );
+        assert_eq!(
+            layout.containers[0].position,
+            (Decimal::from(3), Decimal::from(1))
+        );
+
+        layout.scale(0.5).unwrap();
+        assert_eq!(
+            layout.variables[0].position,
+            (Decimal::from(300), Decimal::from(-115))
+        );
+        assert_eq!(
+            layout.containers[0].position,
+            (Decimal::new(15, 1), Decimal::new(5, 1))
+        );
+        // 1.5 is rounded to 2.
+        assert_eq!(layout.containers[0].size, 2);
+
+        layout.scale(0.1).unwrap();
+        assert_eq!(layout.containers[0].size, 1);
+
+        let original = layout.clone();
+        for factor in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+            assert!(layout.scale(factor).is_err(), "{factor}");
+        }
+        layout.containers[0].size = u32::MAX;
+        assert!(layout.scale(2.0).is_err());
+        layout.containers[0].size = original.containers[0].size;
+        assert_eq!(layout, original);
+
+        for (dx, dy) in [(f64::NAN, 0.0), (0.0, f64::NEG_INFINITY), (1e300, 0.0)] {
+            assert!(layout.translate(dx, dy).is_err(), "({dx}, {dy})");
+        }
+        layout.variables[1].position.0 = Decimal::MAX;
+        assert!(layout.translate(1.0, 0.0).is_err());
+        layout.variables[1].position.0 = original.variables[1].position.0;
+        assert_eq!(layout, original);
+    }
+
+    #[test]
+    fn assign_containers_by_position() {
+        let mut layout = simple_layout();
+        let mut big = BmaLayoutContainer::new(5, "Big");
+        big.size = 4;
+        let mut small = BmaLayoutContainer::new(7, "Small");
+        small.position = (Decimal::from(1), Decimal::from(1));
+        layout.containers = vec![big, small];
+
+        // Inside both containers, outside of all containers, and only inside the big one.
+        layout.variables[0].position = (Decimal::from(300), Decimal::from(300));
+        layout.variables[1].position = (Decimal::from(-10), Decimal::from(10));
+        layout
+            .variables
+            .push(BmaLayoutVariable::new(4, "c", Some(7)));
+        layout.variables[2].position = (Decimal::from(10), Decimal::from(10));
+
+        layout.assign_containers_by_position();
+        assert_eq!(layout.variables[0].container_id, Some(7));
+        assert_eq!(layout.variables[1].container_id, None);
+        assert_eq!(layout.variables[2].container_id, Some(5));
+
+        // A container with unspecified size (`0`) is as large as a container of size `1`.
+        let mut unspecified = BmaLayoutContainer::new(8, "Unspecified");
+        unspecified.size = 0;
+        unspecified.position = (Decimal::from(1), Decimal::from(1));
+        layout.containers.push(unspecified);
+        layout.assign_containers_by_position();
+        assert_eq!(layout.variables[0].container_id, Some(7));
+    }
+
     #[test]
     fn description_empty() {
         let layout = BmaLayout {
@@ -96,7 +429,7 @@ mod tests {
         let model = BmaModel {
             network: simple_network(),
             layout: layout.clone(),
-            metadata: HashMap::default(),
+            metadata: BTreeMap::default(),
         };
         assert!(layout.validate(&model).is_ok());
     }
@@ -1,7 +1,9 @@
-use crate::utils::is_unique_id;
+use crate::utils::count_ids;
 use crate::{BmaLayout, ContextualValidation, ErrorReporter};
 use rust_decimal::Decimal;
+use rust_decimal::dec;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use thiserror::Error;
 
 /// Layout information about a container.
@@ -9,6 +11,13 @@ use thiserror::Error;
 /// Expected invariants (checked during validation):
 ///  - The `id` must be unique within the containers of this [`BmaLayout`].
 ///
+/// A `size` of `0` indicates that the size was not specified (some older files omit it).
+/// Such container is treated as having size `1` (see [`BmaLayoutContainer::effective_size`]).
+///
+/// Note that unlike variable positions, the container `position` and `size` are given in
+/// grid cells, not in canvas coordinates. A single grid cell has dimensions
+/// [`BmaLayoutContainer::CELL_WIDTH`] and [`BmaLayoutContainer::CELL_HEIGHT`].
+///
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BmaLayoutContainer {
     pub id: u32,
@@ -29,6 +38,11 @@ impl Default for BmaLayoutContainer {
 }
 
 impl BmaLayoutContainer {
+    /// Width of a single grid cell in canvas coordinates (as used by the BMA tool).
+    pub const CELL_WIDTH: Decimal = dec!(250);
+    /// Height of a single grid cell in canvas coordinates (as used by the BMA tool).
+    pub const CELL_HEIGHT: Decimal = dec!(280);
+
     /// Create a new container using the given `id` and `name`. Remaining values use
     /// default values (size = 1, position = (0,0)).
     #[must_use]
@@ -39,34 +53,112 @@ impl BmaLayoutContainer {
             ..Default::default()
         }
     }
+
+    /// The size of this container, where an unspecified size (`0`) is replaced
+    /// with the default size `1`.
+    #[must_use]
+    pub fn effective_size(&self) -> u32 {
+        self.size.max(1)
+    }
+
+    /// Check whether the given `position` (in canvas coordinates) lies within the area of
+    /// this container. The area includes its top/left border, but not the bottom/right border.
+    #[must_use]
+    pub fn contains_position(&self, position: (Decimal, Decimal)) -> bool {
+        let size = Decimal::from(self.effective_size());
+        let (x, y) = position;
+        let (min_x, min_y) = (
+            self.position.0 * Self::CELL_WIDTH,
+            self.position.1 * Self::CELL_HEIGHT,
+        );
+        let (max_x, max_y) = (
+            (self.position.0 + size) * Self::CELL_WIDTH,
+            (self.position.1 + size) * Self::CELL_HEIGHT,
+        );
+        min_x <= x && x < max_x && min_y <= y && y < max_y
+    }
 }
 
 #[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum BmaLayoutContainerError {
     #[error("(Container: `{id}`) Id must be unique within `BmaLayout`")]
     IdNotUnique { id: u32 },
+    #[error("(Container: `{id}`) Size is missing or zero; using `1` instead")]
+    DefaultedSize { id: u32 },
+    #[error("(Container: `{id}`) Position is not a finite number")]
+    NonFiniteCoordinate { id: u32 },
+}
+
+impl BmaLayoutContainerError {
+    /// A stable identifier of the error kind (see [`crate::BmaModelError::code`]).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaLayoutContainerError::IdNotUnique { .. } => "layout_container.id_not_unique",
+            BmaLayoutContainerError::DefaultedSize { .. } => "layout_container.defaulted_size",
+            BmaLayoutContainerError::NonFiniteCoordinate { .. } => {
+                "layout_container.non_finite_coordinate"
+            }
+        }
+    }
 }
 
 impl ContextualValidation<BmaLayout> for BmaLayoutContainer {
     type Error = BmaLayoutContainerError;
 
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, context: &BmaLayout, reporter: &mut R) {
-        // Ensure that the container id is unique within the enclosing BmaLayout.
-        let Ok(is_unique) = is_unique_id(&context.containers, self, |x| x.id) else {
-            // This is not a validation error; this violates the whole contract of the validation
-            // mechanism and is therefore allowed to fail (instead of returning an error).
-            panic!("Precondition violation: validated container is not part of the `BmaLayout`.")
-        };
+        // This is not a validation error; this violates the whole contract of the validation
+        // mechanism and is therefore allowed to fail (instead of returning an error).
+        assert!(
+            context.containers.contains(self),
+            "Precondition violation: validated container is not part of the `BmaLayout`."
+        );
 
-        if !is_unique {
+        let id_counts = count_ids(&context.containers, |x| x.id);
+        self.validate_all_with_id_counts(context, &id_counts, reporter);
+    }
+}
+
+impl BmaLayoutContainer {
+    /// Internal version of [`ContextualValidation::validate_all`] which uses precomputed
+    /// `id_counts` of all containers in the `context` layout (see [`count_ids`]). This way,
+    /// the uniqueness of container IDs can be checked in linear time when validating
+    /// the whole layout.
+    pub(crate) fn validate_all_with_id_counts<R: ErrorReporter<BmaLayoutContainerError>>(
+        &self,
+        context: &BmaLayout,
+        id_counts: &HashMap<u32, usize>,
+        reporter: &mut R,
+    ) {
+        // Ensure that the container id is unique within the enclosing BmaLayout.
+        if id_counts.get(&self.id).copied().unwrap_or_default() > 1 {
             reporter.report(BmaLayoutContainerError::IdNotUnique { id: self.id });
         }
+
+        if context.non_finite_containers.contains(&self.id) {
+            reporter.report(BmaLayoutContainerError::NonFiniteCoordinate { id: self.id });
+        }
+    }
+}
+
+impl BmaLayoutContainer {
+    /// Report issues that are tolerated by regular validation (see
+    /// [`crate::BmaModel::validate_strict`]).
+    pub(crate) fn validate_strict_all<R: ErrorReporter<BmaLayoutContainerError>>(
+        &self,
+        reporter: &mut R,
+    ) {
+        if self.size == 0 {
+            reporter.report(BmaLayoutContainerError::DefaultedSize { id: self.id });
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::validation::VecReporter;
     use crate::{BmaLayout, BmaLayoutContainer, BmaLayoutContainerError, ContextualValidation};
+    use rust_decimal::Decimal;
 
     fn make_layout_for_container(container: &BmaLayoutContainer) -> BmaLayout {
         BmaLayout {
@@ -82,6 +174,40 @@ mod tests {
         assert!(container.validate(&layout).is_ok());
     }
 
+    #[test]
+    fn contains_position() {
+        let container = BmaLayoutContainer {
+            size: 2,
+            position: (Decimal::from(1), Decimal::from(-1)),
+            ..Default::default()
+        };
+        let d = |x: i32, y: i32| (Decimal::from(x), Decimal::from(y));
+        assert!(container.contains_position(d(250, -280)));
+        assert!(container.contains_position(d(749, 279)));
+        assert!(!container.contains_position(d(750, 0)));
+        assert!(!container.contains_position(d(300, 280)));
+        assert!(!container.contains_position(d(249, 0)));
+    }
+
+    #[test]
+    fn zero_size() {
+        let container = BmaLayoutContainer {
+            size: 0,
+            ..Default::default()
+        };
+        let layout = make_layout_for_container(&container);
+        assert!(container.validate(&layout).is_ok());
+        assert_eq!(container.effective_size(), 1);
+        assert!(container.contains_position((Decimal::from(10), Decimal::from(10))));
+
+        let mut issues = VecReporter::new();
+        container.validate_strict_all(&mut issues);
+        assert_eq!(
+            issues.into_result(),
+            Err(vec![BmaLayoutContainerError::DefaultedSize { id: 0 }])
+        );
+    }
+
     #[test]
     fn blank_name() {
         let container = BmaLayoutContainer {
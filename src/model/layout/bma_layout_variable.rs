@@ -1,8 +1,9 @@
-use crate::utils::is_unique_id;
+use crate::utils::count_ids;
 use crate::{BmaModel, ContextualValidation, ErrorReporter};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::skip_serializing_none;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
@@ -15,9 +16,13 @@ use thiserror::Error;
 ///  - If `description` is set, it must not be empty.
 ///
 /// Note that variable `name` is also stored in [`crate::BmaVariable`]. Typically, these values
-/// are the same, but this is not a verified invariant (i.e., in theory, you could use one name
-/// for the variable, and another name for its layout counterpart).
+/// are the same, but this is only verified by [`crate::BmaModel::validate_strict`] (i.e., in
+/// theory, you could use one name for the variable, and another name for its layout counterpart).
 ///
+/// The `position` and `angle` are stored as [`Decimal`] values, so they are always finite.
+/// Numbers that cannot be represented are replaced by `0` during import. If the imported
+/// value was not finite (e.g. `NaN` in an XML file), the variable is reported during
+/// validation as [`BmaLayoutVariableError::NonFiniteCoordinate`].
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct BmaLayoutVariable {
@@ -45,9 +50,23 @@ impl BmaLayoutVariable {
             ..Default::default()
         }
     }
+
+    /// Same as [`BmaLayoutVariable::new`], but the variable type is set to
+    /// [`VariableType::Constant`] (see [`crate::BmaVariable::new_constant`]).
+    #[must_use]
+    pub fn new_constant(id: u32, name: &str, container_id: Option<u32>) -> Self {
+        BmaLayoutVariable {
+            r#type: VariableType::Constant,
+            ..Self::new(id, name, container_id)
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// The type of [`BmaLayoutVariable`], which determines how the variable is displayed.
+///
+/// Types are ordered in the order of declaration, with unknown types last (ordered by
+/// their string value).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
 pub enum VariableType {
     #[default]
     Default,
@@ -114,12 +133,69 @@ pub enum BmaLayoutVariableError {
         r#type: VariableType,
         message: String,
     },
+    #[error(
+        "(Layout var.: `{id}`) Name `{name}` does not match name `{network_name}` in `BmaNetwork`"
+    )]
+    NameMismatch {
+        id: u32,
+        name: String,
+        network_name: String,
+    },
+    #[error("(Layout var.: `{id}`) Position or angle is not a finite number")]
+    NonFiniteCoordinate { id: u32 },
+}
+
+impl BmaLayoutVariableError {
+    /// A stable identifier of the error kind (see [`crate::BmaModelError::code`]).
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            BmaLayoutVariableError::IdNotUnique { .. } => "layout_variable.id_not_unique",
+            BmaLayoutVariableError::VariableNotFound { .. } => "layout_variable.variable_not_found",
+            BmaLayoutVariableError::ContainerNotFound { .. } => {
+                "layout_variable.container_not_found"
+            }
+            BmaLayoutVariableError::UnknownVariableType { .. } => {
+                "layout_variable.unknown_variable_type"
+            }
+            BmaLayoutVariableError::InvalidVariableType { .. } => {
+                "layout_variable.invalid_variable_type"
+            }
+            BmaLayoutVariableError::NameMismatch { .. } => "layout_variable.name_mismatch",
+            BmaLayoutVariableError::NonFiniteCoordinate { .. } => {
+                "layout_variable.non_finite_coordinate"
+            }
+        }
+    }
 }
 
 impl ContextualValidation<BmaModel> for BmaLayoutVariable {
     type Error = BmaLayoutVariableError;
 
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, context: &BmaModel, reporter: &mut R) {
+        // This is not a validation error; this violates the whole contract of the validation
+        // mechanism and is therefore allowed to fail (instead of returning an error).
+        assert!(
+            context.layout.variables.contains(self),
+            "Precondition violation: validated variable is not part of the `BmaLayout`."
+        );
+
+        let id_counts = count_ids(&context.layout.variables, |x| x.id);
+        self.validate_all_with_id_counts(context, &id_counts, reporter);
+    }
+}
+
+impl BmaLayoutVariable {
+    /// Internal version of [`ContextualValidation::validate_all`] which uses precomputed
+    /// `id_counts` of all variables in the `context` layout (see [`count_ids`]). This way,
+    /// the uniqueness of variable IDs can be checked in linear time when validating
+    /// the whole layout.
+    pub(crate) fn validate_all_with_id_counts<R: ErrorReporter<BmaLayoutVariableError>>(
+        &self,
+        context: &BmaModel,
+        id_counts: &HashMap<u32, usize>,
+        reporter: &mut R,
+    ) {
         if let Some(bma_var) = context.network.find_variable(self.id) {
             // Ensure that constant variables have the correct type.
             let is_const = self.r#type == VariableType::Constant;
@@ -159,16 +235,14 @@ impl ContextualValidation<BmaModel> for BmaLayoutVariable {
         }
 
         // Ensure the item has a unique ID.
-        let Ok(is_unique) = is_unique_id(&context.layout.variables, self, |x| x.id) else {
-            // This is not a validation error; this violates the whole contract of the validation
-            // mechanism and is therefore allowed to fail (instead of returning an error).
-            panic!("Precondition violation: validated variable is not part of the `BmaLayout`.")
-        };
-
-        if !is_unique {
+        if id_counts.get(&self.id).copied().unwrap_or_default() > 1 {
             reporter.report(BmaLayoutVariableError::IdNotUnique { id: self.id });
         }
 
+        if context.layout.non_finite_variables.contains(&self.id) {
+            reporter.report(BmaLayoutVariableError::NonFiniteCoordinate { id: self.id });
+        }
+
         if let VariableType::Unknown(value) = &self.r#type {
             reporter.report(BmaLayoutVariableError::UnknownVariableType {
                 id: self.id,
@@ -178,11 +252,31 @@ impl ContextualValidation<BmaModel> for BmaLayoutVariable {
     }
 }
 
+impl BmaLayoutVariable {
+    /// Checks that are only performed by [`BmaModel::validate_strict`]: The name of the layout
+    /// variable must match the name of the corresponding [`crate::BmaVariable`].
+    pub(crate) fn validate_strict_all<R: ErrorReporter<BmaLayoutVariableError>>(
+        &self,
+        context: &BmaModel,
+        reporter: &mut R,
+    ) {
+        if let Some(bma_var) = context.network.find_variable(self.id)
+            && bma_var.name != self.name
+        {
+            reporter.report(BmaLayoutVariableError::NameMismatch {
+                id: self.id,
+                name: self.name.clone(),
+                network_name: bma_var.name.clone(),
+            });
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{BmaLayout, BmaNetwork, BmaVariable};
-    use std::collections::HashMap;
+    use std::collections::BTreeMap;
 
     fn make_model_for_variable(l_var: &BmaLayoutVariable) -> BmaModel {
         let n_var = BmaVariable {
@@ -199,10 +293,36 @@ mod tests {
         BmaModel {
             network,
             layout,
-            metadata: HashMap::default(),
+            metadata: BTreeMap::default(),
         }
     }
 
+    #[test]
+    fn variable_type_order() {
+        let mut types = [
+            VariableType::from("x"),
+            VariableType::MembraneReceptor,
+            VariableType::Constant,
+            VariableType::Default,
+        ];
+        types.sort();
+        let names = types.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(names, ["Default", "Constant", "MembraneReceptor", "x"]);
+    }
+
+    #[test]
+    fn new_constant_variable() {
+        let l_var = BmaLayoutVariable::new_constant(3, "c", Some(1));
+        assert_eq!(l_var.r#type, VariableType::Constant);
+        assert_eq!(
+            l_var,
+            BmaLayoutVariable {
+                r#type: VariableType::Constant,
+                ..BmaLayoutVariable::new(3, "c", Some(1))
+            }
+        );
+    }
+
     #[test]
     fn default_variable_is_valid() {
         let l_var = BmaLayoutVariable::default();
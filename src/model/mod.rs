@@ -1,5 +1,7 @@
 pub(crate) mod bma_model;
 pub(crate) mod bma_network;
+pub(crate) mod bma_network_dynamics;
+pub(crate) mod bma_network_graph;
 pub(crate) mod bma_relationship;
 pub(crate) mod bma_variable;
 pub(crate) mod layout;
@@ -36,6 +38,7 @@ mod tests {
             description: "Lorem ipsum".to_string(),
             zoom_level: Some(Decimal::from(1) / Decimal::from(3)),
             pan: Some((Decimal::from(3), Decimal::from(10))),
+            ..Default::default()
         }
     }
 }
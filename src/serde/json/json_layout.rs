@@ -26,6 +26,7 @@ impl From<JsonLayout> for BmaLayout {
             description: value.description,
             zoom_level: None,
             pan: None,
+            ..Default::default()
         }
     }
 }
@@ -25,7 +25,7 @@ impl From<BmaLayoutContainer> for JsonLayoutContainer {
         JsonLayoutContainer {
             id: value.id.into(),
             name: value.name.clone(),
-            size: value.size.into(),
+            size: value.effective_size().into(),
             position_x: f64_or_default(value.position.0),
             position_y: f64_or_default(value.position.1),
         }
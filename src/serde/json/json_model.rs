@@ -1,7 +1,8 @@
 use crate::serde::json::{JsonLayout, JsonNetwork};
 use crate::{BmaModel, BmaNetwork};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
 
 /// An intermediate structure purely for deserializing JSON BMA models.
 ///
@@ -37,6 +38,55 @@ impl JsonBmaModel {
             })
             .collect::<Vec<_>>()
     }
+
+    /// Convert the model into a JSON value that mirrors the files exported by the BMA tool.
+    ///
+    /// Compared to the plain serialization, the layout is always present, it contains
+    /// an empty `AnnotatedGridCells` list, an empty `ltl` object is added to the model,
+    /// and integral numbers are written without a decimal part (e.g. `0` instead of `0.0`).
+    pub fn to_tool_json(&self) -> Result<Value, serde_json::Error> {
+        let mut value = serde_json::to_value(self)?;
+        if let Value::Object(model) = &mut value {
+            let layout = model
+                .entry("Layout")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if !layout.is_object() {
+                *layout = Value::Object(Map::new());
+            }
+            if let Value::Object(layout) = layout {
+                layout.entry("Variables").or_insert_with(|| json!([]));
+                layout.entry("Containers").or_insert_with(|| json!([]));
+                layout
+                    .entry("AnnotatedGridCells")
+                    .or_insert_with(|| json!([]));
+                layout.entry("Description").or_insert_with(|| json!(""));
+            }
+            model
+                .entry("ltl")
+                .or_insert_with(|| json!({ "states": [], "operations": [] }));
+        }
+        integral_floats_to_integers(&mut value);
+        Ok(value)
+    }
+}
+
+/// Recursively replace every floating-point number without a decimal part with an integer.
+fn integral_floats_to_integers(value: &mut Value) {
+    match value {
+        Value::Number(number) if !number.is_i64() && !number.is_u64() => {
+            if let Some(float) = number.as_f64()
+                && float.fract() == 0.0
+                && float.abs() < 9_007_199_254_740_992.0
+            {
+                #[allow(clippy::cast_possible_truncation)]
+                let integer = float as i64;
+                *value = Value::from(integer);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(integral_floats_to_integers),
+        Value::Object(items) => items.values_mut().for_each(integral_floats_to_integers),
+        _ => (),
+    }
 }
 
 impl From<BmaModel> for JsonBmaModel {
@@ -60,7 +110,7 @@ impl From<JsonBmaModel> for BmaModel {
             .unwrap_or_default(); // Default empty layout, if layout is not provided.
 
         // Metadata is not present in JsonBmaModel
-        let metadata = HashMap::new();
+        let metadata = BTreeMap::new();
 
         BmaModel::new(model, layout, metadata)
     }
@@ -32,7 +32,7 @@ impl From<(&JsonBmaModel, &JsonNetwork)> for BmaNetwork {
     fn from(value: (&JsonBmaModel, &JsonNetwork)) -> Self {
         let (model, network) = value;
 
-        BmaNetwork {
+        let mut result = BmaNetwork {
             variables: network
                 .variables
                 .iter()
@@ -40,6 +40,14 @@ impl From<(&JsonBmaModel, &JsonNetwork)> for BmaNetwork {
                 .collect::<Vec<_>>(),
             relationships: clone_into_vec(&network.relationships),
             name: network.name.clone(),
-        }
+            ..Default::default()
+        };
+        result.record_reference_styles(
+            network
+                .variables
+                .iter()
+                .map(|var| (var.id.into(), var.formula.as_str())),
+        );
+        result
     }
 }
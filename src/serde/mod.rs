@@ -5,8 +5,13 @@ pub(crate) mod quote_num;
 
 #[cfg(test)]
 mod tests {
-    use crate::{BmaModel, Validation};
+    use crate::{
+        BmaLayoutContainerError, BmaLayoutError, BmaLayoutVariableError, BmaModel, BmaModelError,
+        Validation,
+    };
+    #[cfg(feature = "bn-conversion")]
     use biodivine_lib_param_bn::BooleanNetwork;
+    use rust_decimal::dec;
     use std::collections::HashMap;
 
     fn xml_model_error_count() -> HashMap<&'static str, usize> {
@@ -211,6 +216,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "bn-conversion")]
     fn aeon_to_xml() {
         let network = BooleanNetwork::try_from_file("./models/test.aeon").unwrap();
         let model = BmaModel::try_from(&network).unwrap();
@@ -226,6 +232,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "bn-conversion")]
     fn aeon_to_json() {
         let network = BooleanNetwork::try_from_file("./models/test.aeon").unwrap();
         let model = BmaModel::try_from(&network).unwrap();
@@ -240,6 +247,305 @@ mod tests {
         );
     }
 
+    #[test]
+    fn xml_container_missing_size() {
+        let xml = r#"<Model Id="1" Name="test">
+            <Containers>
+                <Container Id="1"><PositionX>0</PositionX><PositionY>0</PositionY></Container>
+                <Container Id="2"><PositionX>1</PositionX><PositionY>0</PositionY><Size>0</Size></Container>
+                <Container Id="3"><PositionX>2</PositionX><PositionY>0</PositionY><Size>2</Size></Container>
+            </Containers>
+        </Model>"#;
+        let model = BmaModel::from_xml_string(xml).unwrap();
+        let sizes = model
+            .layout
+            .containers
+            .iter()
+            .map(|c| c.size)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![0, 0, 2]);
+        model.validate().unwrap();
+
+        let codes = model
+            .validate_strict()
+            .unwrap_err()
+            .iter()
+            .map(BmaModelError::code)
+            .collect::<Vec<_>>();
+        assert_eq!(codes, vec!["layout_container.defaulted_size"; 2]);
+
+        // Exported models always use the effective size.
+        let model2 = BmaModel::from_xml_string(model.to_xml_string().unwrap().as_str()).unwrap();
+        let sizes = model2
+            .layout
+            .containers
+            .iter()
+            .map(|c| c.size)
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![1, 1, 2]);
+        model2.validate_strict().unwrap();
+    }
+
+    #[test]
+    fn xml_non_finite_coordinates() {
+        let xml = r#"<Model Id="1" Name="test">
+            <Variables>
+                <Variable Id="1" Name="a">
+                    <RangeFrom>0</RangeFrom><RangeTo>1</RangeTo>
+                    <PositionX>1.5</PositionX><PositionY>2</PositionY><Angle>0</Angle>
+                </Variable>
+            </Variables>
+            <Containers>
+                <Container Id="2"><PositionX>0</PositionX><PositionY>0</PositionY><Size>1</Size></Container>
+            </Containers>
+        </Model>"#;
+        BmaModel::from_xml_string(xml).unwrap().validate().unwrap();
+
+        // Non-finite values are replaced by zero during import and reported by validation.
+        let invalid = xml
+            .replace("<Angle>0</Angle>", "<Angle>NaN</Angle>")
+            .replace("<PositionY>0</PositionY>", "<PositionY>-inf</PositionY>");
+        let model = BmaModel::from_xml_string(invalid.as_str()).unwrap();
+        assert_eq!(model.layout.find_variable(1).unwrap().angle, dec!(0));
+        assert_eq!(model.layout.find_container(2).unwrap().position.1, dec!(0));
+        let errors = model.validate().unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                BmaModelError::from(BmaLayoutError::from(
+                    BmaLayoutVariableError::NonFiniteCoordinate { id: 1 }
+                )),
+                BmaModelError::from(BmaLayoutError::from(
+                    BmaLayoutContainerError::NonFiniteCoordinate { id: 2 }
+                )),
+            ]
+        );
+
+        // Once exported, the replaced coordinates are no longer reported.
+        let exported = model.to_xml_string().unwrap();
+        BmaModel::from_xml_string(exported.as_str())
+            .unwrap()
+            .validate()
+            .unwrap();
+    }
+
+    #[test]
+    fn xml_variable_without_cells() {
+        let path = "./models/xml-trap-mvn/2var_unstableAnalysisInput.xml";
+        let xml = std::fs::read_to_string(path).unwrap();
+        let model = BmaModel::from_xml_string(xml.as_str()).unwrap();
+        assert!(!model.layout.variables.is_empty());
+        for var in &model.layout.variables {
+            assert_eq!(var.cell, None);
+        }
+
+        // Empty or incomplete cell coordinates are also treated as missing.
+        let xml = r#"<Model Id="1" Name="test">
+            <Variables>
+                <Variable Id="1" Name="a">
+                    <RangeFrom>0</RangeFrom><RangeTo>1</RangeTo>
+                    <CellX></CellX><CellY/>
+                </Variable>
+                <Variable Id="2" Name="b">
+                    <RangeFrom>0</RangeFrom><RangeTo>1</RangeTo>
+                    <CellX>3</CellX>
+                </Variable>
+                <Variable Id="3" Name="c">
+                    <RangeFrom>0</RangeFrom><RangeTo>1</RangeTo>
+                    <CellX>3</CellX><CellY>4</CellY>
+                </Variable>
+            </Variables>
+        </Model>"#;
+        let model = BmaModel::from_xml_string(xml).unwrap();
+        let cells = model
+            .layout
+            .variables
+            .iter()
+            .map(|v| v.cell)
+            .collect::<Vec<_>>();
+        assert_eq!(cells, vec![None, None, Some((3, 4))]);
+    }
+
+    #[test]
+    fn xml_round_trip() {
+        for file in std::fs::read_dir("./models/xml-repo").unwrap() {
+            let file = file.unwrap();
+            println!("File: {}", file.path().display());
+            let xml_data = std::fs::read_to_string(file.path()).unwrap();
+            let model = BmaModel::from_xml_string(xml_data.as_str()).unwrap();
+            let exported = model.to_xml_string().unwrap();
+            let model2 = BmaModel::from_xml_string(exported.as_str()).unwrap();
+            assert_eq!(model, model2);
+        }
+
+        let xml_data = std::fs::read_to_string("./models/xml-repo/BooleanLoop.xml").unwrap();
+        let model = BmaModel::from_xml_string(xml_data.as_str()).unwrap();
+        let exported = model.to_xml_string().unwrap();
+        assert!(exported.contains("<Variables><Variable Id=\"1\" Name=\"a\">"));
+        assert!(exported.contains("<Relationships><Relationship Id=\"1\">"));
+        assert!(exported.contains("<Containers><Container Id=\"1\""));
+        assert!(exported.contains("<PositionX>113</PositionX>"));
+    }
+
+    #[test]
+    fn xml_cells_and_angles_round_trip() {
+        let xml = r#"<Model Id="1" Name="test">
+            <Variables>
+                <Variable Id="1" Name="a">
+                    <RangeFrom>0</RangeFrom><RangeTo>1</RangeTo>
+                    <PositionX>10</PositionX><PositionY>20</PositionY>
+                    <CellX>2</CellX><CellY>3</CellY><Angle>-45</Angle>
+                </Variable>
+                <Variable Id="2" Name="b">
+                    <RangeFrom>0</RangeFrom><RangeTo>1</RangeTo>
+                    <Angle>22.5</Angle>
+                </Variable>
+            </Variables>
+        </Model>"#;
+        let model = BmaModel::from_xml_string(xml).unwrap();
+        let exported = model.to_xml_string().unwrap();
+        assert!(exported.contains("<CellX>2</CellX><CellY>3</CellY>"));
+        assert!(exported.contains("<Angle>-45</Angle>"));
+        assert!(exported.contains("<Angle>22.5</Angle>"));
+
+        let model2 = BmaModel::from_xml_string(exported.as_str()).unwrap();
+        let layout = |m: &BmaModel| {
+            m.layout
+                .variables
+                .iter()
+                .map(|v| (v.cell, v.angle))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            layout(&model2),
+            vec![(Some((2, 3)), dec!(-45)), (None, dec!(22.5))]
+        );
+        assert_eq!(layout(&model), layout(&model2));
+
+        // Tissue models keep their cell placement.
+        let path = "./models/xml-repo/Skin2D_3cells_2layers.xml";
+        let xml_data = std::fs::read_to_string(path).unwrap();
+        let model = BmaModel::from_xml_string(xml_data.as_str()).unwrap();
+        assert!(
+            model
+                .layout
+                .variables
+                .iter()
+                .any(|v| v.cell != Some((0, 0)))
+        );
+        let exported = model.to_xml_string().unwrap();
+        let model2 = BmaModel::from_xml_string(exported.as_str()).unwrap();
+        assert_eq!(layout(&model), layout(&model2));
+    }
+
+    #[test]
+    fn variable_descriptions_round_trip() {
+        let path = "./models/xml-repo/BooleanLoop.xml";
+        let xml_data = std::fs::read_to_string(path).unwrap();
+        let mut model = BmaModel::from_xml_string(xml_data.as_str()).unwrap();
+        model.set_variable_description(1, "Annotated <variable>");
+
+        let xml = model.to_xml_string().unwrap();
+        let from_xml = BmaModel::from_xml_string(xml.as_str()).unwrap();
+        assert_eq!(
+            from_xml.variable_description(1),
+            Some("Annotated <variable>")
+        );
+        assert_eq!(from_xml.variable_description(2), Some(""));
+        assert_eq!(model, from_xml);
+
+        let json = model.to_json_string().unwrap();
+        let from_json = BmaModel::from_json_string(json.as_str()).unwrap();
+        assert_eq!(
+            from_json.variable_description(1),
+            Some("Annotated <variable>")
+        );
+        let json = model.to_json_string_tool_compatible().unwrap();
+        let from_json = BmaModel::from_json_string(json.as_str()).unwrap();
+        assert_eq!(
+            from_json.variable_description(1),
+            Some("Annotated <variable>")
+        );
+    }
+
+    #[test]
+    fn json_unrepresentable_coordinates() {
+        let json = r#"{
+            "Model": {
+                "Name": "m",
+                "Variables": [{ "Id": 1, "Name": "a", "RangeFrom": 0, "RangeTo": 1, "Formula": "" }],
+                "Relationships": []
+            },
+            "Layout": {
+                "Variables": [{ "Id": 1, "Name": "a", "PositionX": 1e300, "PositionY": 2.5, "Angle": -1e300 }],
+                "Containers": [{ "Id": 1, "Name": "c", "PositionX": 1e300, "PositionY": 1, "Size": 1 }]
+            }
+        }"#;
+        // Layout coordinates are stored as decimals, hence they are always finite. Values
+        // outside of the decimal range are replaced by zero.
+        let model = BmaModel::from_json_string(json).unwrap();
+        let variable = model.layout.find_variable(1).unwrap();
+        assert_eq!(variable.position, (dec!(0), dec!(2.5)));
+        assert_eq!(variable.angle, dec!(0));
+        assert_eq!(model.layout.find_container(1).unwrap().position.0, dec!(0));
+        assert!(model.validate().is_ok());
+
+        // Non-finite numbers cannot be parsed from JSON at all.
+        let invalid = json.replace("1e300", "1e400");
+        assert!(BmaModel::from_json_string(invalid.as_str()).is_err());
+    }
+
+    #[test]
+    fn json_tool_compatible_round_trip() {
+        for file in std::fs::read_dir("./models/json-export-from-tool").unwrap() {
+            let file = file.unwrap();
+            println!("File: {}", file.path().display());
+            let json_data = std::fs::read_to_string(file.path()).unwrap();
+            let model = BmaModel::from_json_string(json_data.as_str()).unwrap();
+            let exported = model.to_json_string_tool_compatible().unwrap();
+            let expected = serde_json::from_str::<serde_json::Value>(json_data.as_str()).unwrap();
+            let actual = serde_json::from_str::<serde_json::Value>(exported.as_str()).unwrap();
+            assert_tool_json_eq(&actual, &expected);
+            // Layout positions can lose precision when converted to `f64`.
+            let model2 = BmaModel::from_json_string(exported.as_str()).unwrap();
+            assert_eq!(model.network, model2.network);
+        }
+    }
+
+    /// Compare two JSON values exported by the tool. Update functions can be formatted
+    /// differently and numbers can lose some precision (or be quoted), but everything else must match.
+    fn assert_tool_json_eq(actual: &serde_json::Value, expected: &serde_json::Value) {
+        use serde_json::Value;
+        match (actual, expected) {
+            (Value::Object(actual), Value::Object(expected)) => {
+                let actual_keys = actual.keys().collect::<Vec<_>>();
+                let expected_keys = expected.keys().collect::<Vec<_>>();
+                assert_eq!(actual_keys, expected_keys);
+                for (key, value) in expected {
+                    if key != "Formula" {
+                        assert_tool_json_eq(&actual[key], value);
+                    }
+                }
+            }
+            (Value::Array(actual), Value::Array(expected)) => {
+                assert_eq!(actual.len(), expected.len());
+                for (a, e) in actual.iter().zip(expected) {
+                    assert_tool_json_eq(a, e);
+                }
+            }
+            (Value::Number(actual), Value::Number(expected)) => {
+                assert_eq!(actual.is_f64(), expected.is_f64());
+                let (a, e) = (actual.as_f64().unwrap(), expected.as_f64().unwrap());
+                assert!((a - e).abs() < 1e-9, "{a} != {e}");
+            }
+            (Value::Number(actual), Value::String(expected)) => {
+                // Some exported files contain quoted numbers.
+                assert_eq!(&actual.to_string(), expected);
+            }
+            (actual, expected) => assert_eq!(actual, expected),
+        }
+    }
+
     fn validate_model(path: &str, model: &BmaModel, expected: &HashMap<&'static str, usize>) {
         let errors = if let Err(errors) = model.validate() {
             println!("\tValidation errors: {}", errors.len());
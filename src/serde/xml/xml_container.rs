@@ -4,8 +4,10 @@ use serde::{Deserialize, Serialize};
 
 /// Structure to deserialize XML info about container.
 ///
-/// All details must be provided, except for the name. If the name is missing,
-/// we set it to an empty string.
+/// All details must be provided, except for the name and size. If the name is missing,
+/// we set it to an empty string. Some older files omit the size (or set it to `0`).
+/// In such case, we keep `0`, which is then interpreted as the default size `1`
+/// (see [`BmaLayoutContainer::effective_size`]).
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct XmlContainer {
     #[serde(rename = "@Id", alias = "Id")]
@@ -16,10 +18,17 @@ pub(crate) struct XmlContainer {
     pub position_x: f64,
     #[serde(rename = "PositionY")]
     pub position_y: f64,
-    #[serde(rename = "Size")]
+    #[serde(default, rename = "Size")]
     pub size: u32,
 }
 
+impl XmlContainer {
+    /// Check that the position of the container is a finite number.
+    pub fn has_finite_coordinates(&self) -> bool {
+        self.position_x.is_finite() && self.position_y.is_finite()
+    }
+}
+
 impl From<BmaLayoutContainer> for XmlContainer {
     fn from(value: BmaLayoutContainer) -> Self {
         XmlContainer {
@@ -27,7 +36,7 @@ impl From<BmaLayoutContainer> for XmlContainer {
             name: value.name.clone(),
             position_x: f64_or_default(value.position.0),
             position_y: f64_or_default(value.position.1),
-            size: value.size,
+            size: value.effective_size(),
         }
     }
 }
@@ -61,6 +61,20 @@ impl From<&XmlBmaModel> for BmaLayout {
             description: value.description.clone(),
             zoom_level,
             pan,
+            non_finite_variables: value
+                .variables
+                .variable
+                .iter()
+                .filter(|v| !v.has_finite_coordinates())
+                .map(|v| v.id)
+                .collect(),
+            non_finite_containers: value
+                .containers
+                .iter()
+                .flat_map(|c| &c.container)
+                .filter(|c| !c.has_finite_coordinates())
+                .map(|c| c.id)
+                .collect(),
         }
     }
 }
@@ -1,8 +1,8 @@
-use crate::serde::xml::{XmlContainers, XmlLayout, XmlRelationships, XmlVariables};
+use crate::serde::xml::{XmlContainers, XmlLayout, XmlRelationships, XmlVariable, XmlVariables};
 use crate::utils::clone_into_vec;
 use crate::{BmaLayout, BmaModel, BmaNetwork};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 /// An intermediate structure purely for deserializing XML BMA models.
 ///
@@ -66,8 +66,17 @@ impl XmlBmaModel {
 impl From<BmaModel> for XmlBmaModel {
     fn from(model: BmaModel) -> Self {
         XmlBmaModel {
+            // Layout information of variables is stored together with the variables.
             variables: XmlVariables {
-                variable: clone_into_vec(&model.network.variables),
+                variable: model
+                    .network
+                    .variables
+                    .iter()
+                    .map(|var| match model.layout.find_variable(var.id) {
+                        Some(layout) => XmlVariable::from((var.clone(), layout.clone())),
+                        None => XmlVariable::from(var.clone()),
+                    })
+                    .collect(),
             },
             relationships: XmlRelationships {
                 relationship: clone_into_vec(&model.network.relationships),
@@ -79,16 +88,16 @@ impl From<BmaModel> for XmlBmaModel {
             containers: Some(XmlContainers {
                 container: clone_into_vec(&model.layout.containers),
             }),
-            biocheck_version: model.metadata.get("biocheck_version").cloned(),
-            created_date: model.metadata.get("created_date").cloned(),
-            modified_date: model.metadata.get("modified_date").cloned(),
+            biocheck_version: model.metadata.get(BmaModel::BIOCHECK_VERSION_KEY).cloned(),
+            created_date: model.metadata.get(BmaModel::CREATED_DATE_KEY).cloned(),
+            modified_date: model.metadata.get(BmaModel::MODIFIED_DATE_KEY).cloned(),
         }
     }
 }
 
 impl From<XmlBmaModel> for BmaModel {
     fn from(value: XmlBmaModel) -> Self {
-        let network = BmaNetwork {
+        let mut network = BmaNetwork {
             name: value.name.clone(),
             variables: value
                 .variables
@@ -97,20 +106,34 @@ impl From<XmlBmaModel> for BmaModel {
                 .map(|v| (&value, v).into())
                 .collect::<Vec<_>>(),
             relationships: clone_into_vec(&value.relationships.relationship),
+            ..Default::default()
         };
+        network.record_reference_styles(
+            value
+                .variables
+                .variable
+                .iter()
+                .map(|v| (v.id, v.formula.as_str())),
+        );
 
         let layout = BmaLayout::from(&value);
 
         // Metadata can be constructed from various XML fields
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         if let Some(biocheck_version) = &value.biocheck_version {
-            metadata.insert("biocheck_version".to_string(), biocheck_version.clone());
+            metadata.insert(
+                BmaModel::BIOCHECK_VERSION_KEY.to_string(),
+                biocheck_version.clone(),
+            );
         }
         if let Some(created_date) = &value.created_date {
-            metadata.insert("created_date".to_string(), created_date.clone());
+            metadata.insert(BmaModel::CREATED_DATE_KEY.to_string(), created_date.clone());
         }
         if let Some(modified_date) = &value.modified_date {
-            metadata.insert("modified_date".to_string(), modified_date.clone());
+            metadata.insert(
+                BmaModel::MODIFIED_DATE_KEY.to_string(),
+                modified_date.clone(),
+            );
         }
 
         BmaModel {
@@ -2,7 +2,7 @@ use crate::serde::xml::XmlBmaModel;
 use crate::update_function::BmaUpdateFunction;
 use crate::utils::{decimal_or_default, f64_or_default};
 use crate::{BmaLayoutVariable, BmaVariable, VariableType};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, de};
 
 /// Structure to deserialize XML info about a variable. BMA XML format mixes
 /// functional and layout information for variables (unlike JSON),
@@ -13,7 +13,9 @@ use serde::{Deserialize, Serialize};
 /// If the type is missing, we set it to the default value.
 ///
 /// All other layout details are optional. If not provided, we set them to `None` here,
-/// and some are set to default values later as needed.
+/// and some are set to default values later as needed. Cell coordinates are also treated
+/// as missing when the tag is empty (e.g. `<CellX/>`), or when only one of them is present.
+/// The variable description is only written if it is not empty.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub(crate) struct XmlVariable {
     // By default, ID and name are attributes, but they can be also present as child tags.
@@ -38,10 +40,34 @@ pub(crate) struct XmlVariable {
     pub angle: f64,
     #[serde(default, rename = "ContainerId")]
     pub container_id: Option<u32>,
-    #[serde(default, rename = "CellX")]
+    #[serde(default, rename = "CellX", deserialize_with = "optional_u32")]
     pub cell_x: Option<u32>,
-    #[serde(default, rename = "CellY")]
+    #[serde(default, rename = "CellY", deserialize_with = "optional_u32")]
     pub cell_y: Option<u32>,
+    #[serde(
+        default,
+        rename = "Description",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub description: String,
+}
+
+/// Deserialize an optional `u32` value, treating an empty (or blank) tag as a missing value.
+fn optional_u32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse::<u32>().map(Some).map_err(de::Error::custom),
+    }
+}
+
+impl XmlVariable {
+    /// Check that the position and angle of the variable are finite numbers.
+    pub fn has_finite_coordinates(&self) -> bool {
+        [self.position_x, self.position_y, self.angle]
+            .iter()
+            .all(|x| x.is_finite())
+    }
 }
 
 impl From<BmaVariable> for XmlVariable {
@@ -59,6 +85,7 @@ impl From<BmaVariable> for XmlVariable {
             container_id: None,
             cell_x: None,
             cell_y: None,
+            description: String::default(),
         }
     }
 }
@@ -72,6 +99,7 @@ impl From<(BmaVariable, BmaLayoutVariable)> for XmlVariable {
         variable.position_y = f64_or_default(layout.position.1);
         variable.angle = f64_or_default(layout.angle);
         variable.container_id = layout.container_id;
+        variable.description = layout.description;
         if let Some((x, y)) = layout.cell {
             variable.cell_x = Some(x);
             variable.cell_y = Some(y);
@@ -110,7 +138,7 @@ impl From<XmlVariable> for BmaLayoutVariable {
             container_id: value.container_id,
             r#type: VariableType::from(value.r#type.as_str()),
             name: value.name.clone(),
-            description: String::default(),
+            description: value.description.clone(),
             position: (
                 decimal_or_default(value.position_x),
                 decimal_or_default(value.position_y),
@@ -0,0 +1,176 @@
+use crate::update_function::{
+    AggregateFn, ArithOp, BmaExpressionNodeData, BmaUpdateFunction, Literal, UnaryFn,
+};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The JSON representation of a single [`BmaUpdateFunction`] node (see
+/// [`BmaUpdateFunction::to_ast_json`]).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum AstNode {
+    Const {
+        value: i32,
+    },
+    Var {
+        id: u32,
+    },
+    Arith {
+        op: String,
+        left: Box<AstNode>,
+        right: Box<AstNode>,
+    },
+    Unary {
+        op: String,
+        arg: Box<AstNode>,
+    },
+    Aggregate {
+        op: String,
+        args: Vec<AstNode>,
+    },
+}
+
+impl BmaUpdateFunction {
+    /// Convert this function into a JSON representation of its syntax tree.
+    ///
+    /// Each node is an object with a `type` field, which is one of the following:
+    ///  - `const`: An integer constant stored in `value`.
+    ///  - `var`: A variable with ID stored in `id`.
+    ///  - `arith`: A binary operator `op` (`+`, `-`, `*`, or `/`) with
+    ///    operands `left` and `right`.
+    ///  - `unary`: A function `op` (`abs`, `ceil`, `floor`, or `neg`) with argument `arg`.
+    ///  - `aggregate`: A function `op` (`min`, `max`, or `avg`) with a list of arguments `args`.
+    ///
+    /// The representation can be converted back using [`BmaUpdateFunction::from_ast_json`].
+    #[must_use]
+    pub fn to_ast_json(&self) -> Value {
+        serde_json::to_value(AstNode::from(self))
+            .expect("Invariant violation: AST node is always serializable.")
+    }
+
+    /// Build a [`BmaUpdateFunction`] from the JSON representation of its syntax tree
+    /// (see [`BmaUpdateFunction::to_ast_json`]).
+    ///
+    /// Returns an error if the JSON value is not a valid syntax tree.
+    pub fn from_ast_json(value: &Value) -> anyhow::Result<BmaUpdateFunction> {
+        let node = AstNode::deserialize(value)?;
+        BmaUpdateFunction::try_from(&node)
+    }
+}
+
+impl From<&BmaUpdateFunction> for AstNode {
+    fn from(value: &BmaUpdateFunction) -> Self {
+        match value.as_data() {
+            BmaExpressionNodeData::Terminal(Literal::Const(value)) => {
+                AstNode::Const { value: *value }
+            }
+            BmaExpressionNodeData::Terminal(Literal::Var(id)) => AstNode::Var { id: *id },
+            BmaExpressionNodeData::Arithmetic(op, left, right) => AstNode::Arith {
+                op: op.to_string(),
+                left: Box::new(AstNode::from(left)),
+                right: Box::new(AstNode::from(right)),
+            },
+            BmaExpressionNodeData::Unary(op, arg) => AstNode::Unary {
+                op: match op {
+                    UnaryFn::Neg => "neg".to_string(),
+                    op => op.to_string(),
+                },
+                arg: Box::new(AstNode::from(arg)),
+            },
+            BmaExpressionNodeData::Aggregation(op, args) => AstNode::Aggregate {
+                op: op.to_string(),
+                args: args.iter().map(AstNode::from).collect(),
+            },
+        }
+    }
+}
+
+impl TryFrom<&AstNode> for BmaUpdateFunction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &AstNode) -> Result<Self, Self::Error> {
+        Ok(match value {
+            AstNode::Const { value } => BmaUpdateFunction::mk_constant(*value),
+            AstNode::Var { id } => BmaUpdateFunction::mk_variable(*id),
+            AstNode::Arith { op, left, right } => {
+                let mut chars = op.chars();
+                let parsed = match (chars.next(), chars.next()) {
+                    (Some(c), None) => ArithOp::try_from(c).ok(),
+                    _ => None,
+                };
+                let op = parsed.ok_or_else(|| anyhow!("Unknown arithmetic operator `{op}`"))?;
+                let left = BmaUpdateFunction::try_from(left.as_ref())?;
+                let right = BmaUpdateFunction::try_from(right.as_ref())?;
+                BmaUpdateFunction::mk_arithmetic(op, &left, &right)
+            }
+            AstNode::Unary { op, arg } => {
+                let op = if op == "neg" {
+                    UnaryFn::Neg
+                } else {
+                    UnaryFn::try_from(op.as_str())
+                        .map_err(|()| anyhow!("Unknown unary function `{op}`"))?
+                };
+                let arg = BmaUpdateFunction::try_from(arg.as_ref())?;
+                BmaUpdateFunction::mk_unary(op, &arg)
+            }
+            AstNode::Aggregate { op, args } => {
+                let op = AggregateFn::try_from(op.as_str())
+                    .map_err(|()| anyhow!("Unknown aggregation function `{op}`"))?;
+                let args = args
+                    .iter()
+                    .map(BmaUpdateFunction::try_from)
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                BmaUpdateFunction::try_mk_aggregation(op, &args)?
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::update_function::BmaUpdateFunction;
+    use serde_json::json;
+
+    #[test]
+    fn ast_json_round_trip() {
+        let function =
+            BmaUpdateFunction::try_from("max(var(1), 2) - abs(-var(3)) * avg(1, floor(var(2)))")
+                .unwrap();
+        let ast = function.to_ast_json();
+        assert_eq!(BmaUpdateFunction::from_ast_json(&ast).unwrap(), function);
+    }
+
+    #[test]
+    fn ast_json_format() {
+        let function = BmaUpdateFunction::try_from("min(var(1), -3) / 2").unwrap();
+        let expected = json!({
+            "type": "arith",
+            "op": "/",
+            "left": {
+                "type": "aggregate",
+                "op": "min",
+                "args": [
+                    { "type": "var", "id": 1 },
+                    { "type": "unary", "op": "neg", "arg": { "type": "const", "value": 3 } },
+                ]
+            },
+            "right": { "type": "const", "value": 2 },
+        });
+        assert_eq!(function.to_ast_json(), expected);
+    }
+
+    #[test]
+    fn ast_json_invalid() {
+        let invalid = [
+            json!({ "type": "variable", "id": 1 }),
+            json!({ "type": "var" }),
+            json!({ "type": "arith", "op": "%", "left": { "type": "const", "value": 1 }, "right": { "type": "const", "value": 1 } }),
+            json!({ "type": "unary", "op": "sqrt", "arg": { "type": "const", "value": 1 } }),
+            json!({ "type": "aggregate", "op": "max", "args": [] }),
+        ];
+        for value in invalid {
+            assert!(BmaUpdateFunction::from_ast_json(&value).is_err());
+        }
+    }
+}
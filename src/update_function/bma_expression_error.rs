@@ -21,6 +21,10 @@ impl ParserError {
 }
 
 /// An error raised when an update function expression is invalid and cannot be parsed correctly.
+///
+/// The `position` is the index of the *character* (not byte) of the `expression` where
+/// the error was detected. Use [`InvalidBmaExpression::position`] to only obtain positions
+/// that refer to an actual character of the expression (e.g. to highlight it in an editor).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Error)]
 #[error("Invalid expression `{expression}`: {message} at position `{position}`")]
 pub struct InvalidBmaExpression {
@@ -37,4 +41,31 @@ impl InvalidBmaExpression {
             message: error.message,
         }
     }
+
+    /// The index of the character in `expression` where the error was detected.
+    ///
+    /// Returns `None` if the error does not point to a specific character (for example,
+    /// when the expression is empty).
+    #[must_use]
+    pub fn position(&self) -> Option<usize> {
+        if self.position < self.expression.chars().count() {
+            Some(self.position)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::update_function::BmaUpdateFunction;
+
+    #[test]
+    fn error_position() {
+        let error = BmaUpdateFunction::try_from("var(1) + )").unwrap_err();
+        assert_eq!(error.position(), Some(9));
+
+        let error = BmaUpdateFunction::try_from("").unwrap_err();
+        assert_eq!(error.position(), None);
+    }
 }
@@ -1,8 +1,10 @@
-use crate::update_function::expression_parser::parse_bma_formula;
+use crate::update_function::expression_parser::{parse_bma_formula, parse_bma_formula_collecting};
+use crate::update_function::expression_token::{collect_id_references, collect_name_references};
 use crate::update_function::{
     AggregateFn, ArithOp, BmaExpressionNodeData, InvalidBmaExpression, Literal, UnaryFn,
 };
 use crate::utils::take_if_not_blank;
+use anyhow::bail;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 use std::sync::Arc;
@@ -69,13 +71,29 @@ impl BmaUpdateFunction {
     /// Create a [`BmaUpdateFunction`] representing an aggregation operator
     /// applied to given arguments.
     ///
-    /// Precondition: The list of inner nodes must not be empty!
+    /// See [`BmaUpdateFunction::try_mk_aggregation`] for a non-panicking alternative.
+    ///
+    /// # Panics
+    /// The list of inner nodes must not be empty, since aggregations without
+    /// arguments cannot be evaluated.
     #[must_use]
     pub fn mk_aggregation(op: AggregateFn, inner_nodes: &[BmaUpdateFunction]) -> BmaUpdateFunction {
         assert!(!inner_nodes.is_empty(), "At least one argument required.");
         BmaExpressionNodeData::Aggregation(op, inner_nodes.to_vec()).into()
     }
 
+    /// The same as [`BmaUpdateFunction::mk_aggregation`], but returns an error instead of
+    /// panicking when the list of inner nodes is empty.
+    pub fn try_mk_aggregation(
+        op: AggregateFn,
+        inner_nodes: &[BmaUpdateFunction],
+    ) -> anyhow::Result<BmaUpdateFunction> {
+        if inner_nodes.is_empty() {
+            bail!("Function `{op}` expects at least one argument");
+        }
+        Ok(BmaExpressionNodeData::Aggregation(op, inner_nodes.to_vec()).into())
+    }
+
     /// Return true if this function is a constant.
     ///
     /// Note that this only performs a syntactic check. Something like `0 - 0` will not be
@@ -89,6 +107,12 @@ impl BmaUpdateFunction {
     ///
     /// Note that this only performs a syntactic check. Something like `0 - 0` will not be
     /// considered constant in this case.
+    ///
+    /// The value is the raw number from the expression, i.e., it is not normalized to the
+    /// range of any variable. It can be negative (e.g. `mk_constant(-1)`), even though such
+    /// value is later truncated to the variable range. However, a negative number in a formula
+    /// string (e.g. `-1`) is parsed as a unary minus applied to a constant, which is not
+    /// considered constant by this check.
     #[must_use]
     pub fn as_constant(&self) -> Option<i32> {
         match self.as_data() {
@@ -96,6 +120,139 @@ impl BmaUpdateFunction {
             _ => None,
         }
     }
+
+    /// Replace every occurrence of variable `var_id` with the given `replacement` expression.
+    #[must_use]
+    pub(crate) fn substitute_variable(
+        &self,
+        var_id: u32,
+        replacement: &BmaUpdateFunction,
+    ) -> BmaUpdateFunction {
+        match self.as_data() {
+            BmaExpressionNodeData::Terminal(Literal::Var(id)) if *id == var_id => {
+                replacement.clone()
+            }
+            BmaExpressionNodeData::Terminal(_) => self.clone(),
+            BmaExpressionNodeData::Unary(op, child) => {
+                BmaUpdateFunction::mk_unary(*op, &child.substitute_variable(var_id, replacement))
+            }
+            BmaExpressionNodeData::Arithmetic(op, left, right) => BmaUpdateFunction::mk_arithmetic(
+                *op,
+                &left.substitute_variable(var_id, replacement),
+                &right.substitute_variable(var_id, replacement),
+            ),
+            BmaExpressionNodeData::Aggregation(op, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.substitute_variable(var_id, replacement))
+                    .collect::<Vec<_>>();
+                BmaUpdateFunction::mk_aggregation(*op, &args)
+            }
+        }
+    }
+
+    /// Rewrite common negation patterns in this function into a canonical form.
+    ///
+    /// The canonical form of a (Boolean) negation is `1 - var(x)`, which is also what
+    /// is produced when importing Boolean networks. More generally, for a variable with
+    /// range `0..k`, the negation is `k - var(x)`. Such expressions are kept as they are,
+    /// while the following equivalent forms are rewritten into them:
+    ///
+    ///  - `k + -(e)`, `-(e) + k`, `k + (0 - e)`, and `(0 - e) + k` become `k - e`;
+    ///  - `k1 - (k2 - e)` becomes `e` when `k1 == k2`, or `(k1 - k2) + e` otherwise;
+    ///  - `-(-(e))` becomes `e`.
+    ///
+    /// The rewrites are applied bottom-up and do not change the semantics of the function.
+    #[must_use]
+    pub fn simplify(&self) -> BmaUpdateFunction {
+        match self.as_data() {
+            BmaExpressionNodeData::Terminal(_) => self.clone(),
+            BmaExpressionNodeData::Unary(op, child) => {
+                let child = child.simplify();
+                if let (UnaryFn::Neg, BmaExpressionNodeData::Unary(UnaryFn::Neg, inner)) =
+                    (op, child.as_data())
+                {
+                    inner.clone()
+                } else {
+                    BmaUpdateFunction::mk_unary(*op, &child)
+                }
+            }
+            BmaExpressionNodeData::Arithmetic(op, left, right) => {
+                Self::simplify_arithmetic(*op, &left.simplify(), &right.simplify())
+            }
+            BmaExpressionNodeData::Aggregation(op, args) => {
+                let args = args.iter().map(Self::simplify).collect::<Vec<_>>();
+                BmaUpdateFunction::mk_aggregation(*op, &args)
+            }
+        }
+    }
+
+    /// Apply the rewrites of [`BmaUpdateFunction::simplify`] to an arithmetic node whose
+    /// children are already simplified.
+    fn simplify_arithmetic(
+        op: ArithOp,
+        left: &BmaUpdateFunction,
+        right: &BmaUpdateFunction,
+    ) -> BmaUpdateFunction {
+        match op {
+            ArithOp::Plus => {
+                if left.is_constant()
+                    && let Some(negated) = right.as_negation()
+                {
+                    return BmaUpdateFunction::mk_arithmetic(ArithOp::Minus, left, negated);
+                }
+                if right.is_constant()
+                    && let Some(negated) = left.as_negation()
+                {
+                    return BmaUpdateFunction::mk_arithmetic(ArithOp::Minus, right, negated);
+                }
+            }
+            ArithOp::Minus => {
+                if let Some(k1) = left.as_constant()
+                    && let BmaExpressionNodeData::Arithmetic(ArithOp::Minus, inner, e) =
+                        right.as_data()
+                    && let Some(k2) = inner.as_constant()
+                    && let Some(diff) = k1.checked_sub(k2)
+                {
+                    match diff {
+                        0 => return e.clone(),
+                        d if d > 0 => {
+                            return BmaUpdateFunction::mk_arithmetic(
+                                ArithOp::Plus,
+                                &BmaUpdateFunction::mk_constant(d),
+                                e,
+                            );
+                        }
+                        // `-i32::MIN` is not representable, so the node is left unchanged.
+                        d => {
+                            if let Some(d) = d.checked_neg() {
+                                return BmaUpdateFunction::mk_arithmetic(
+                                    ArithOp::Minus,
+                                    e,
+                                    &BmaUpdateFunction::mk_constant(d),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            ArithOp::Mult | ArithOp::Div => (),
+        }
+        BmaUpdateFunction::mk_arithmetic(op, left, right)
+    }
+
+    /// If this function is `-(e)` or `0 - e`, return `e`.
+    fn as_negation(&self) -> Option<&BmaUpdateFunction> {
+        match self.as_data() {
+            BmaExpressionNodeData::Unary(UnaryFn::Neg, inner) => Some(inner),
+            BmaExpressionNodeData::Arithmetic(ArithOp::Minus, zero, inner)
+                if zero.as_constant() == Some(0) =>
+            {
+                Some(inner)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl BmaUpdateFunction {
@@ -113,6 +270,45 @@ impl BmaUpdateFunction {
             .map_err(|e| InvalidBmaExpression::from_parser_error(e, expression.to_string()))
     }
 
+    /// The same as [`BmaUpdateFunction::parse_with_hint`], but instead of stopping at the first
+    /// error, the parser tries to recover and report as many errors as possible (e.g. all
+    /// unknown identifiers, or unbalanced parentheses together with other issues).
+    ///
+    /// The function is only returned if there are no errors. The errors are reported in the
+    /// order in which they were discovered. Note that an error can be a consequence of
+    /// a previous error.
+    #[must_use]
+    pub fn parse_collecting(
+        expression: &str,
+        variable_id_hint: &[(u32, String)],
+    ) -> (Option<BmaUpdateFunction>, Vec<InvalidBmaExpression>) {
+        let (function, errors) = parse_bma_formula_collecting(expression, variable_id_hint);
+        let errors = errors
+            .into_iter()
+            .map(|e| InvalidBmaExpression::from_parser_error(e, expression.to_string()))
+            .collect();
+        (function, errors)
+    }
+
+    /// Check whether the given `expression` references any variable by its name
+    /// (i.e. `var(name)` instead of `var(id)`).
+    ///
+    /// Such references are resolved to IDs by [`BmaUpdateFunction::parse_with_hint`], so
+    /// the expression can break if the variable is renamed before it is parsed. The check
+    /// is purely lexical, meaning the expression does not need to be valid.
+    #[must_use]
+    pub fn uses_name_references(expression: &str) -> bool {
+        !collect_name_references(expression).is_empty()
+    }
+
+    /// Check whether the given `expression` references any variable by its numeric ID
+    /// (i.e. `var(id)`). Like [`BmaUpdateFunction::uses_name_references`], the check is
+    /// purely lexical.
+    #[must_use]
+    pub(crate) fn uses_id_references(expression: &str) -> bool {
+        !collect_id_references(expression).is_empty()
+    }
+
     /// The same as [`BmaUpdateFunction::parse_with_hint`], but if the string is empty, the
     /// method returns `None`.
     #[must_use]
@@ -193,3 +389,106 @@ impl<'de> Deserialize<'de> for BmaUpdateFunction {
         BmaUpdateFunction::try_from(value.as_str()).map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::update_function::{AggregateFn, ArithOp, BmaUpdateFunction};
+    use rust_decimal::Decimal;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn uses_name_references() {
+        assert!(BmaUpdateFunction::uses_name_references("var(x) + var(1)"));
+        assert!(BmaUpdateFunction::uses_name_references("max(var( y_1 ), 2"));
+        assert!(!BmaUpdateFunction::uses_name_references(
+            "var(1) + variance(x)"
+        ));
+        assert!(!BmaUpdateFunction::uses_name_references(
+            "var(12) - var (3)"
+        ));
+        assert!(!BmaUpdateFunction::uses_name_references(""));
+
+        assert!(BmaUpdateFunction::uses_id_references("var(x) + var( 1 )"));
+        assert!(!BmaUpdateFunction::uses_id_references("var(x) + 1"));
+    }
+
+    #[test]
+    fn aggregation_requires_arguments() {
+        let arg = BmaUpdateFunction::mk_variable(1);
+        let function = BmaUpdateFunction::try_mk_aggregation(AggregateFn::Min, &[arg]).unwrap();
+        assert_eq!(function.to_string(), "min(var(1))");
+        let error = BmaUpdateFunction::try_mk_aggregation(AggregateFn::Avg, &[]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Function `avg` expects at least one argument"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "At least one argument required.")]
+    fn empty_aggregation_panics() {
+        let _ = BmaUpdateFunction::mk_aggregation(AggregateFn::Max, &[]);
+    }
+
+    #[test]
+    fn constant_detection() {
+        let parse = |s: &str| BmaUpdateFunction::try_from(s).unwrap();
+        assert_eq!(parse("42").as_constant(), Some(42));
+        assert!(parse("(3)").is_constant());
+        assert_eq!(BmaUpdateFunction::mk_constant(-1).as_constant(), Some(-1));
+        for function in ["-1", "0 - 0", "var(1)", "abs(2)"] {
+            assert!(!parse(function).is_constant(), "{function}");
+        }
+    }
+
+    #[test]
+    fn simplify_negation() {
+        let parse = |s: &str| BmaUpdateFunction::try_from(s).unwrap();
+        let cases = [
+            ("1 - var(1)", "1 - var(1)"),
+            ("2 - var(1)", "2 - var(1)"),
+            ("1 + -var(1)", "1 - var(1)"),
+            ("-(var(1)) + 1", "1 - var(1)"),
+            ("1 + (0 - var(1))", "1 - var(1)"),
+            ("(0 - var(1)) + 2", "2 - var(1)"),
+            ("1 - (1 - var(1))", "var(1)"),
+            ("2 - (1 - var(1))", "1 + var(1)"),
+            ("1 - (2 - var(1))", "var(1) - 1"),
+            ("-(-(var(1)))", "var(1)"),
+            (
+                "max(1 + -var(1), var(2) * (0 - var(1)))",
+                "max(1 - var(1), var(2) * (0 - var(1)))",
+            ),
+        ];
+        for (input, expected) in cases {
+            let function = parse(input);
+            let simplified = function.simplify();
+            assert_eq!(simplified, parse(expected), "{input}");
+            for x in 0..3 {
+                for y in 0..3 {
+                    let valuation = BTreeMap::from([(1, Decimal::from(x)), (2, Decimal::from(y))]);
+                    assert_eq!(
+                        function.evaluate_raw(&valuation).unwrap(),
+                        simplified.evaluate_raw(&valuation).unwrap(),
+                        "{input}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn simplify_without_overflow() {
+        // `-1 - (i32::MAX - var(1))` would need the constant `-i32::MIN`.
+        let function = BmaUpdateFunction::mk_arithmetic(
+            ArithOp::Minus,
+            &BmaUpdateFunction::mk_constant(-1),
+            &BmaUpdateFunction::mk_arithmetic(
+                ArithOp::Minus,
+                &BmaUpdateFunction::mk_constant(i32::MAX),
+                &BmaUpdateFunction::mk_variable(1),
+            ),
+        );
+        assert_eq!(function.simplify(), function);
+    }
+}
@@ -8,7 +8,8 @@ use num_traits::Zero;
 use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy::MidpointAwayFromZero;
 use std::cmp::{max, min};
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use thiserror::Error;
 
 /// A function table is a vector of tuples, where each tuple contains a variable valuation
 /// and output value. Variable valuation is a mapping of variable IDs to their values. In theory,
@@ -20,6 +21,25 @@ use std::collections::{BTreeMap, HashSet};
 /// computation within the update function can involve
 pub type FunctionTable = Vec<(BTreeMap<u32, u32>, u32)>;
 
+/// Error reported by [`BmaNetwork::build_function_table`] when the update function divides
+/// by zero for one of the input valuations (e.g. `1 / var(3)` when `var(3)` is zero).
+///
+/// The error is returned as [`anyhow::Error`], but it can be recovered using
+/// [`anyhow::Error::downcast_ref`] to obtain the `valuation` that triggered it.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Cannot evaluate {expression} in {valuation:?}: Division by zero")]
+pub struct DivisionByZeroError {
+    /// The update function that was evaluated.
+    pub expression: String,
+    /// The input valuation (regulator levels) for which the division by zero occurred.
+    pub valuation: BTreeMap<u32, u32>,
+}
+
+/// Internal marker error returned by [`BmaUpdateFunction::evaluate_with`] for division by zero.
+#[derive(Error, Debug)]
+#[error("Division by zero")]
+struct DivisionByZero;
+
 impl BmaNetwork {
     /// Evaluate the BMA function expression assigned to the given variable. The result is a level
     /// within the allowed range of this variable (the value is truncated if it does not fit
@@ -40,6 +60,128 @@ impl BmaNetwork {
             .find_variable(var_id)
             .ok_or_else(|| anyhow!("Target variable with id `{var_id}` not found"))?;
 
+        let normalized_valuation = self.normalized_valuation(var_id, valuation)?;
+
+        if let Some(function) = &target_var.formula {
+            let function = function.as_ref().map_err(|e| anyhow!(e.to_string()))?;
+            let raw_result = self.evaluate_function(function, &normalized_valuation)?;
+            Ok(target_var.normalize_output_level(raw_result))
+        } else {
+            Err(anyhow!("No update function found for `{var_id}`"))
+        }
+    }
+
+    /// The same as [`BmaNetwork::evaluate`], but `partial` does not need to contain the levels
+    /// of all variables used by the update function. Every missing variable is set to
+    /// `default_level`, clamped into the range of that variable (e.g. `0` means the minimal
+    /// level of each variable). That is, missing variables are defaulted, not ignored.
+    ///
+    /// The operation fails under the same conditions as [`BmaNetwork::evaluate`], except for
+    /// the valuation being incomplete.
+    pub fn evaluate_partial(
+        &self,
+        var_id: u32,
+        partial: &BTreeMap<u32, u32>,
+        default_level: u32,
+    ) -> anyhow::Result<u32> {
+        let target_var = self
+            .find_variable(var_id)
+            .ok_or_else(|| anyhow!("Target variable with id `{var_id}` not found"))?;
+
+        let mut valuation = partial.clone();
+        if let Some(Ok(function)) = &target_var.formula {
+            for source_id in function.variables_in_order() {
+                if valuation.contains_key(&source_id) {
+                    continue;
+                }
+                let source_var = self
+                    .find_variable(source_id)
+                    .ok_or_else(|| anyhow!("Source variable with id `{source_id}` not found"))?;
+                let level = default_level.clamp(source_var.min_level(), source_var.max_level());
+                valuation.insert(source_id, level);
+            }
+        }
+
+        self.evaluate(var_id, &valuation)
+    }
+
+    /// Evaluate the update function of the given variable for each of the given `valuations`.
+    ///
+    /// The result is the same as calling [`BmaNetwork::evaluate`] for each valuation, but the
+    /// update function and the variable lookups are only resolved once for the whole batch.
+    /// The operation fails under the same conditions as [`BmaNetwork::evaluate`], in which
+    /// case no partial results are returned.
+    pub fn evaluate_batch(
+        &self,
+        var_id: u32,
+        valuations: &[BTreeMap<u32, u32>],
+    ) -> anyhow::Result<Vec<u32>> {
+        let target_var = self
+            .find_variable(var_id)
+            .ok_or_else(|| anyhow!("Target variable with id `{var_id}` not found"))?;
+
+        let Some(function) = &target_var.formula else {
+            return Err(anyhow!("No update function found for `{var_id}`"));
+        };
+        let function = function.as_ref().map_err(|e| anyhow!(e.to_string()))?;
+
+        let mut sources: HashMap<u32, &BmaVariable> = HashMap::new();
+        let mut results = Vec::with_capacity(valuations.len());
+        for valuation in valuations {
+            let mut normalized_valuation = BTreeMap::new();
+            for (source_id, level) in valuation {
+                let source_var = if let Some(source_var) = sources.get(source_id) {
+                    *source_var
+                } else {
+                    let source_var = self.find_variable(*source_id).ok_or_else(|| {
+                        anyhow!("Source variable with id `{source_id}` not found")
+                    })?;
+                    sources.insert(*source_id, source_var);
+                    source_var
+                };
+                let normalized_level = target_var.normalize_input_level(source_var, *level);
+                normalized_valuation.insert(*source_id, normalized_level);
+            }
+
+            let raw_result = self.evaluate_function(function, &normalized_valuation)?;
+            results.push(target_var.normalize_output_level(raw_result));
+        }
+
+        Ok(results)
+    }
+
+    /// Evaluate the given `function` in a normalized `valuation` (see
+    /// [`BmaUpdateFunction::evaluate_raw`]), respecting the `avg_integer_semantics` flag
+    /// of this network.
+    fn evaluate_function(
+        &self,
+        function: &BmaUpdateFunction,
+        valuation: &BTreeMap<u32, Decimal>,
+    ) -> anyhow::Result<Decimal> {
+        function.evaluate_with(
+            &|var_id| valuation.get(&var_id).copied(),
+            self.avg_integer_semantics,
+        )
+    }
+
+    /// Compute the normalized input valuation that [`BmaNetwork::evaluate`] passes to
+    /// [`BmaUpdateFunction::evaluate_raw`] when evaluating the update function of `target`.
+    ///
+    /// Every level in `valuation` is converted to the range of the `target` variable using
+    /// [`BmaVariable::normalize_input_level`]. This is mostly useful for checking the range
+    /// conversion against known BMA outputs (e.g., the examples in the BMA FAQ).
+    ///
+    /// The operation fails if the `target` variable, or any of the variables referenced
+    /// in `valuation`, does not exist in this network.
+    pub fn normalized_valuation(
+        &self,
+        target: u32,
+        valuation: &BTreeMap<u32, u32>,
+    ) -> anyhow::Result<BTreeMap<u32, Decimal>> {
+        let target_var = self
+            .find_variable(target)
+            .ok_or_else(|| anyhow!("Target variable with id `{target}` not found"))?;
+
         let mut normalized_valuation = BTreeMap::new();
         for (source_id, level) in valuation {
             let source_var = self
@@ -49,13 +191,7 @@ impl BmaNetwork {
             normalized_valuation.insert(*source_id, normalized_level);
         }
 
-        if let Some(function) = &target_var.formula {
-            let function = function.as_ref().map_err(|e| anyhow!(e.to_string()))?;
-            let raw_result = function.evaluate_raw(&normalized_valuation)?;
-            Ok(target_var.normalize_output_level(raw_result))
-        } else {
-            Err(anyhow!("No update function found for `{var_id}`"))
-        }
+        Ok(normalized_valuation)
     }
 
     /// Build a complete [`FunctionTable`] with all input-output combinations.
@@ -96,30 +232,30 @@ impl BmaNetwork {
         }
 
         if target_var.has_constant_range() {
-            // For constant variables, the update function is built a bit differently, because
-            // we technically allow them to be 0 even if that value is outside variable range.
-
-            let const_level = target_var.min_level();
-            let output = match function.as_constant() {
-                Some(value) => {
-                    let Ok(value) = u32::try_from(value) else {
-                        return Err(anyhow!("Constant value cannot be negative."));
-                    };
-                    if value == 0 || value == const_level {
-                        value
-                    } else {
-                        return Err(anyhow!("Constant value does not match variable level."));
-                    }
-                }
-                // I would consider this an invalid model, but BMA seems to think this is fine,
-                // so we will accept this as valid, even though validation will report it as
-                // an issue.
-                _ => const_level,
-            };
-
+            let output = target_var.constant_output_level(&function)?;
             Ok(vec![(BTreeMap::new(), output)])
         } else {
-            target_var.build_function_table(&function, &regulators_map)
+            target_var.build_function_table(&function, &regulators_map, self.avg_integer_semantics)
+        }
+    }
+
+    /// Check whether the update function of the given variable always produces the same
+    /// output level, regardless of the levels of its regulators. If this is the case,
+    /// the function returns `Some(level)`, otherwise it returns `None`.
+    ///
+    /// Unlike [`BmaVariable::has_constant_range`], this is a semantic check performed on the
+    /// [`FunctionTable`] of the variable (see [`BmaNetwork::build_function_table`], which
+    /// also describes the possible error states).
+    pub fn is_effectively_constant(&self, var_id: u32) -> anyhow::Result<Option<u32>> {
+        let table = self.build_function_table(var_id)?;
+        let mut outputs = table.iter().map(|(_, output)| *output);
+        let Some(first) = outputs.next() else {
+            return Ok(None);
+        };
+        if outputs.all(|output| output == first) {
+            Ok(Some(first))
+        } else {
+            Ok(None)
         }
     }
 }
@@ -130,70 +266,141 @@ impl BmaVariable {
     /// [`BmaUpdateFunction::evaluate_raw`]).
     #[must_use]
     pub fn normalize_input_level(&self, input: &BmaVariable, value: u32) -> Decimal {
-        // input \in [a,b]
-        // self \in [c,d]
-        // (value-a)*(d-c)/(b-a)+c
-        let value = i64::from(value);
-        let (a, b) = (i64::from(input.min_level()), i64::from(input.max_level()));
-        let (c, d) = (i64::from(self.min_level()), i64::from(self.max_level()));
-
-        if a == b {
-            // For constants, the value is always taken as is.
-            return Decimal::from(value);
+        normalize_level(value, input.range, self.range)
+    }
+
+    /// Compute the output level of a variable with a constant range (see
+    /// [`BmaVariable::has_constant_range`]) when it is updated using the given `function`.
+    ///
+    /// For constant variables, the output is computed a bit differently, because
+    /// we technically allow them to be 0 even if that value is outside variable range.
+    pub(crate) fn constant_output_level(
+        &self,
+        function: &BmaUpdateFunction,
+    ) -> anyhow::Result<u32> {
+        let const_level = self.min_level();
+        match function.as_constant() {
+            Some(value) => {
+                let Ok(value) = u32::try_from(value) else {
+                    return Err(anyhow!("Constant value cannot be negative."));
+                };
+                if value == 0 || value == const_level {
+                    Ok(value)
+                } else {
+                    Err(anyhow!("Constant value does not match variable level."))
+                }
+            }
+            // I would consider this an invalid model, but BMA seems to think this is fine,
+            // so we will accept this as valid, even though validation will report it as
+            // an issue.
+            _ => Ok(const_level),
         }
-        let numerator = (value - a) * (d - c);
-        let denominator = b - a;
-        (Decimal::from(numerator) / Decimal::from(denominator)) + Decimal::from(c)
     }
 
     /// Normalize the output level of this variable. This means (a) round the output correctly,
     /// (b) truncate it to the range of this variable.
     #[must_use]
     pub fn normalize_output_level(&self, value: Decimal) -> u32 {
-        let (low, high) = (i64::from(self.min_level()), i64::from(self.max_level()));
-        // BMA seems to be using round half up / round half away from zero convention, which
-        // is also implemented here. However, if you see any weird behavior in your results,
-        // it may be good to make sure this is actually the correct rounding.
-        let raw_result = value.round_dp_with_strategy(0, MidpointAwayFromZero);
-        let raw_result = i64::try_from(raw_result)
-            .expect("Invariant violation: Rounded output level is not a 64-bit number.");
-
-        // The `u32` conversion must succeed because the number now fits into the bounds
-        // of the variable, which are defined as `u32` values.
-        let trunc_result = max(min(raw_result, high), low);
-        u32::try_from(trunc_result).expect("Invariant violation: Result must fit into `u32`")
+        normalize_output(value, self.range)
     }
 
     /// Internal version of [`BmaModel::build_function_table`] which assumes you already have
-    /// some of the inputs pre-computed.
+    /// some of the inputs pre-computed. If `integer_avg` is set, the results of `avg`
+    /// aggregations are truncated (see [`BmaNetwork`]).
+    ///
+    /// If this variable and all its regulators are Boolean, and the `function` only uses
+    /// operations that are closed on integers, the table is computed using integer arithmetic
+    /// (see [`BmaUpdateFunction::evaluate_integer`]). The result is the same as for the
+    /// general [`Decimal`] evaluation.
     pub(crate) fn build_function_table(
         &self,
         function: &BmaUpdateFunction,
         regulators_map: &BTreeMap<u32, &BmaVariable>,
+        integer_avg: bool,
+    ) -> anyhow::Result<FunctionTable> {
+        let is_boolean = self.is_boolean() && regulators_map.values().all(|r| r.is_boolean());
+        if is_boolean && function.has_integer_semantics(integer_avg) {
+            self.build_boolean_function_table(function, regulators_map, integer_avg)
+        } else {
+            self.build_general_function_table(function, regulators_map, integer_avg)
+        }
+    }
+
+    /// The fast path of [`BmaVariable::build_function_table`]: Boolean inputs need no
+    /// normalization, so the function can be evaluated directly on the input levels. Rows where
+    /// integer evaluation fails (e.g. missing inputs or overflow) fall back to the general path,
+    /// such that results and errors are always the same.
+    fn build_boolean_function_table(
+        &self,
+        function: &BmaUpdateFunction,
+        regulators_map: &BTreeMap<u32, &BmaVariable>,
+        integer_avg: bool,
     ) -> anyhow::Result<FunctionTable> {
         let regulators: Vec<_> = regulators_map.values().copied().collect();
 
         let mut table = Vec::new();
         for valuation in generate_input_valuations(&regulators) {
-            let mut normalized_valuation = BTreeMap::new();
-            for (source_id, level) in &valuation {
-                let source_var = regulators_map
-                    .get(source_id)
-                    .expect("Invariant violation: Invalid regulator");
-                let normalized_level = self.normalize_input_level(source_var, *level);
-                normalized_valuation.insert(*source_id, normalized_level);
-            }
-
-            let raw_result = match function.evaluate_raw(&normalized_valuation) {
-                Ok(result) => result,
-                Err(e) => return Err(anyhow!("Cannot evaluate {function} in {valuation:?}: {e}")),
+            let lookup = |var_id| valuation.get(&var_id).map(|level| i64::from(*level));
+            let output = match function.evaluate_integer(&lookup, integer_avg) {
+                Some(result) => self.normalize_output_level(Decimal::from(result)),
+                None => {
+                    self.evaluate_table_row(function, regulators_map, &valuation, integer_avg)?
+                }
             };
+            table.push((valuation, output));
+        }
 
-            table.push((valuation, self.normalize_output_level(raw_result)));
+        Ok(table)
+    }
+
+    /// The general path of [`BmaVariable::build_function_table`], which evaluates
+    /// the function on normalized [`Decimal`] input levels.
+    fn build_general_function_table(
+        &self,
+        function: &BmaUpdateFunction,
+        regulators_map: &BTreeMap<u32, &BmaVariable>,
+        integer_avg: bool,
+    ) -> anyhow::Result<FunctionTable> {
+        let regulators: Vec<_> = regulators_map.values().copied().collect();
+
+        let mut table = Vec::new();
+        for valuation in generate_input_valuations(&regulators) {
+            let output =
+                self.evaluate_table_row(function, regulators_map, &valuation, integer_avg)?;
+            table.push((valuation, output));
         }
 
         Ok(table)
     }
+
+    /// Compute the output level of a single [`FunctionTable`] row using [`Decimal`] evaluation.
+    fn evaluate_table_row(
+        &self,
+        function: &BmaUpdateFunction,
+        regulators_map: &BTreeMap<u32, &BmaVariable>,
+        valuation: &BTreeMap<u32, u32>,
+        integer_avg: bool,
+    ) -> anyhow::Result<u32> {
+        let mut normalized_valuation = BTreeMap::new();
+        for (source_id, level) in valuation {
+            let source_var = regulators_map
+                .get(source_id)
+                .expect("Invariant violation: Invalid regulator");
+            let normalized_level = self.normalize_input_level(source_var, *level);
+            normalized_valuation.insert(*source_id, normalized_level);
+        }
+
+        let lookup = |var_id| normalized_valuation.get(&var_id).copied();
+        match function.evaluate_with(&lookup, integer_avg) {
+            Ok(result) => Ok(self.normalize_output_level(result)),
+            Err(e) if e.is::<DivisionByZero>() => Err(DivisionByZeroError {
+                expression: function.to_string(),
+                valuation: valuation.clone(),
+            }
+            .into()),
+            Err(e) => Err(anyhow!("Cannot evaluate {function} in {valuation:?}: {e}")),
+        }
+    }
 }
 
 impl BmaUpdateFunction {
@@ -224,6 +431,71 @@ impl BmaUpdateFunction {
         result
     }
 
+    /// Collect all variable IDs used in this BMA function expression, in the order in which
+    /// they first appear in the expression (from left to right). Each ID is listed only once.
+    ///
+    /// Unlike [`BmaUpdateFunction::collect_variables`], the result is deterministic and follows
+    /// the way the expression is written, which is preferable e.g. for rendering.
+    #[must_use]
+    pub fn variables_in_order(&self) -> Vec<u32> {
+        fn collect_rec(function: &BmaUpdateFunction, result: &mut Vec<u32>) {
+            match &function.as_data() {
+                Terminal(Literal::Var(var_id)) => {
+                    if !result.contains(var_id) {
+                        result.push(*var_id);
+                    }
+                }
+                Terminal(Literal::Const(_)) => (),
+                BmaExpressionNodeData::Arithmetic(_, left, right) => {
+                    collect_rec(left, result);
+                    collect_rec(right, result);
+                }
+                BmaExpressionNodeData::Unary(_, child_node) => collect_rec(child_node, result),
+                BmaExpressionNodeData::Aggregation(_, arguments) => {
+                    for arg in arguments {
+                        collect_rec(arg, result);
+                    }
+                }
+            }
+        }
+
+        let mut result = Vec::new();
+        collect_rec(self, &mut result);
+        result
+    }
+
+    /// The depth of the expression tree. A terminal (variable or constant) has depth `1`.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        let children_depth = match self.as_data() {
+            Terminal(_) => 0,
+            BmaExpressionNodeData::Arithmetic(_, left, right) => left.depth().max(right.depth()),
+            BmaExpressionNodeData::Unary(_, child_node) => child_node.depth(),
+            BmaExpressionNodeData::Aggregation(_, arguments) => arguments
+                .iter()
+                .map(BmaUpdateFunction::depth)
+                .max()
+                .unwrap_or(0),
+        };
+        children_depth + 1
+    }
+
+    /// The total number of nodes in the expression tree (terminals, operators, and functions).
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        let children_count = match self.as_data() {
+            Terminal(_) => 0,
+            BmaExpressionNodeData::Arithmetic(_, left, right) => {
+                left.node_count() + right.node_count()
+            }
+            BmaExpressionNodeData::Unary(_, child_node) => child_node.node_count(),
+            BmaExpressionNodeData::Aggregation(_, arguments) => {
+                arguments.iter().map(BmaUpdateFunction::node_count).sum()
+            }
+        };
+        children_count + 1
+    }
+
     /// Raw evaluation function which returns the rational value of the function expression
     /// without truncation to the valid variable interval. The function expects the valuation
     /// to be in the "normalized" format (the level of each variable is adjusted to the
@@ -256,11 +528,94 @@ impl BmaUpdateFunction {
     ///
     /// See also [`BmaNetwork::evaluate`].
     pub fn evaluate_raw(&self, valuation: &BTreeMap<u32, Decimal>) -> anyhow::Result<Decimal> {
+        self.evaluate_with(&|var_id| valuation.get(&var_id).copied(), false)
+    }
+
+    /// Check whether the result of this function is always an integer when all inputs are
+    /// integers. This is the case unless the function uses division, or `avg` without
+    /// `integer_avg` semantics (see [`BmaNetwork`]).
+    pub(crate) fn has_integer_semantics(&self, integer_avg: bool) -> bool {
+        match self.as_data() {
+            Terminal(_) => true,
+            BmaExpressionNodeData::Arithmetic(ArithOp::Div, _, _) => false,
+            BmaExpressionNodeData::Arithmetic(_, left, right) => {
+                left.has_integer_semantics(integer_avg) && right.has_integer_semantics(integer_avg)
+            }
+            BmaExpressionNodeData::Unary(_, child_node) => {
+                child_node.has_integer_semantics(integer_avg)
+            }
+            BmaExpressionNodeData::Aggregation(AggregateFn::Avg, _) if !integer_avg => false,
+            BmaExpressionNodeData::Aggregation(_, arguments) => arguments
+                .iter()
+                .all(|arg| arg.has_integer_semantics(integer_avg)),
+        }
+    }
+
+    /// Integer version of [`BmaUpdateFunction::evaluate_with`] for functions with
+    /// [`BmaUpdateFunction::has_integer_semantics`]. Returns `None` if the function cannot
+    /// be evaluated this way (an input is missing, the function has no integer semantics,
+    /// or the computation overflows), in which case [`Decimal`] evaluation should be used.
+    pub(crate) fn evaluate_integer<F: Fn(u32) -> Option<i64>>(
+        &self,
+        lookup: &F,
+        integer_avg: bool,
+    ) -> Option<i64> {
+        match self.as_data() {
+            Terminal(Literal::Const(value)) => Some(i64::from(*value)),
+            Terminal(Literal::Var(var_id)) => lookup(*var_id),
+            BmaExpressionNodeData::Arithmetic(operator, left, right) => {
+                let left_value = left.evaluate_integer(lookup, integer_avg)?;
+                let right_value = right.evaluate_integer(lookup, integer_avg)?;
+                match operator {
+                    ArithOp::Plus => left_value.checked_add(right_value),
+                    ArithOp::Minus => left_value.checked_sub(right_value),
+                    ArithOp::Mult => left_value.checked_mul(right_value),
+                    ArithOp::Div => None,
+                }
+            }
+            BmaExpressionNodeData::Unary(function, child_node) => {
+                let child_value = child_node.evaluate_integer(lookup, integer_avg)?;
+                match function {
+                    UnaryFn::Abs => child_value.checked_abs(),
+                    UnaryFn::Ceil | UnaryFn::Floor => Some(child_value),
+                    UnaryFn::Neg => child_value.checked_neg(),
+                }
+            }
+            BmaExpressionNodeData::Aggregation(function, arguments) => {
+                let mut arg_values = Vec::with_capacity(arguments.len());
+                for arg in arguments {
+                    arg_values.push(arg.evaluate_integer(lookup, integer_avg)?);
+                }
+                match function {
+                    // Integer division truncates towards zero, the same as `Decimal::trunc`.
+                    AggregateFn::Avg if integer_avg => {
+                        let count = i64::try_from(arg_values.len()).ok()?;
+                        let sum = arg_values
+                            .iter()
+                            .try_fold(0i64, |sum, value| sum.checked_add(*value))?;
+                        sum.checked_div(count)
+                    }
+                    AggregateFn::Avg => None,
+                    AggregateFn::Max => arg_values.iter().copied().max(),
+                    AggregateFn::Min => arg_values.iter().copied().min(),
+                }
+            }
+        }
+    }
+
+    /// Internal version of [`BmaUpdateFunction::evaluate_raw`] where the (normalized) variable
+    /// values are provided by a `lookup` function instead of a map. If `integer_avg` is set,
+    /// the results of `avg` aggregations are truncated to integers (see [`BmaNetwork`]).
+    pub(crate) fn evaluate_with<F: Fn(u32) -> Option<Decimal>>(
+        &self,
+        lookup: &F,
+        integer_avg: bool,
+    ) -> anyhow::Result<Decimal> {
         match &self.as_data() {
             Terminal(Literal::Const(value)) => Ok(Decimal::from(*value)),
             Terminal(Literal::Var(var_id)) => {
-                if let Some(value) = valuation.get(var_id) {
-                    Ok(*value)
+                if let Some(value) = lookup(*var_id) {
+                    Ok(value)
                 } else {
                     Err(anyhow!(format!(
                         "Missing input value for variable `{var_id}`"
@@ -268,15 +623,15 @@ impl BmaUpdateFunction {
                 }
             }
             BmaExpressionNodeData::Arithmetic(operator, left, right) => {
-                let left_value = left.evaluate_raw(valuation)?;
-                let right_value = right.evaluate_raw(valuation)?;
+                let left_value = left.evaluate_with(lookup, integer_avg)?;
+                let right_value = right.evaluate_with(lookup, integer_avg)?;
                 let res = match operator {
                     ArithOp::Plus => left_value + right_value,
                     ArithOp::Minus => left_value - right_value,
                     ArithOp::Mult => left_value * right_value,
                     ArithOp::Div => {
                         if right_value == Decimal::zero() {
-                            return Err(anyhow!("Division by zero"));
+                            return Err(DivisionByZero.into());
                         }
                         left_value / right_value
                     }
@@ -284,7 +639,7 @@ impl BmaUpdateFunction {
                 Ok(res)
             }
             BmaExpressionNodeData::Unary(function, child_node) => {
-                let child_value = child_node.evaluate_raw(valuation)?;
+                let child_value = child_node.evaluate_with(lookup, integer_avg)?;
                 let res = match function {
                     UnaryFn::Abs => child_value.abs(),
                     UnaryFn::Ceil => child_value.ceil(),
@@ -301,14 +656,15 @@ impl BmaUpdateFunction {
                 }
                 let arg_values = arguments
                     .iter()
-                    .map(|arg| arg.evaluate_raw(valuation))
+                    .map(|arg| arg.evaluate_with(lookup, integer_avg))
                     .collect::<anyhow::Result<Vec<_>>>()?;
                 let res = match function {
                     AggregateFn::Avg => {
                         let count = i64::try_from(arg_values.len())
                             .expect("Invariant violation: Number of arguments is too large.");
                         let sum: Decimal = arg_values.iter().copied().sum();
-                        sum / Decimal::from(count)
+                        let avg = sum / Decimal::from(count);
+                        if integer_avg { avg.trunc() } else { avg }
                     }
                     AggregateFn::Max => arg_values
                         .iter()
@@ -327,15 +683,52 @@ impl BmaUpdateFunction {
     }
 }
 
+/// Convert `value` from the `input` range to the `target` range using the BMA range
+/// conversion formula (see [`BmaVariable::normalize_input_level`]).
+pub(crate) fn normalize_level(value: u32, input: (u32, u32), target: (u32, u32)) -> Decimal {
+    // input \in [a,b]
+    // target \in [c,d]
+    // (value-a)*(d-c)/(b-a)+c
+    // Using `i128` ensures the product below cannot overflow for any `u32` range.
+    let value = i128::from(value);
+    let (a, b) = (i128::from(input.0), i128::from(input.1));
+    let (c, d) = (i128::from(target.0), i128::from(target.1));
+
+    if a == b {
+        // For constants, the value is always taken as is.
+        return Decimal::from(value);
+    }
+    let numerator = (value - a) * (d - c);
+    let denominator = b - a;
+    (Decimal::from(numerator) / Decimal::from(denominator)) + Decimal::from(c)
+}
+
+/// Round the given output `value` and truncate it to the `target` range
+/// (see [`BmaVariable::normalize_output_level`]).
+pub(crate) fn normalize_output(value: Decimal, target: (u32, u32)) -> u32 {
+    let (low, high) = (i64::from(target.0), i64::from(target.1));
+    // BMA seems to be using round half up / round half away from zero convention, which
+    // is also implemented here. However, if you see any weird behavior in your results,
+    // it may be good to make sure this is actually the correct rounding.
+    let raw_result = value.round_dp_with_strategy(0, MidpointAwayFromZero);
+    let raw_result = i64::try_from(raw_result)
+        .expect("Invariant violation: Rounded output level is not a 64-bit number.");
+
+    // The `u32` conversion must succeed because the number now fits into the bounds
+    // of the variable, which are defined as `u32` values.
+    let trunc_result = max(min(raw_result, high), low);
+    u32::try_from(trunc_result).expect("Invariant violation: Result must fit into `u32`")
+}
+
 /// Generate all possible input combinations for the given variables, respecting their
 /// possible levels.
 ///
 /// This function can handle multivalued variables (arg `max_levels` specifies maximum
 /// level for each variable).
 ///
-/// The valuations are generated starting at 0, and going up to the maximum level, last
-/// variable first. For instance, in binary case, valuations are generated in the order:
-/// 00, 01, 10, 11.
+/// The valuations are generated starting at the minimum level of each variable (which does
+/// not have to be 0), and going up to the maximum level, last variable first. For instance,
+/// in binary case, valuations are generated in the order: 00, 01, 10, 11.
 fn generate_input_valuations(variables: &[&BmaVariable]) -> Vec<BTreeMap<u32, u32>> {
     fn generate_input_valuations_rec(
         variables: &[&BmaVariable],
@@ -349,7 +742,7 @@ fn generate_input_valuations(variables: &[&BmaVariable]) -> Vec<BTreeMap<u32, u3
 
         let variable = &variables[0];
 
-        for level in variable.min_level()..=variable.max_level() {
+        for level in variable.levels() {
             current.insert(variable.id, level);
             generate_input_valuations_rec(&variables[1..], current, results);
         }
@@ -366,7 +759,8 @@ fn generate_input_valuations(variables: &[&BmaVariable]) -> Vec<BTreeMap<u32, u3
 mod tests {
     use crate::update_function::expression_parser::parse_bma_formula;
     use crate::update_function::tests::{and_model, complex_model};
-    use crate::update_function::{BmaUpdateFunction, FunctionTable};
+    use crate::update_function::{BmaUpdateFunction, DivisionByZeroError, FunctionTable};
+    use crate::{BmaNetwork, BmaRelationship, BmaVariable};
     use rust_decimal::Decimal;
     use std::collections::{BTreeMap, HashSet};
 
@@ -375,6 +769,19 @@ mod tests {
         Decimal::from(x)
     }
 
+    #[test]
+    fn test_depth_and_node_count() {
+        let expression = parse_bma_formula("3", &[]).unwrap();
+        assert_eq!((expression.depth(), expression.node_count()), (1, 1));
+
+        let expression = parse_bma_formula("var(1) + (1 - min(var(2), var(3), 1))", &[]).unwrap();
+        assert_eq!(expression.depth(), 4);
+        assert_eq!(expression.node_count(), 8);
+
+        let expression = parse_bma_formula("abs(-var(1))", &[]).unwrap();
+        assert_eq!((expression.depth(), expression.node_count()), (3, 3));
+    }
+
     #[test]
     fn test_collect_variables() {
         let vars = vec![
@@ -398,6 +805,16 @@ mod tests {
         assert_eq!(expression.collect_variables(), HashSet::from([2, 3]));
     }
 
+    #[test]
+    fn test_variables_in_order() {
+        let vars = vec![(1, "a".to_string()), (2, "b".to_string())];
+        let expression =
+            parse_bma_formula("var(3) + max(var(b), var(3), 2) - var(a) * var(2)", &vars).unwrap();
+        assert_eq!(expression.variables_in_order(), vec![3, 2, 1]);
+        let expression = parse_bma_formula("1 - 2", &vars).unwrap();
+        assert!(expression.variables_in_order().is_empty());
+    }
+
     #[test]
     fn test_evaluate_terminal_str() {
         let vars = vec![(1, "x".to_string())];
@@ -479,6 +896,251 @@ mod tests {
         assert_eq!(result_table, expected_table);
     }
 
+    #[test]
+    fn test_evaluate_batch() {
+        let model = complex_model();
+        let valuations: Vec<BTreeMap<u32, u32>> = (0..8u32)
+            .map(|i| BTreeMap::from([(1, i & 1), (2, (i >> 1) & 1), (3, (i >> 2) & 1)]))
+            .collect();
+
+        let batch = model.network.evaluate_batch(1, &valuations).unwrap();
+        let expected = valuations
+            .iter()
+            .map(|v| model.network.evaluate(1, v).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(batch, expected);
+
+        assert!(model.network.evaluate_batch(1, &[]).unwrap().is_empty());
+        // Variable `2` has no update function.
+        assert!(model.network.evaluate_batch(2, &valuations).is_err());
+        // Unknown variable in one of the valuations.
+        let invalid = vec![valuations[0].clone(), BTreeMap::from([(5, 0)])];
+        assert!(model.network.evaluate_batch(1, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_partial() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "x", (0, 2), None),
+                BmaVariable::new(2, "y", (1, 2), None),
+                BmaVariable::new(3, "z", (0, 4), None),
+            ],
+            vec![],
+        );
+        network.variables[2].formula = Some(BmaUpdateFunction::try_from("var(1) + var(2)"));
+        let partial = BTreeMap::from([(1, 2)]);
+        assert!(network.evaluate(3, &partial).is_err());
+
+        // Missing `y` is set to its minimal level.
+        let full = BTreeMap::from([(1, 2), (2, 1)]);
+        let expected = network.evaluate(3, &full).unwrap();
+        assert_eq!(network.evaluate_partial(3, &partial, 0).unwrap(), expected);
+        // The default level is clamped to the maximal level of `x` and `y`.
+        let full = BTreeMap::from([(1, 2), (2, 2)]);
+        let expected = network.evaluate(3, &full).unwrap();
+        assert_eq!(
+            network.evaluate_partial(3, &BTreeMap::new(), 10).unwrap(),
+            expected
+        );
+        assert!(network.evaluate_partial(4, &partial, 0).is_err());
+    }
+
+    #[test]
+    fn test_is_effectively_constant() {
+        let model = complex_model();
+        assert_eq!(model.network.is_effectively_constant(1).unwrap(), None);
+
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "x", (0, 2), None),
+                BmaVariable::new(2, "y", (0, 2), None),
+                BmaVariable::new(3, "c", (1, 1), None),
+            ],
+            vec![
+                BmaRelationship::new_activator(10, 1, 2),
+                BmaRelationship::new_activator(11, 1, 1),
+            ],
+        );
+        network.variables[1].formula = Some(BmaUpdateFunction::try_from("min(var(1), 0)"));
+        network.variables[0].formula = Some(BmaUpdateFunction::try_from("var(1)"));
+
+        assert_eq!(network.is_effectively_constant(2).unwrap(), Some(0));
+        assert_eq!(network.is_effectively_constant(1).unwrap(), None);
+        // The default function of a variable without regulators is the constant `0`.
+        assert_eq!(network.is_effectively_constant(3).unwrap(), Some(0));
+        assert!(network.is_effectively_constant(4).is_err());
+    }
+
+    #[test]
+    fn test_normalized_valuation() {
+        // The FAQ example: `x` in [0,1] activates `y` in [0,2]; `c` is a constant [3,3].
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "x", (0, 1), None),
+                BmaVariable::new(2, "y", (0, 2), None),
+                BmaVariable::new(3, "c", (3, 3), None),
+                BmaVariable::new(4, "z", (1, 3), None),
+            ],
+            vec![],
+        );
+
+        let valuation = BTreeMap::from([(1, 1), (2, 1), (3, 3), (4, 2)]);
+        let normalized = network.normalized_valuation(2, &valuation).unwrap();
+        // (1-0)*(2-0)/(1-0)+0 = 2
+        assert_eq!(normalized[&1], d(2));
+        // Levels of the target itself are unchanged.
+        assert_eq!(normalized[&2], d(1));
+        // Constants are not converted.
+        assert_eq!(normalized[&3], d(3));
+        // (2-1)*(2-0)/(3-1)+0 = 1
+        assert_eq!(normalized[&4], d(1));
+
+        // Conversion in the other direction can produce non-integer levels.
+        let normalized = network.normalized_valuation(1, &valuation).unwrap();
+        assert_eq!(normalized[&2], Decimal::new(5, 1));
+
+        assert!(network.normalized_valuation(5, &valuation).is_err());
+        let invalid = BTreeMap::from([(7, 0)]);
+        assert!(network.normalized_valuation(1, &invalid).is_err());
+    }
+
+    #[test]
+    fn function_tables_with_non_zero_min_level() {
+        let f = |s: &str| Some(BmaUpdateFunction::try_from(s).unwrap());
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (1, 3), None),
+                BmaVariable::new(2, "b", (0, 1), f("var(1)")),
+                BmaVariable::new(3, "c", (1, 3), f("var(2)")),
+            ],
+            vec![
+                BmaRelationship::new_activator(1, 1, 2),
+                BmaRelationship::new_activator(2, 2, 3),
+            ],
+        );
+
+        // The default function of `a` is constant zero, which is truncated to level `1`.
+        let table = network.build_function_table(1).unwrap();
+        assert_eq!(table, vec![(BTreeMap::new(), 1)]);
+
+        // Inputs of `a` range over `1..=3` and are normalized to `0`, `0.5`, and `1`.
+        let table = network.build_function_table(2).unwrap();
+        let expected = vec![
+            (BTreeMap::from([(1, 1)]), 0),
+            (BTreeMap::from([(1, 2)]), 1),
+            (BTreeMap::from([(1, 3)]), 1),
+        ];
+        assert_eq!(table, expected);
+
+        // Inputs of `b` are normalized to levels `1` and `3`.
+        let table = network.build_function_table(3).unwrap();
+        let expected = vec![(BTreeMap::from([(2, 0)]), 1), (BTreeMap::from([(2, 1)]), 3)];
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn integer_avg_semantics() {
+        let f = |s: &str| Some(BmaUpdateFunction::try_from(s).unwrap());
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "x", (0, 2), None),
+                BmaVariable::new(2, "y", (0, 2), f("avg(var(1), 0) * 2")),
+            ],
+            vec![BmaRelationship::new_activator(1, 1, 2)],
+        );
+        let valuation = BTreeMap::from([(1, 1)]);
+
+        // By default, `avg(1, 0) = 0.5`, hence the output is `1`.
+        assert!(!network.avg_integer_semantics());
+        assert_eq!(network.evaluate(2, &valuation).unwrap(), 1);
+        let exact = network.build_function_table(2).unwrap();
+        assert_eq!(exact.iter().map(|(_, o)| *o).collect::<Vec<_>>(), [0, 1, 2]);
+
+        // With integer semantics, `avg(1, 0) = 0`, hence the output is `0`.
+        network.set_avg_integer_semantics(true);
+        assert_eq!(network.evaluate(2, &valuation).unwrap(), 0);
+        assert_eq!(network.evaluate_batch(2, &[valuation]).unwrap(), [0]);
+        let table = network.build_function_table(2).unwrap();
+        assert_eq!(table.iter().map(|(_, o)| *o).collect::<Vec<_>>(), [0, 0, 2]);
+        assert_eq!(network.compile_variable(2).unwrap().eval(&[1]), 0);
+
+        // The flag is not serialized.
+        let json = serde_json::to_string(&network).unwrap();
+        assert!(!json.contains("avg_integer_semantics"));
+        let network = serde_json::from_str::<BmaNetwork>(&json).unwrap();
+        assert!(!network.avg_integer_semantics());
+    }
+
+    #[test]
+    fn boolean_fast_path_matches_general_path() {
+        let regulators = (1..=12)
+            .map(|id| BmaVariable::new_boolean(id, &format!("v_{id}"), None))
+            .collect::<Vec<_>>();
+        let regulators_map = regulators
+            .iter()
+            .map(|v| (v.id, v))
+            .collect::<BTreeMap<_, _>>();
+        let target = BmaVariable::new_boolean(0, "target", None);
+
+        let formulas = [
+            "max(var(1), var(2), var(3)) - min(var(4), var(5)) + var(6) * var(7)",
+            "abs(var(8) - var(9) - var(10)) * ceil(var(11)) - floor(var(12))",
+            "avg(var(1), var(2), var(3), var(4), var(5), var(6), var(7), var(8))",
+            "-var(1) + 2 * var(12)",
+            "var(1) + var(13)",
+        ];
+        for formula in formulas {
+            let function = BmaUpdateFunction::try_from(formula).unwrap();
+            for integer_avg in [false, true] {
+                let fast =
+                    target.build_boolean_function_table(&function, &regulators_map, integer_avg);
+                let general =
+                    target.build_general_function_table(&function, &regulators_map, integer_avg);
+                match (fast, general) {
+                    (Ok(fast), Ok(general)) => {
+                        assert_eq!(fast.len(), 1 << 12);
+                        assert_eq!(fast, general);
+                    }
+                    (Err(fast), Err(general)) => assert_eq!(fast.to_string(), general.to_string()),
+                    _ => panic!("Fast and general paths differ for `{formula}`."),
+                }
+            }
+        }
+
+        let avg = BmaUpdateFunction::try_from("avg(var(1), var(2))").unwrap();
+        assert!(avg.has_integer_semantics(true));
+        assert!(!avg.has_integer_semantics(false));
+        assert!(
+            !BmaUpdateFunction::try_from("var(1) / 2")
+                .unwrap()
+                .has_integer_semantics(true)
+        );
+    }
+
+    #[test]
+    fn division_by_zero_valuation() {
+        let function = BmaUpdateFunction::try_from("2 / (var(1) - var(2))").unwrap();
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 2), None),
+                BmaVariable::new(2, "b", (0, 2), None),
+                BmaVariable::new(3, "c", (0, 2), Some(function)),
+            ],
+            vec![
+                BmaRelationship::new_activator(1, 1, 3),
+                BmaRelationship::new_inhibitor(2, 2, 3),
+            ],
+        );
+        let error = network.build_function_table(3).unwrap_err();
+        let error = error.downcast_ref::<DivisionByZeroError>().unwrap();
+        assert_eq!(error.valuation, BTreeMap::from([(1, 0), (2, 0)]));
+        assert_eq!(
+            error.to_string(),
+            "Cannot evaluate (2 / (var(1) - var(2))) in {1: 0, 2: 0}: Division by zero"
+        );
+    }
+
     /// A simple wrapper to easily put together a boolean `FunctionTable` (a truth table).
     /// This is meant to be used for testing purposes.
     ///
@@ -0,0 +1,237 @@
+use crate::BmaNetwork;
+use crate::update_function::BmaUpdateFunction;
+use crate::update_function::bma_update_function_evaluation::{normalize_level, normalize_output};
+use anyhow::anyhow;
+use rust_decimal::Decimal;
+
+/// A pre-processed update function of a single [`crate::BmaVariable`] which can be evaluated
+/// repeatedly without any variable lookups or map allocations.
+///
+/// The inputs of the function are the regulators of the variable (as declared by the
+/// relationships of the [`BmaNetwork`]), sorted by their IDs. The levels of these regulators
+/// are given positionally (see [`CompiledUpdate::regulators`]).
+///
+/// See [`BmaNetwork::compile_variable`] and [`BmaNetwork::evaluate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledUpdate {
+    function: BmaUpdateFunction,
+    target_range: (u32, u32),
+    regulators: Vec<u32>,
+    regulator_ranges: Vec<(u32, u32)>,
+    constant: Option<u32>,
+    integer_avg: bool,
+}
+
+impl BmaNetwork {
+    /// Build a [`CompiledUpdate`] for the given variable.
+    ///
+    /// Similar to [`BmaNetwork::build_function_table`], the inputs are the declared regulators
+    /// of the variable, and if the update function is missing, the "default" update function
+    /// is used instead (see [`BmaNetwork::build_default_update_function`]).
+    ///
+    /// The operation fails if the variable or one of its regulators does not exist, or if
+    /// the update function is in an error state.
+    pub fn compile_variable(&self, var_id: u32) -> anyhow::Result<CompiledUpdate> {
+        let target_var = self
+            .find_variable(var_id)
+            .ok_or_else(|| anyhow!("Target variable with id `{var_id}` not found"))?;
+
+        let function = match &target_var.formula {
+            None => self.build_default_update_function(var_id),
+            Some(function) => function
+                .as_ref()
+                .cloned()
+                .map_err(|e| anyhow!(e.to_string()))?,
+        };
+
+        let mut regulators = Vec::from_iter(self.get_regulators(var_id, &None));
+        regulators.sort_unstable();
+        let mut regulator_ranges = Vec::with_capacity(regulators.len());
+        for id in &regulators {
+            let var = self
+                .find_variable(*id)
+                .ok_or_else(|| anyhow!("Regulator variable `{id}` does not exist"))?;
+            regulator_ranges.push(var.range);
+        }
+
+        let constant = if target_var.has_constant_range() {
+            Some(target_var.constant_output_level(&function)?)
+        } else {
+            None
+        };
+
+        Ok(CompiledUpdate {
+            function,
+            target_range: target_var.range,
+            regulators,
+            regulator_ranges,
+            constant,
+            integer_avg: self.avg_integer_semantics,
+        })
+    }
+
+    /// Compute the output levels of the `target` variable for every level of the given
+    /// `regulator`, i.e. the "response curve" of the target with respect to this regulator.
+    ///
+    /// All other regulators are held at their mid-level, which is the average of their
+    /// minimum and maximum level, rounded down. The result contains `(regulator level, output)`
+    /// pairs, sorted by the regulator level.
+    ///
+    /// The operation fails if `regulator` is not a declared regulator of `target`, or under
+    /// the same conditions as [`BmaNetwork::compile_variable`] and [`CompiledUpdate::try_eval`].
+    pub fn response_curve(&self, target: u32, regulator: u32) -> anyhow::Result<Vec<(u32, u32)>> {
+        let compiled = self.compile_variable(target)?;
+        let Ok(index) = compiled.regulators.binary_search(&regulator) else {
+            return Err(anyhow!(
+                "Variable `{regulator}` is not a regulator of `{target}`"
+            ));
+        };
+
+        let mut inputs = compiled
+            .regulator_ranges
+            .iter()
+            .map(|(min, max)| min + (max - min) / 2)
+            .collect::<Vec<_>>();
+        let (min, max) = compiled.regulator_ranges[index];
+        let mut result = Vec::new();
+        for level in min..=max {
+            inputs[index] = level;
+            result.push((level, compiled.try_eval(&inputs)?));
+        }
+        Ok(result)
+    }
+}
+
+impl CompiledUpdate {
+    /// The IDs of the input variables, in the order in which their levels are expected
+    /// by [`CompiledUpdate::eval`].
+    #[must_use]
+    pub fn regulators(&self) -> &[u32] {
+        &self.regulators
+    }
+
+    /// Evaluate the update function for the given regulator levels (in the order
+    /// given by [`CompiledUpdate::regulators`]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match the number of regulators, or if
+    /// the evaluation fails (see [`CompiledUpdate::try_eval`]).
+    #[must_use]
+    pub fn eval(&self, inputs: &[u32]) -> u32 {
+        match self.try_eval(inputs) {
+            Ok(level) => level,
+            Err(e) => panic!("Cannot evaluate `{}`: {e}", self.function),
+        }
+    }
+
+    /// The same as [`CompiledUpdate::eval`], but returns an error instead of panicking.
+    ///
+    /// The operation fails if the number of inputs does not match the number of regulators,
+    /// if the function uses a variable that is not a declared regulator, or if an invalid
+    /// arithmetic operation occurs (e.g., division by zero).
+    pub fn try_eval(&self, inputs: &[u32]) -> anyhow::Result<u32> {
+        if inputs.len() != self.regulators.len() {
+            return Err(anyhow!(
+                "Expected {} input levels, got {}",
+                self.regulators.len(),
+                inputs.len()
+            ));
+        }
+
+        if let Some(level) = self.constant {
+            return Ok(level);
+        }
+
+        let lookup = |var_id: u32| -> Option<Decimal> {
+            let index = self.regulators.binary_search(&var_id).ok()?;
+            Some(normalize_level(
+                inputs[index],
+                self.regulator_ranges[index],
+                self.target_range,
+            ))
+        };
+
+        let raw_result = self.function.evaluate_with(&lookup, self.integer_avg)?;
+        Ok(normalize_output(raw_result, self.target_range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::update_function::BmaUpdateFunction;
+    use crate::update_function::tests::{and_model, complex_model};
+    use crate::{BmaNetwork, BmaRelationship, BmaVariable};
+
+    #[test]
+    fn compiled_update_matches_function_table() {
+        let model = complex_model();
+        for var_id in [1, 2, 3] {
+            let compiled = model.network.compile_variable(var_id).unwrap();
+            let table = model.network.build_function_table(var_id).unwrap();
+            for (valuation, output) in table {
+                let inputs = compiled
+                    .regulators()
+                    .iter()
+                    .map(|id| valuation[id])
+                    .collect::<Vec<_>>();
+                assert_eq!(compiled.eval(&inputs), output);
+            }
+        }
+    }
+
+    #[test]
+    fn response_curve() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "x", (0, 4), None),
+                BmaVariable::new(2, "y", (0, 3), None),
+                BmaVariable::new(3, "z", (0, 4), None),
+            ],
+            vec![
+                BmaRelationship::new_activator(10, 1, 3),
+                BmaRelationship::new_inhibitor(11, 2, 3),
+            ],
+        );
+        let function = BmaUpdateFunction::try_from("var(1) - var(2)").unwrap();
+        network.variables[2].formula = Some(Ok(function));
+
+        // `y` is held at level `1`, which is normalized to `4/3`.
+        let curve = network.response_curve(3, 1).unwrap();
+        assert_eq!(curve, vec![(0, 0), (1, 0), (2, 1), (3, 2), (4, 3)]);
+
+        // `x` is held at level `2`.
+        let curve = network.response_curve(3, 2).unwrap();
+        assert_eq!(curve, vec![(0, 2), (1, 1), (2, 0), (3, 0)]);
+
+        assert!(network.response_curve(3, 3).is_err());
+        assert!(network.response_curve(4, 1).is_err());
+    }
+
+    #[test]
+    fn compiled_update_normalizes_levels() {
+        let mut network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "x", (0, 1), None),
+                BmaVariable::new(2, "y", (0, 2), None),
+            ],
+            vec![BmaRelationship::new_activator(10, 1, 2)],
+        );
+        network.populate_missing_functions();
+
+        let compiled = network.compile_variable(2).unwrap();
+        assert_eq!(compiled.regulators(), &[1]);
+        assert_eq!(compiled.eval(&[0]), 0);
+        assert_eq!(compiled.eval(&[1]), 2);
+    }
+
+    #[test]
+    fn compiled_update_errors() {
+        let model = and_model();
+        assert!(model.network.compile_variable(5).is_err());
+
+        let compiled = model.network.compile_variable(1).unwrap();
+        assert!(compiled.try_eval(&[1]).is_err());
+        assert_eq!(compiled.try_eval(&[1, 1]).unwrap(), 1);
+    }
+}
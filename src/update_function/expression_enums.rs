@@ -1,10 +1,40 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// All function names recognized by the parser: the aggregate functions (see [`AggregateFn`])
+/// followed by the unary functions (see [`UnaryFn`]).
+const FUNCTIONS: [&str; 6] = ["min", "max", "avg", "abs", "ceil", "floor"];
+
+/// Names of the aggregate functions recognized by the parser (see [`AggregateFn`]).
+pub(crate) const AGGREGATE_FUNCTIONS: &[&str] = FUNCTIONS.split_at(3).0;
+
+/// Names of the unary functions recognized by the parser (see [`UnaryFn`]).
+pub(crate) const UNARY_FUNCTIONS: &[&str] = FUNCTIONS.split_at(3).1;
+
+/// All function names recognized in BMA function expressions, i.e., the names of
+/// the aggregate functions (see [`AggregateFn`]) followed by the unary functions
+/// (see [`UnaryFn`]).
+#[must_use]
+pub fn supported_functions() -> &'static [&'static str] {
+    &FUNCTIONS
+}
+
+/// All binary operators recognized in BMA function expressions (see [`ArithOp`]).
+/// Note that `-` can also be used as a unary minus.
+#[must_use]
+pub fn supported_operators() -> &'static [&'static str] {
+    &["+", "-", "*", "/"]
+}
+
 /// An atomic expression that can be either an integer or a variable.
 ///
 /// There are some weird format differences, and a variable can be referenced by
 /// either its ID or its name. We convert everything to IDs for easier processing.
+///
+/// The parser never produces negative constants: A leading minus (e.g. `-3`) is parsed
+/// as [`UnaryFn::Neg`] applied to a positive constant, and printed as `-(3)`. A negative
+/// `Const` (e.g. created using [`crate::update_function::BmaUpdateFunction::mk_constant`])
+/// is printed as `-3`, which parses back into the same value (but not the same tree).
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum Literal {
     Const(i32),
@@ -117,3 +147,26 @@ impl fmt::Display for AggregateFn {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supported_functions_are_recognized() {
+        assert_eq!(
+            supported_functions(),
+            [AGGREGATE_FUNCTIONS, UNARY_FUNCTIONS].concat()
+        );
+        for name in AGGREGATE_FUNCTIONS {
+            assert_eq!(AggregateFn::try_from(*name).unwrap().to_string(), *name);
+        }
+        for name in UNARY_FUNCTIONS {
+            assert_eq!(UnaryFn::try_from(*name).unwrap().to_string(), *name);
+        }
+        for op in supported_operators() {
+            let op_char = op.chars().next().unwrap();
+            assert_eq!(ArithOp::try_from(op_char).unwrap().to_string(), *op);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use crate::update_function::expression_enums::{ArithOp, Literal};
-use crate::update_function::expression_token::{BmaToken, BmaTokenData, try_tokenize_bma_formula};
+use crate::update_function::expression_token::{
+    BmaToken, BmaTokenData, tokenize_bma_formula_recovering, try_tokenize_bma_formula,
+};
 use crate::update_function::{BmaUpdateFunction, ParserError};
 use BmaTokenData::{Aggregate, Atomic, Binary, TokenList, Unary};
 
@@ -19,6 +21,25 @@ pub fn parse_bma_formula(
     Ok(tree)
 }
 
+/// The same as [`parse_bma_formula`], but tries to recover from errors and report as many
+/// of them as possible.
+///
+/// Structural errors (e.g. a missing operand) are only reported when the formula can
+/// be tokenized without errors.
+pub fn parse_bma_formula_collecting(
+    formula: &str,
+    variable_id_hint: &[(u32, String)],
+) -> (Option<BmaUpdateFunction>, Vec<ParserError>) {
+    let (tokens, errors) = tokenize_bma_formula_recovering(formula, variable_id_hint);
+    if !errors.is_empty() {
+        return (None, errors);
+    }
+    match parse_bma_fn_tokens(&tokens) {
+        Ok(tree) => (Some(tree), Vec::new()),
+        Err(error) => (None, vec![error]),
+    }
+}
+
 /// A utility function that allows us to properly handle empty token list errors.
 fn before_or_empty<F: Fn(&[BmaToken]) -> Result<BmaUpdateFunction, ParserError>>(
     op: F,
@@ -566,4 +587,94 @@ mod tests {
         );
         assert_eq!(result, Ok(expected));
     }
+
+    #[test]
+    fn test_unary_minus_round_trip() {
+        let vars = vec![(0u32, "x".to_string()), (1u32, "y".to_string())];
+        for (input, printed) in [
+            ("-var(0)", "-(var(0))"),
+            ("-3 + var(1)", "(-(3) + var(1))"),
+            ("var(0) - -var(1)", "(var(0) - -(var(1)))"),
+            ("-min(-var(0), 2)", "-(min(-(var(0)), 2))"),
+        ] {
+            let function = parse_bma_formula(input, &vars).unwrap();
+            assert_eq!(function.to_string(), printed);
+            assert_eq!(parse_bma_formula(printed, &vars), Ok(function));
+        }
+
+        let expected = BmaUpdateFunction::mk_arithmetic(
+            ArithOp::Plus,
+            &BmaUpdateFunction::mk_unary(UnaryFn::Neg, &BmaUpdateFunction::mk_constant(3)),
+            &BmaUpdateFunction::mk_variable(1),
+        );
+        assert_eq!(parse_bma_formula("-3 + var(1)", &vars), Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_collecting_valid() {
+        let input = "max(var(1), 3) - var(2)";
+        let (result, errors) = parse_bma_formula_collecting(input, &[]);
+        assert!(errors.is_empty());
+        assert_eq!(result, parse_bma_formula(input, &[]).ok());
+    }
+
+    #[test]
+    fn test_parse_collecting_multiple_errors() {
+        let vars = vec![(1, "a".to_string())];
+        let input = "var(b) + foo(var(a)) * max(var(c), 2) + bar";
+        let (result, errors) = parse_bma_formula_collecting(input, &vars);
+        assert_eq!(result, None);
+        let messages = errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "`b` is not a known regulator",
+                "`foo` is not a recognized function or variable",
+                "`c` is not a known regulator",
+                "`bar` is not a recognized function or variable",
+            ]
+        );
+        let positions = errors.iter().map(|e| e.position).collect::<Vec<_>>();
+        assert_eq!(positions, vec![3, 9, 30, 40]);
+
+        // The first error is the same as the one reported by the regular parser.
+        assert_eq!(Err(errors[0].clone()), parse_bma_formula(input, &vars));
+    }
+
+    #[test]
+    fn test_parse_collecting_parentheses() {
+        let input = "(var(1) + abs(2)) ) * min(var(1), (3 $ 4)";
+        let (result, errors) = parse_bma_formula_collecting(input, &[]);
+        assert_eq!(result, None);
+        let messages = errors
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "Unexpected `)` (missing opening `(`)",
+                "Unexpected `$`",
+                "Input ended while expecting `)`",
+            ]
+        );
+
+        // Structural errors are reported when the tokens are valid.
+        let (result, errors) = parse_bma_formula_collecting("var(1) + ", &[]);
+        assert_eq!(result, None);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_collecting_public_api() {
+        let (result, errors) = BmaUpdateFunction::parse_collecting("x + y", &[]);
+        assert_eq!(result, None);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].position(), Some(0));
+        assert_eq!(errors[1].position(), Some(4));
+        assert!(errors.iter().all(|e| e.expression == "x + y"));
+    }
 }
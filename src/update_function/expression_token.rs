@@ -1,5 +1,7 @@
 use crate::update_function::ParserError;
-use crate::update_function::expression_enums::{AggregateFn, ArithOp, Literal, UnaryFn};
+use crate::update_function::expression_enums::{
+    AGGREGATE_FUNCTIONS, AggregateFn, ArithOp, Literal, UNARY_FUNCTIONS, UnaryFn,
+};
 use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
 
@@ -75,7 +77,8 @@ pub fn try_tokenize_bma_formula(
     variable_id_hint: &[(u32, String)],
 ) -> Result<Vec<BmaToken>, ParserError> {
     let chars: Vec<char> = formula.chars().collect();
-    let (tokens, length) = try_tokenize_recursive(&chars, 0, false, false, variable_id_hint)?;
+    let (tokens, length) =
+        try_tokenize_recursive(&chars, 0, false, false, variable_id_hint, &mut None)?;
 
     // If the tokenizer succeeds, it should always read the whole string.
     debug_assert!(length == chars.len());
@@ -83,6 +86,93 @@ pub fn try_tokenize_bma_formula(
     Ok(tokens)
 }
 
+/// Collect the identifiers of all `var(...)` references in the given formula (in the order of
+/// appearance), regardless of whether they are numeric IDs or variable names.
+///
+/// This only performs a lexical scan, so the formula does not need to be valid and
+/// the identifiers do not need to correspond to existing variables.
+fn collect_variable_references(formula: &str) -> Vec<String> {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut result = Vec::new();
+    let mut position = 0;
+    while position < chars.len() {
+        if !is_valid_start_name(chars[position]) {
+            position += 1;
+            continue;
+        }
+        let id = collect_identifier_str(&chars, position);
+        position += id.chars().count();
+        if id == "var"
+            && let Ok((identifier, length)) = collect_variable_identifier(&chars, position)
+        {
+            result.push(identifier);
+            position += length;
+        }
+    }
+    result
+}
+
+/// Collect the identifiers of all `var(name)` references in the given formula that use
+/// a variable name instead of a numeric ID (in the order of appearance).
+///
+/// This only performs a lexical scan, so the formula does not need to be valid and
+/// the names do not need to correspond to existing variables.
+pub(crate) fn collect_name_references(formula: &str) -> Vec<String> {
+    collect_variable_references(formula)
+        .into_iter()
+        .filter(|identifier| identifier.parse::<u32>().is_err())
+        .collect()
+}
+
+/// Collect the numeric IDs of all `var(id)` references in the given formula (in the order
+/// of appearance). See also [`collect_name_references`].
+pub(crate) fn collect_id_references(formula: &str) -> Vec<u32> {
+    collect_variable_references(formula)
+        .iter()
+        .filter_map(|identifier| identifier.parse::<u32>().ok())
+        .collect()
+}
+
+/// Tokenize a BMA function expression into tokens, but instead of failing on the first error,
+/// try to recover and collect as many errors as possible.
+///
+/// Recovery skips the offending atomic expression (including its argument list, if any) and
+/// replaces it with a placeholder constant. Unexpected delimiters are skipped and unclosed
+/// parentheses are closed at the end of input. As such, the returned tokens are only meaningful
+/// when no errors are reported.
+pub fn tokenize_bma_formula_recovering(
+    formula: &str,
+    variable_id_hint: &[(u32, String)],
+) -> (Vec<BmaToken>, Vec<ParserError>) {
+    let chars: Vec<char> = formula.chars().collect();
+    let mut errors = Some(Vec::new());
+    let result = try_tokenize_recursive(&chars, 0, false, false, variable_id_hint, &mut errors);
+    let Ok((tokens, _length)) = result else {
+        unreachable!("Tokenizer invariant: errors are always recovered when collecting.")
+    };
+
+    // Some problems (like unclosed parentheses) can be detected by multiple nested groups.
+    let mut unique_errors = Vec::new();
+    for error in errors.unwrap_or_default() {
+        if !unique_errors.contains(&error) {
+            unique_errors.push(error);
+        }
+    }
+
+    (tokens, unique_errors)
+}
+
+/// Report an `error` that occurred during tokenization. If `errors` are being collected,
+/// the error is saved and the caller should try to recover. Otherwise, the error is returned.
+fn recover(errors: &mut Option<Vec<ParserError>>, error: ParserError) -> Result<(), ParserError> {
+    if let Some(errors) = errors {
+        errors.push(error);
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
 /// Process an input string into a vector of [`BmaTokenData`] objects, starting from the
 /// `start_at` position. The function also returns the *length of the tokenized region*.
 ///
@@ -93,6 +183,9 @@ pub fn try_tokenize_bma_formula(
 ///
 /// If provided, `variable_id_hint` will be used to resolve variable names into IDs.
 ///
+/// If `errors` is `Some`, the function tries to recover from errors (see
+/// [`tokenize_bma_formula_recovering`]) and saves them into the list instead of returning them.
+///
 #[allow(clippy::too_many_lines)]
 fn try_tokenize_recursive(
     input: &[char],
@@ -100,6 +193,7 @@ fn try_tokenize_recursive(
     ends_with_comma: bool,
     ends_with_parenthesis: bool,
     variable_id_hint: &[(u32, String)],
+    errors: &mut Option<Vec<ParserError>>,
 ) -> Result<(Vec<BmaToken>, usize), ParserError> {
     let mut result = Vec::new();
     let mut position = start_at;
@@ -107,25 +201,28 @@ fn try_tokenize_recursive(
     while position < input.len() {
         match input[position] {
             ',' => {
-                return if ends_with_comma {
+                if ends_with_comma {
                     // We have found the stop character. This tokenization group is done, and
                     // next group can start from the next position.
-                    Ok((result, position - start_at + 1))
+                    return Ok((result, position - start_at + 1));
                 } else if ends_with_parenthesis {
                     let message = "Unclosed parenthesis (group closed by `,` before `)` was found)";
-                    Err(ParserError::at(position, message.to_string()))
+                    recover(errors, ParserError::at(position, message.to_string()))?;
                 } else {
                     let message = "Unexpected `,`";
-                    Err(ParserError::at(position, message.to_string()))
-                };
+                    recover(errors, ParserError::at(position, message.to_string()))?;
+                }
+                // Recovery: skip the unexpected delimiter.
+                position += 1;
             }
             ')' => {
-                return if ends_with_parenthesis {
-                    Ok((result, (position - start_at) + 1))
-                } else {
-                    let message = "Unexpected `)` (missing opening `(`)";
-                    Err(ParserError::at(position, message.to_string()))
-                };
+                if ends_with_parenthesis {
+                    return Ok((result, (position - start_at) + 1));
+                }
+                let message = "Unexpected `)` (missing opening `(`)";
+                recover(errors, ParserError::at(position, message.to_string()))?;
+                // Recovery: skip the unexpected delimiter.
+                position += 1;
             }
             c if c.is_whitespace() => {
                 // Ignore all whitespace.
@@ -150,13 +247,21 @@ fn try_tokenize_recursive(
 
                     if position >= input.len() {
                         let message = "Expected expression after unary minus `-`";
-                        return Err(ParserError::at(unary_minus_pos, message.to_string()));
+                        recover(
+                            errors,
+                            ParserError::at(unary_minus_pos, message.to_string()),
+                        )?;
+                        break;
                     }
 
                     // Parse the following atomic expression (number, variable, function, or parenthesized group)
                     let following_expr_start = position;
-                    let following_token =
-                        parse_atomic_expression(input, &mut position, variable_id_hint)?;
+                    let following_token = parse_atomic_expression_or_recover(
+                        input,
+                        &mut position,
+                        variable_id_hint,
+                        errors,
+                    )?;
 
                     // Wrap the following token in a TokenList (to match the pattern of other unary functions)
                     // and then wrap that in a Unary(Neg, ...) token
@@ -190,47 +295,119 @@ fn try_tokenize_recursive(
             }
             '(' => {
                 // Parenthesized expression
-                let token = parse_atomic_expression(input, &mut position, variable_id_hint)?;
+                let token = parse_atomic_expression_or_recover(
+                    input,
+                    &mut position,
+                    variable_id_hint,
+                    errors,
+                )?;
                 result.push(token);
             }
             '0'..='9' => {
                 // Number literal
-                let token = parse_atomic_expression(input, &mut position, variable_id_hint)?;
+                let token = parse_atomic_expression_or_recover(
+                    input,
+                    &mut position,
+                    variable_id_hint,
+                    errors,
+                )?;
                 result.push(token);
             }
             c if is_valid_start_name(c) => {
                 // Variable or function call
-                let token = parse_atomic_expression(input, &mut position, variable_id_hint)?;
+                let token = parse_atomic_expression_or_recover(
+                    input,
+                    &mut position,
+                    variable_id_hint,
+                    errors,
+                )?;
                 result.push(token);
             }
             c => {
                 // Any other character is unexpected at this point.
-                let message = format!("Unexpected `{c}`");
-                return Err(ParserError::at(position, message));
+                let message = unexpected_message("Unexpected", c);
+                recover(errors, ParserError::at(position, message))?;
+                // Recovery: skip the unexpected character.
+                position += 1;
             }
         }
     }
 
     // Technically, if something ends with a comma, it must always also end with parenthesis,
     // but in theory, future implementations do not need to require this.
+    // Recovery: the group is considered closed by the end of input.
     if ends_with_parenthesis {
         let message = "Input ended while expecting `)`";
-        return Err(ParserError::at(position, message.to_string()));
-    }
-    if ends_with_comma {
+        recover(errors, ParserError::at(position, message.to_string()))?;
+    } else if ends_with_comma {
         let message = "Input ended while expecting `,`";
-        return Err(ParserError::at(position, message.to_string()));
+        recover(errors, ParserError::at(position, message.to_string()))?;
     }
 
     Ok((result, position - start_at))
 }
 
+/// The same as [`parse_atomic_expression`], but if `errors` are being collected, a failed
+/// expression is skipped and replaced by a placeholder constant.
+fn parse_atomic_expression_or_recover(
+    input: &[char],
+    position: &mut usize,
+    variable_id_hint: &[(u32, String)],
+    errors: &mut Option<Vec<ParserError>>,
+) -> Result<BmaToken, ParserError> {
+    let start_pos = *position;
+    match parse_atomic_expression(input, position, variable_id_hint, errors) {
+        Ok(token) => Ok(token),
+        Err(error) => {
+            recover(errors, error)?;
+            *position = skip_atomic_expression(input, start_pos);
+            Ok(BmaTokenData::Atomic(Literal::Const(0)).at(start_pos))
+        }
+    }
+}
+
+/// Find the end of an atomic expression starting at the given position without validating it.
+/// This is an identifier or a number, followed by an optional (balanced) parenthesis group.
+///
+/// The returned position is always greater than `start_at` (unless the input ended).
+fn skip_atomic_expression(input: &[char], start_at: usize) -> usize {
+    let mut position = start_at;
+    if position < input.len() && is_valid_in_name(input[position]) {
+        while position < input.len() && is_valid_in_name(input[position]) {
+            position += 1;
+        }
+        let next = next_non_whitespace_character(input, position);
+        if next >= input.len() || input[next] != '(' {
+            return position;
+        }
+        position = next;
+    } else if position < input.len() && input[position] != '(' {
+        return position + 1;
+    }
+
+    // Skip a balanced parenthesis group (or everything until the end of input).
+    let mut depth = 0usize;
+    while position < input.len() {
+        match input[position] {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => (),
+        }
+        position += 1;
+        if depth == 0 {
+            break;
+        }
+    }
+    position
+}
+
 /// Parse a single atomic expression (number, variable, function, or parenthesized group)
 /// starting at the given position. Returns the token and advances the position.
 fn parse_atomic_expression(
     input: &[char],
     position: &mut usize,
     variable_id_hint: &[(u32, String)],
+    errors: &mut Option<Vec<ParserError>>,
 ) -> Result<BmaToken, ParserError> {
     let start_pos = *position;
 
@@ -244,7 +421,7 @@ fn parse_atomic_expression(
             // Parenthesized expression
             *position += 1;
             let (group, length) =
-                try_tokenize_recursive(input, *position, false, true, variable_id_hint)?;
+                try_tokenize_recursive(input, *position, false, true, variable_id_hint, errors)?;
             let token = BmaTokenData::TokenList(group).at(*position);
             *position += length;
             Ok(token)
@@ -295,9 +472,9 @@ fn parse_atomic_expression(
                     *position += length;
                     Ok(BmaTokenData::Atomic(Literal::Var(var_id)).at(identifier_start))
                 }
-                id if ["min", "max", "avg"].contains(&id) => {
+                id if AGGREGATE_FUNCTIONS.contains(&id) => {
                     let (args, length) =
-                        collect_function_arguments(input, *position, variable_id_hint)?;
+                        collect_function_arguments(input, *position, variable_id_hint, errors)?;
                     let op = AggregateFn::try_from(id).unwrap();
                     if args.is_empty() {
                         let message = format!("Function `{id}` expects at least one argument");
@@ -306,9 +483,9 @@ fn parse_atomic_expression(
                     *position += length;
                     Ok(BmaTokenData::Aggregate(op, args).at(identifier_start))
                 }
-                id if ["abs", "ceil", "floor"].contains(&id) => {
+                id if UNARY_FUNCTIONS.contains(&id) => {
                     let (args, length) =
-                        collect_function_arguments(input, *position, variable_id_hint)?;
+                        collect_function_arguments(input, *position, variable_id_hint, errors)?;
                     if args.len() != 1 {
                         let message = format!(
                             "Function `{}` expects exactly one argument; found `{}`",
@@ -329,12 +506,28 @@ fn parse_atomic_expression(
             }
         }
         c => {
-            let message = format!("Unexpected character `{c}`");
+            let message = unexpected_message("Unexpected character", c);
             Err(ParserError::at(start_pos, message))
         }
     }
 }
 
+/// Build an error message for an unexpected character `c`. For operators that commonly
+/// appear in hand-written functions but are not supported by BMA (`^` and `%`), the message
+/// also explains how to rewrite the expression.
+fn unexpected_message(prefix: &str, c: char) -> String {
+    match c {
+        '^' => format!(
+            "{prefix} `^` (exponentiation is not supported; use repeated multiplication, \
+            e.g. `var(1) * var(1)`)"
+        ),
+        '%' => format!(
+            "{prefix} `%` (modulo is not supported; only `+`, `-`, `*`, and `/` can be used)"
+        ),
+        c => format!("{prefix} `{c}`"),
+    }
+}
+
 /// Check all whitespaces at the front of the iterator.
 fn next_non_whitespace_character(input: &[char], mut position: usize) -> usize {
     while position < input.len() && input[position].is_whitespace() {
@@ -418,6 +611,7 @@ fn collect_function_arguments(
     input: &[char],
     start_at: usize,
     variable_id_hint: &[(u32, String)],
+    errors: &mut Option<Vec<ParserError>>,
 ) -> Result<(Vec<BmaToken>, usize), ParserError> {
     let mut position = next_non_whitespace_character(input, start_at);
 
@@ -437,23 +631,26 @@ fn collect_function_arguments(
 
         // Tokenization of a single argument can end if comma or parenthesis is found.
         let (group, length) =
-            try_tokenize_recursive(input, position, true, true, variable_id_hint)?;
+            try_tokenize_recursive(input, position, true, true, variable_id_hint, errors)?;
 
         if group.is_empty() {
-            let message = "Argument is empty";
-            return Err(ParserError::at(position, message.to_string()));
+            // If the input ended, this has already been reported during recovery.
+            if length > 0 {
+                let message = "Argument is empty";
+                recover(errors, ParserError::at(position, message.to_string()))?;
+            }
+        } else {
+            args.push(BmaTokenData::TokenList(group).at(position));
         }
 
-        args.push(BmaTokenData::TokenList(group).at(position));
-
-        debug_assert!(length > 0);
         position += length;
 
-        // If the last character of this group is parenthesis, we are done.
-        if input[position - 1] == ')' {
+        // If the last character of this group is parenthesis, we are done. Other than comma,
+        // the group can only end with the end of input when recovering from errors.
+        if length == 0 || input[position - 1] != ',' {
+            debug_assert!(errors.is_some() || input[position - 1] == ')');
             break;
         }
-        debug_assert_eq!(input[position - 1], ',');
         position = next_non_whitespace_character(input, position);
     }
 
@@ -464,7 +661,7 @@ fn collect_function_arguments(
 mod tests {
     use crate::update_function::expression_enums::{AggregateFn, ArithOp, Literal, UnaryFn};
     use crate::update_function::expression_token::{
-        BmaTokenData, try_tokenize_bma_formula, try_tokenize_recursive,
+        BmaTokenData, parse_atomic_expression, try_tokenize_bma_formula, try_tokenize_recursive,
     };
     use AggregateFn::{Max, Min};
     use ArithOp::{Minus, Plus};
@@ -628,7 +825,8 @@ mod tests {
         // But it could appear as a use case in the future.
         let input = "2 * 3";
         let input_chars = Vec::from_iter(input.chars());
-        let result = try_tokenize_recursive(&input_chars, 0, true, false, &[]).unwrap_err();
+        let result =
+            try_tokenize_recursive(&input_chars, 0, true, false, &[], &mut None).unwrap_err();
         assert_eq!(result.message, "Input ended while expecting `,`");
         assert_eq!(result.position, 5);
     }
@@ -641,6 +839,35 @@ mod tests {
         assert_eq!(result.position, 4);
     }
 
+    #[test]
+    fn test_unsupported_operators() {
+        let result = try_tokenize_bma_formula("var(1) ^ 2", &[]).unwrap_err();
+        assert_eq!(
+            result.message,
+            "Unexpected `^` (exponentiation is not supported; use repeated multiplication, \
+            e.g. `var(1) * var(1)`)"
+        );
+        assert_eq!(result.position, 7);
+
+        let result = try_tokenize_bma_formula("5 % 2", &[]).unwrap_err();
+        assert!(
+            result
+                .message
+                .starts_with("Unexpected `%` (modulo is not supported")
+        );
+        assert_eq!(result.position, 2);
+
+        let input_chars = "^".chars().collect::<Vec<_>>();
+        let mut position = 0;
+        let result =
+            parse_atomic_expression(&input_chars, &mut position, &[], &mut None).unwrap_err();
+        assert!(
+            result
+                .message
+                .starts_with("Unexpected character `^` (exponentiation")
+        );
+    }
+
     #[test]
     fn test_function_with_no_arguments_invalid() {
         let input = "abs()";
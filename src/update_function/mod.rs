@@ -2,72 +2,72 @@ mod bma_update_function;
 mod expression_enums;
 mod expression_node_data;
 
+mod ast_json;
 mod bma_expression_error;
 mod bma_update_function_evaluation;
+mod compiled_update;
 mod expression_default_builder;
 mod expression_parser;
 mod expression_token;
+#[cfg(feature = "bn-conversion")]
 mod from_aeon;
 
 pub use bma_update_function::BmaUpdateFunction;
-pub use expression_enums::{AggregateFn, ArithOp, Literal, UnaryFn};
+pub use expression_enums::{
+    AggregateFn, ArithOp, Literal, UnaryFn, supported_functions, supported_operators,
+};
 pub use expression_node_data::BmaExpressionNodeData;
 
 pub use bma_expression_error::InvalidBmaExpression;
 pub(crate) use bma_expression_error::ParserError;
 pub(crate) use expression_default_builder::create_default_update_fn;
 
-pub use bma_update_function_evaluation::FunctionTable;
+pub use bma_update_function_evaluation::{DivisionByZeroError, FunctionTable};
+pub use compiled_update::CompiledUpdate;
 
 #[cfg(test)]
 mod tests {
+    use crate::RelationshipType::Activator;
     use crate::update_function::BmaUpdateFunction;
-    use crate::{BmaModel, BmaNetwork, BmaRelationship, BmaVariable};
+    use crate::{BmaModel, BmaModelBuilder, BmaNetwork, BmaRelationship, BmaVariable};
 
     /// Build a model with two variables, each having update function "a * b" (boolean AND on {0,1}).
     pub fn and_model() -> BmaModel {
-        let expression = BmaUpdateFunction::try_from("var(1) * var(2)").unwrap();
-
-        BmaModel {
-            network: BmaNetwork {
-                name: "".to_string(),
-                variables: vec![
-                    BmaVariable::new(1, "a", (0, 1), Some(expression.clone())),
-                    BmaVariable::new(2, "b", (0, 1), Some(expression)),
-                ],
-                relationships: vec![
-                    BmaRelationship::new_activator(100, 1, 1),
-                    BmaRelationship::new_activator(101, 2, 1),
-                    BmaRelationship::new_activator(102, 1, 2),
-                    BmaRelationship::new_activator(103, 2, 2),
-                ],
-            },
-            layout: Default::default(),
-            metadata: Default::default(),
-        }
+        BmaModelBuilder::new("")
+            .add_variable(1, "a", (0, 1))
+            .add_variable(2, "b", (0, 1))
+            .add_relationship_with_id(100, 1, 1, Activator)
+            .add_relationship_with_id(101, 2, 1, Activator)
+            .add_relationship_with_id(102, 1, 2, Activator)
+            .add_relationship_with_id(103, 2, 2, Activator)
+            .set_formula(1, "var(1) * var(2)")
+            .set_formula(2, "var(1) * var(2)")
+            .build()
+            .unwrap()
     }
 
     /// Build a model with three variables, the first having a complex update function.
+    ///
+    /// Note that the model is not valid (the relationships from `b` and `c` are declared as
+    /// activators, but they are inhibiting), so it cannot be created using [`BmaModelBuilder`].
     pub fn complex_model() -> BmaModel {
         let expression =
             BmaUpdateFunction::try_from("var(1) + (1 - min((var(2) + var(3)), 1))").unwrap();
-
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new(1, "a", (0, 1), Some(expression)),
+                BmaVariable::new(2, "b", (0, 1), None),
+                BmaVariable::new(3, "c", (0, 1), None),
+            ],
+            vec![
+                BmaRelationship::new_activator(100, 1, 1),
+                BmaRelationship::new_activator(101, 2, 1),
+                BmaRelationship::new_activator(102, 3, 1),
+            ],
+        );
         BmaModel {
-            network: BmaNetwork {
-                name: "".to_string(),
-                variables: vec![
-                    BmaVariable::new(1, "a", (0, 1), Some(expression)),
-                    BmaVariable::new(2, "b", (0, 1), None),
-                    BmaVariable::new(3, "c", (0, 1), None),
-                ],
-                relationships: vec![
-                    BmaRelationship::new_activator(100, 1, 1),
-                    BmaRelationship::new_activator(101, 2, 1),
-                    BmaRelationship::new_activator(102, 3, 1),
-                ],
-            },
-            layout: Default::default(),
-            metadata: Default::default(),
+            network,
+            ..Default::default()
         }
     }
 }
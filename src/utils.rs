@@ -1,7 +1,10 @@
 use num_traits::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::hash::Hash;
 
-/// Make a trimmed copy of the provided `&str`.
+/// Make a trimmed copy of the provided `&str`, or `None` if the value is *blank* (i.e. empty
+/// or whitespace only). See also [`crate::BmaVariable::display_name`].
 pub fn take_if_not_blank(value: &str) -> Option<String> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -26,26 +29,34 @@ pub fn decimal_or_default(rational: f64) -> Decimal {
     Decimal::from_f64(rational).unwrap_or_default()
 }
 
-/// A helper method to check that a given `container` has the expected value, and it is the
-/// only value with such ID in the container.
+/// Escape the special XML characters in the given string, such that it can be used
+/// as XML text or attribute value.
+pub fn escape_xml(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Count how many times each ID appears in the given `container`.
 ///
-/// The method returns `Err(())` when the `value` is not in the `container`. If the `value` is in
-/// the container, the method either returns `Ok(true)` (the value is unique), or `Ok(false)`
-/// (the value is not unique).
-pub fn is_unique_id<T: Eq, ID: Eq, F>(container: &[T], value: &T, id: F) -> Result<bool, ()>
+/// Validation uses this to check ID uniqueness of all items using a single pass over the
+/// container (instead of scanning the whole container for every item, which is quadratic).
+pub fn count_ids<T, ID: Eq + Hash, F>(container: &[T], id: F) -> HashMap<ID, usize>
 where
     F: Fn(&T) -> ID,
 {
-    let check_id = id(value);
-    let mut found = false;
-    let mut count = 0u32;
+    let mut counts = HashMap::new();
     for item in container {
-        if value == item {
-            found = true;
-        }
-        if id(item) == check_id {
-            count += 1;
-        }
+        *counts.entry(id(item)).or_insert(0) += 1;
     }
-    if found { Ok(count == 1) } else { Err(()) }
+    counts
 }
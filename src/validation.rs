@@ -8,10 +8,55 @@ use std::marker::PhantomData;
 /// based on [`ErrorReporter::wrap`]. However, in most cases, users will just use `validate`
 /// methods that use the default vector reporter without caring too much about the underlying
 /// implementation.
+///
+/// # Custom reporters
+///
+/// To route validation issues directly into another diagnostics system (e.g. a logger),
+/// implement this trait for your own type. Only [`ErrorReporter::report`] is required,
+/// and a reporter that is generic over `E` can be used with every validated type:
+///
+/// ```
+/// use biodivine_lib_io_bma::{BmaModel, ErrorReporter, Validation};
+/// use std::error::Error;
+///
+/// /// Writes every issue as a separate line of a log (e.g. `tracing::warn!` in a real app).
+/// struct LogReporter {
+///     log: Vec<String>,
+/// }
+///
+/// impl<E: Error> ErrorReporter<E> for LogReporter {
+///     fn report<X: Into<E>>(&mut self, error: X) {
+///         let error: E = error.into();
+///         self.log.push(format!("validation issue: {error}"));
+///     }
+/// }
+///
+/// let json = r#"{ "Model": { "Name": "m", "Variables": [
+///     { "Id": 1, "Name": "a", "RangeFrom": 2, "RangeTo": 1, "Formula": "" }
+/// ] } }"#;
+/// let model = BmaModel::from_json_string(json).unwrap();
+/// let mut reporter = LogReporter { log: Vec::new() };
+/// model.validate_all(&mut reporter);
+/// assert!(!reporter.log.is_empty());
+/// ```
+///
+/// Reporters receive the errors of the validated type (e.g. [`crate::BmaModelError`] for
+/// [`crate::BmaModel`]). Nested objects are validated through a [`ReporterWrapper`] (see
+/// [`ErrorReporter::wrap`]), which converts their errors into the outer error type, so a
+/// reporter never needs to handle the nested error types. To stop the validation early,
+/// also implement [`ErrorReporter::is_full`] (see [`LimitReporter`]).
 pub trait ErrorReporter<E: StdError>: Sized {
     /// Report an error into this [`ErrorReporter`].
     fn report<E2: Into<E>>(&mut self, error: E2);
 
+    /// Returns `true` if this [`ErrorReporter`] does not accept any more errors. Validation
+    /// can then stop early instead of running the remaining (possibly expensive) checks.
+    ///
+    /// By default, a reporter is never full.
+    fn is_full(&self) -> bool {
+        false
+    }
+
     /// Wrap a mutable reference to this [`ErrorReporter`] into a [`ReporterWrapper`]
     /// which automatically performs type conversions from `E2`.
     fn wrap<E2: StdError + Into<E>>(&mut self) -> ReporterWrapper<'_, E2, E, Self> {
@@ -28,6 +73,21 @@ pub struct VecReporter<E: StdError> {
     errors: Vec<E>,
 }
 
+/// An [`ErrorReporter`] implementation that collects at most `limit` errors into a vector.
+/// Once the limit is reached, the remaining errors are ignored and the reporter is full
+/// (see [`ErrorReporter::is_full`]).
+pub struct LimitReporter<E: StdError> {
+    errors: Vec<E>,
+    limit: usize,
+}
+
+/// An [`ErrorReporter`] implementation that passes every error to a callback function
+/// as soon as it is reported (instead of collecting the errors).
+pub struct CallbackReporter<E: StdError, F: FnMut(&E)> {
+    callback: F,
+    _e: PhantomData<E>,
+}
+
 /// A simple [`ErrorReporter`] implementation that defers to an internal [`ErrorReporter`]
 /// by performing type conversion from `E1` into `E2` using `Into`.
 pub struct ReporterWrapper<'a, E1: StdError + Into<E2>, E2: StdError, W: ErrorReporter<E2>> {
@@ -42,6 +102,33 @@ impl<E1: StdError + Into<E2>, E2: StdError, W: ErrorReporter<E2>> ErrorReporter<
     fn report<X: Into<E1>>(&mut self, error: X) {
         self.inner.report(error.into());
     }
+
+    fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+}
+
+impl<E: StdError> VecReporter<E> {
+    /// Create a new empty [`VecReporter`].
+    #[must_use]
+    pub fn new() -> Self {
+        VecReporter { errors: vec![] }
+    }
+
+    /// Convert the collected errors into a validation result (`Ok` if no errors were reported).
+    pub fn into_result(self) -> Result<(), Vec<E>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+impl<E: StdError> Default for VecReporter<E> {
+    fn default() -> Self {
+        VecReporter::new()
+    }
 }
 
 impl<E: StdError> ErrorReporter<E> for VecReporter<E> {
@@ -50,6 +137,58 @@ impl<E: StdError> ErrorReporter<E> for VecReporter<E> {
     }
 }
 
+impl<E: StdError> LimitReporter<E> {
+    /// Create a new empty [`LimitReporter`] which collects at most `limit` errors.
+    ///
+    /// A `limit` of `0` is treated as `1`, such that an invalid input is never reported
+    /// as valid.
+    #[must_use]
+    pub fn new(limit: usize) -> Self {
+        LimitReporter {
+            errors: vec![],
+            limit: limit.max(1),
+        }
+    }
+
+    /// Convert the collected errors into a validation result (`Ok` if no errors were reported).
+    pub fn into_result(self) -> Result<(), Vec<E>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+impl<E: StdError> ErrorReporter<E> for LimitReporter<E> {
+    fn report<X: Into<E>>(&mut self, error: X) {
+        if !self.is_full() {
+            self.errors.push(error.into());
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.errors.len() >= self.limit
+    }
+}
+
+impl<E: StdError, F: FnMut(&E)> CallbackReporter<E, F> {
+    /// Create a new [`CallbackReporter`] which calls `callback` for every reported error.
+    #[must_use]
+    pub fn new(callback: F) -> Self {
+        CallbackReporter {
+            callback,
+            _e: PhantomData,
+        }
+    }
+}
+
+impl<E: StdError, F: FnMut(&E)> ErrorReporter<E> for CallbackReporter<E, F> {
+    fn report<X: Into<E>>(&mut self, error: X) {
+        (self.callback)(&error.into());
+    }
+}
+
 /// Contextual validation trait is implemented by objects that can only be validated against a
 /// certain context. Usually, this happens when a certain type needs extra information to
 /// infer that all of its invariants are satisfied.
@@ -71,13 +210,9 @@ pub trait ContextualValidation<Context> {
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, context: &Context, reporter: &mut R);
 
     fn validate(&self, context: &Context) -> Result<(), Vec<Self::Error>> {
-        let mut reporter = VecReporter { errors: vec![] };
+        let mut reporter = VecReporter::new();
         self.validate_all(context, &mut reporter);
-        if reporter.errors.is_empty() {
-            Ok(())
-        } else {
-            Err(reporter.errors)
-        }
+        reporter.into_result()
     }
 }
 
@@ -94,12 +229,8 @@ pub trait Validation {
     fn validate_all<R: ErrorReporter<Self::Error>>(&self, reporter: &mut R);
 
     fn validate(&self) -> Result<(), Vec<Self::Error>> {
-        let mut reporter = VecReporter { errors: vec![] };
+        let mut reporter = VecReporter::new();
         self.validate_all(&mut reporter);
-        if reporter.errors.is_empty() {
-            Ok(())
-        } else {
-            Err(reporter.errors)
-        }
+        reporter.into_result()
     }
 }
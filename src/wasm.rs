@@ -0,0 +1,98 @@
+use crate::{BmaModel, Validation};
+use serde_json::json;
+
+/// Validate a model given as a BMA JSON string, returning a JSON report.
+///
+/// The report has the following format:
+///
+/// ```json
+/// {
+///   "valid": false,
+///   "parse_error": null,
+///   "errors": [{ "code": "variable.range_invalid", "message": "..." }]
+/// }
+/// ```
+///
+/// If the input cannot be parsed, `parse_error` contains the error message and `errors`
+/// is empty. See [`crate::BmaModelError::code`] for the meaning of `code`.
+#[must_use]
+pub fn bma_validate_json(input: &str) -> String {
+    let report = match BmaModel::from_json_string(input) {
+        Err(e) => json!({
+            "valid": false,
+            "parse_error": e.to_string(),
+            "errors": [],
+        }),
+        Ok(model) => {
+            let errors = model.validate().err().unwrap_or_default();
+            let errors = errors
+                .iter()
+                .map(|e| json!({ "code": e.code(), "message": e.to_string() }))
+                .collect::<Vec<_>>();
+            json!({
+                "valid": errors.is_empty(),
+                "parse_error": null,
+                "errors": errors,
+            })
+        }
+    };
+    report.to_string()
+}
+
+/// Convert a model given as a BMA JSON (or XML) string into the AEON `.aeon` format.
+///
+/// Requires the `bn-conversion` feature.
+///
+/// The error is a human-readable message describing why the model could not be
+/// parsed or converted.
+#[cfg(feature = "bn-conversion")]
+pub fn bma_to_aeon(input: &str) -> Result<String, String> {
+    let model = BmaModel::from_json_string(input)
+        .or_else(|_| BmaModel::from_xml_string(input))
+        .map_err(|_| "Failed to parse BMA format (tried both JSON and XML)".to_string())?;
+    model.to_aeon_string().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "bn-conversion")]
+    use crate::wasm::bma_to_aeon;
+    use crate::wasm::bma_validate_json;
+    use crate::{BmaModel, BmaNetwork, BmaRelationship, BmaVariable};
+
+    fn model_json() -> String {
+        let network = BmaNetwork::new(
+            vec![
+                BmaVariable::new_boolean(1, "a", None),
+                BmaVariable::new_boolean(2, "b", None),
+            ],
+            vec![BmaRelationship::new_activator(3, 1, 2)],
+        );
+        let model = BmaModel {
+            network,
+            ..Default::default()
+        };
+        model.to_json_string().unwrap()
+    }
+
+    #[test]
+    fn validate_json_report() {
+        let report: serde_json::Value =
+            serde_json::from_str(&bma_validate_json(&model_json())).unwrap();
+        assert_eq!(report["valid"], true);
+        assert!(report["parse_error"].is_null());
+
+        let report: serde_json::Value =
+            serde_json::from_str(&bma_validate_json("not a model")).unwrap();
+        assert_eq!(report["valid"], false);
+        assert!(report["parse_error"].is_string());
+    }
+
+    #[test]
+    #[cfg(feature = "bn-conversion")]
+    fn convert_to_aeon() {
+        let aeon = bma_to_aeon(&model_json()).unwrap();
+        assert!(aeon.contains("v1_a_b1 -> v2_b_b1"));
+        assert!(bma_to_aeon("not a model").is_err());
+    }
+}